@@ -1,33 +1,138 @@
+use anchor_lang::system_program::{self, Transfer};
 use anchor_lang::{
     prelude::*,
-    solana_program::{program::invoke_signed, system_instruction},
+    solana_program::{
+        instruction::{get_stack_height, AccountMeta, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT},
+        program::{get_return_data, invoke, invoke_signed},
+        system_instruction,
+        sysvar::slot_hashes,
+    },
 };
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, MintTo, Token, TokenAccount},
+    token::{self, spl_token::instruction::AuthorityType, Mint, MintTo, Token, TokenAccount},
 };
-use vault_stub::{self, ADAPTER_RESERVE_SEED};
+use solana_instructions_sysvar::get_instruction_relative;
+use solana_sdk_ids::{ed25519_program, sysvar::instructions as instructions_sysvar};
+use solana_sha256_hasher::hashv;
 
 declare_id!("Akk9YtTtkqG9K8PdbqtKd2k6zDF2egc8xnkdMWD2nvaU");
 
-const EVENT_SEED: &[u8] = b"event";
-const PASS_SEED: &[u8] = b"event-pass";
-const VAULT_STATE_SEED: &[u8] = b"vault-state";
-const VAULT_TREASURY_SEED: &[u8] = b"vault-treasury";
-const LOYALTY_MINT_SEED: &[u8] = b"loyalty-mint";
+/// PDA seeds and derivation helpers, public so clients derive the same
+/// addresses as the account constraints instead of hardcoding byte strings.
+pub mod seeds {
+    use anchor_lang::prelude::Pubkey;
+
+    pub const EVENT_SEED: &[u8] = b"event";
+    pub const PASS_SEED: &[u8] = b"event-pass";
+    pub const VAULT_STATE_SEED: &[u8] = b"vault-state";
+    pub const VAULT_TREASURY_SEED: &[u8] = b"vault-treasury";
+    pub const LOYALTY_MINT_SEED: &[u8] = b"loyalty-mint";
+    pub const LOYALTY_AUTHORITY_SEED: &[u8] = b"loyalty-authority";
+    pub const LUCKY_MINT_SEED: &[u8] = b"lucky-mint";
+    pub const MANDATE_SEED: &[u8] = b"renew-mandate";
+    pub const CREDIT_SEED: &[u8] = b"credit";
+    pub const ORGANIZER_STATS_SEED: &[u8] = b"organizer-stats";
+    pub const GUEST_LIST_SEED: &[u8] = b"guest-list";
+    pub const PROTOCOL_CONFIG_SEED: &[u8] = b"protocol-config";
+    pub const ATTENDEE_RECORD_SEED: &[u8] = b"attendee-record";
+    pub const TOKEN_VAULT_SEED: &[u8] = b"token-vault";
+
+    pub fn event_pda(organizer: &Pubkey, event_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[EVENT_SEED, organizer.as_ref(), &event_id.to_le_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// `pass_index` is the event's `next_pass_index` at the time of the mint.
+    pub fn event_pass_pda(event: &Pubkey, owner: &Pubkey, pass_index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                PASS_SEED,
+                event.as_ref(),
+                owner.as_ref(),
+                &pass_index.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    pub fn vault_state_pda(event: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[VAULT_STATE_SEED, event.as_ref()], &crate::ID)
+    }
+
+    pub fn vault_treasury_pda(event: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[VAULT_TREASURY_SEED, event.as_ref()], &crate::ID)
+    }
+
+    pub fn loyalty_mint_pda(event_pass: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[LOYALTY_MINT_SEED, event_pass.as_ref()], &crate::ID)
+    }
+}
+
+use seeds::*;
 
 const MAX_TIER_COUNT: usize = 4;
-const MAX_VERIFIER_COUNT: usize = 5;
+/// Absolute ceiling for an event's configurable `max_verifiers`.
+const MAX_VERIFIER_COUNT: usize = 32;
+const MAX_BANNED_COUNT: usize = 16;
+const MAX_FORFEITURE_SHARES: usize = 4;
+/// Partial withdrawals kept in `VaultState::tranches`; older ones roll off.
+const MAX_TRANCHES: usize = 8;
 const MAX_NAME_LEN: usize = 64;
 const MAX_VENUE_LEN: usize = 64;
 const MAX_TIER_LABEL_LEN: usize = 32;
+const MAX_IMAGE_URI_LEN: usize = 200;
+const MAX_THUMBNAIL_URI_LEN: usize = 128;
+/// Most passes one `batch_check_in` may admit; each pass costs a
+/// deserialize, a write-back and an event, and 20 stays well under the
+/// default compute budget.
+const MAX_BATCH_CHECK_INS: usize = 20;
+/// Most passes one `refund_batch` may refund; each takes two accounts and
+/// closes one, so 10 keeps the transaction within its account limit.
+const MAX_BATCH_REFUNDS: usize = 10;
+/// Default time holders of a cancelled event have to claim their refund
+/// before `sweep_forfeitures` may pay it to the forfeiture split.
+const REFUND_CLAIM_WINDOW: i64 = 30 * 24 * 60 * 60;
+/// Largest event account `create_event` will allocate; accounts created from
+/// inside a program are capped at 10 KiB.
+const MAX_EVENT_SPACE: usize = 10_240;
+const SECONDS_PER_YEAR: u128 = 365 * 24 * 60 * 60;
+/// Longest span from `start_ts` to `end_ts`, extensions included.
+const MAX_EVENT_DURATION: i64 = 365 * 24 * 60 * 60;
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Perk bits for `TierConfig::benefits`, copied onto every pass so other
+/// programs (merch, parking, access control) can check entitlements by reading
+/// the pass. Bits above these are free for organizer-defined perks.
+pub const BENEFIT_BACKSTAGE: u32 = 1 << 0;
+pub const BENEFIT_MERCH: u32 = 1 << 1;
+pub const BENEFIT_PARKING: u32 = 1 << 2;
+pub const BENEFIT_LOUNGE: u32 = 1 << 3;
+pub const BENEFIT_FAST_LANE: u32 = 1 << 4;
+
+/// Anchor discriminator of `is_authorized`, i.e. `sha256("global:is_authorized")[..8]`.
+/// External check-in authorization programs must expose this entrypoint, take
+/// `[verifier (signer), event_pass, ..extra]` and return a borsh `bool`.
+const AUTHORIZE_IX_DISCRIMINATOR: [u8; 8] = [159, 169, 126, 64, 23, 72, 93, 151];
 
 #[program]
 pub mod anchor_project {
     use super::*;
 
     pub fn create_event(ctx: Context<CreateEvent>, args: CreateEventArgs) -> Result<()> {
-        args.validate()?;
+        args.validate(Clock::get()?.unix_timestamp)?;
+        // Settling into the event's own treasury would never pay anyone out.
+        let (vault_treasury, _) = Pubkey::find_program_address(
+            &[VAULT_TREASURY_SEED, ctx.accounts.event.key().as_ref()],
+            &crate::ID,
+        );
+        require_keys_neq!(
+            args.settlement_treasury,
+            vault_treasury,
+            EventFluxError::InvalidSettlementTreasury
+        );
 
         let CreateEventArgs {
             event_id,
@@ -39,6 +144,25 @@ pub mod anchor_project {
             yield_strategy,
             authorized_verifiers,
             tiers,
+            strict_tier_order,
+            auth_program,
+            loyalty_requires_checkin,
+            currency,
+            tiers_finalized,
+            metadata_hash,
+            loyalty_delay_secs,
+            max_reentries,
+            max_verifiers,
+            resale_royalty_bps,
+            max_resale_lamports,
+            allow_cpi,
+            image_uri,
+            thumbnail_uri,
+            allowlist_root,
+            max_passes_per_wallet,
+            referral_bps,
+            sale_start_ts,
+            attendee_share_bps,
         } = args;
 
         let event = &mut ctx.accounts.event;
@@ -48,29 +172,172 @@ pub mod anchor_project {
         event.settlement_treasury = settlement_treasury;
         event.name = name;
         event.venue = venue;
+        event.metadata_hash = metadata_hash;
+        event.image_uri = image_uri;
+        event.thumbnail_uri = thumbnail_uri;
         event.start_ts = start_ts;
         event.end_ts = end_ts;
         event.yield_strategy = yield_strategy;
         event.authorized_verifiers = authorized_verifiers;
+        event.max_verifiers = max_verifiers;
+        event.banned = Vec::new();
         event.tiers = tiers
             .into_iter()
             .map(TierConfig::from_input)
             .collect::<Result<Vec<_>>>()?;
+        event.strict_tier_order = strict_tier_order;
+        event.tiers_finalized = tiers_finalized;
+        event.auth_program = auth_program;
+        event.loyalty_requires_checkin = loyalty_requires_checkin;
+        event.loyalty_delay_secs = loyalty_delay_secs;
+        event.max_reentries = max_reentries;
+        event.resale_royalty_bps = resale_royalty_bps;
+        event.max_resale_lamports = max_resale_lamports;
+        event.allow_cpi = allow_cpi;
+        event.config_frozen = false;
+        event.disputed = false;
+        event.cancelled = false;
+        event.cancelled_at = None;
+        event.refund_claim_window = REFUND_CLAIM_WINDOW;
+        event.sales_paused = false;
+        event.refund_deadline = None;
+        event.allowlist_root = allowlist_root;
+        event.max_passes_per_wallet = max_passes_per_wallet;
+        event.referral_bps = referral_bps;
+        event.sale_start_ts = sale_start_ts;
+        event.attendee_share_bps = attendee_share_bps;
+        event.forfeiture_split = Vec::new();
+        event.currency = currency;
         event.total_passes = 0;
+        event.checked_in_count = 0;
+        event.next_pass_index = 0;
         event.vault_state = ctx.accounts.vault_state.key();
         event.settled = false;
 
+        let organizer_stats = &mut ctx.accounts.organizer_stats;
+        organizer_stats.bump = ctx.bumps.organizer_stats;
+        organizer_stats.organizer = event.organizer;
+
+        let guest_list = &mut ctx.accounts.guest_list;
+        guest_list.bump = ctx.bumps.guest_list;
+        guest_list.event = event.key();
+        guest_list.passes = Vec::new();
+
         let vault_state = &mut ctx.accounts.vault_state;
         vault_state.bump = ctx.bumps.vault_state;
         vault_state.event = event.key();
         vault_state.strategy = event.yield_strategy.clone();
+        vault_state.currency = event.currency.clone();
         vault_state.total_deposited = 0;
         vault_state.total_withdrawn = 0;
         vault_state.total_yield_harvested = 0;
-        vault_state.vault_treasury_bump = ctx.bumps.vault_treasury;
+        vault_state.vault_treasury_bump = 0;
+        vault_state.treasury_initialized = false;
         vault_state.last_harvest_ts = 0;
+        vault_state.surplus_lamports = 0;
+        vault_state.first_deposit_ts = 0;
+        vault_state.effective_apy_bps = 0;
+        vault_state.harvest_paused = false;
+        vault_state.deposits_held = 0;
+        vault_state.adapter_program = vault_stub::ID;
+        vault_state.deployed_principal = 0;
+        vault_state.yield_withdrawn = 0;
+        vault_state.dividend_per_pass = 0;
+        vault_state.fees_collected = 0;
+        vault_state.fees_refunded = 0;
+        vault_state.total_refunded = 0;
+        vault_state.fee_refunds_held = 0;
+        vault_state.tranches = Vec::new();
 
-        let event_key = event.key();
+        emit!(EventCreated {
+            event: event.key(),
+            organizer: event.organizer,
+            metadata_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Replaces the on-chain name and venue together with the hash of the
+    /// off-chain details they describe.
+    pub fn update_event_metadata(
+        ctx: Context<UpdateEventMetadata>,
+        name: String,
+        venue: String,
+        metadata_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(!name.is_empty(), EventFluxError::InvalidMetadata);
+        require!(!venue.is_empty(), EventFluxError::InvalidMetadata);
+        require!(name.len() <= MAX_NAME_LEN, EventFluxError::MetadataTooLong);
+        require!(
+            venue.len() <= MAX_VENUE_LEN,
+            EventFluxError::MetadataTooLong
+        );
+
+        let event = &mut ctx.accounts.event;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        require!(!event.settled, EventFluxError::AlreadySettled);
+
+        event.name = name;
+        event.venue = venue;
+        event.metadata_hash = metadata_hash;
+        let new_space = event.space();
+
+        emit!(EventUpdated {
+            event: event.key(),
+            metadata_hash,
+        });
+
+        resize_account(
+            &event.to_account_info(),
+            &ctx.accounts.organizer,
+            &ctx.accounts.system_program,
+            new_space,
+        )
+    }
+
+    /// Sets or clears the listing image and thumbnail, resizing the event to
+    /// fit.
+    pub fn set_event_images(
+        ctx: Context<SetEventImages>,
+        image_uri: Option<String>,
+        thumbnail_uri: Option<String>,
+    ) -> Result<()> {
+        validate_image_uris(&image_uri, &thumbnail_uri)?;
+
+        let event = &mut ctx.accounts.event;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        require!(!event.settled, EventFluxError::AlreadySettled);
+
+        event.image_uri = image_uri;
+        event.thumbnail_uri = thumbnail_uri;
+        let new_space = event.space();
+
+        emit!(EventImagesUpdated {
+            event: event.key(),
+            image_uri: event.image_uri.clone(),
+            thumbnail_uri: event.thumbnail_uri.clone(),
+        });
+
+        resize_account(
+            &event.to_account_info(),
+            &ctx.accounts.organizer,
+            &ctx.accounts.system_program,
+            new_space,
+        )
+    }
+
+    pub fn initialize_event_treasury(ctx: Context<InitializeEventTreasury>) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        require!(
+            !vault_state.treasury_initialized,
+            EventFluxError::TreasuryAlreadyInitialized
+        );
+
+        vault_state.vault_treasury_bump = ctx.bumps.vault_treasury;
+        vault_state.treasury_initialized = true;
+
+        let event_key = ctx.accounts.event.key();
         let signer_seeds: &[&[u8]] = &[
             VAULT_TREASURY_SEED,
             event_key.as_ref(),
@@ -80,495 +347,6300 @@ pub mod anchor_project {
         create_vault_treasury_if_needed(
             &ctx.accounts.organizer,
             &ctx.accounts.vault_treasury,
-            ctx.accounts.rent.minimum_balance(0),
+            Rent::get()?.minimum_balance(0),
             &ctx.accounts.system_program,
             signer_seeds,
-        )?;
+        )
+    }
 
-        Ok(())
+    /// `max_lamports` caps what the attendee is willing to pay at execution
+    /// time; zero disables the check.
+    /// `referrer` earns the event's `referral_bps` of the price and must also
+    /// be passed as the `referrer` account.
+    pub fn mint_pass(
+        ctx: Context<MintPass>,
+        tier_id: u8,
+        max_lamports: u64,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        mint_sol_pass(ctx, tier_id, max_lamports, None, referrer)
     }
 
-    pub fn mint_pass(ctx: Context<MintPass>, tier_id: u8) -> Result<()> {
-        let event = &mut ctx.accounts.event;
-        let vault_state = &mut ctx.accounts.vault_state;
-        let now = Clock::get()?.unix_timestamp;
+    /// Mints on an invite-only event. `proof` is the Merkle path from the
+    /// attendee's leaf to the event's `allowlist_root`.
+    pub fn mint_pass_allowlisted(
+        ctx: Context<MintPass>,
+        tier_id: u8,
+        max_lamports: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        mint_sol_pass(ctx, tier_id, max_lamports, Some(&proof), None)
+    }
 
-        require!(now < event.end_ts, EventFluxError::EventEnded);
+    /// Mints on an event priced in an SPL token. Tier prices are read in the
+    /// token's base units and paid into the event's token vault, whose
+    /// authority is the vault treasury PDA.
+    pub fn mint_pass_spl(ctx: Context<MintPassSpl>, tier_id: u8, max_price: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let tier_price = record_pass_mint(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.event_pass,
+            ctx.bumps.event_pass,
+            ctx.accounts.attendee.key(),
+            tier_id,
+            max_price,
+            now,
+            None,
+        )?;
+        // Deposits are returned in lamports, so token-priced tiers can't
+        // carry one.
+        require!(
+            ctx.accounts.event_pass.deposit_lamports == 0,
+            EventFluxError::CurrencyMismatch
+        );
+        record_wallet_mint(
+            &mut ctx.accounts.attendee_record,
+            ctx.bumps.attendee_record,
+            &ctx.accounts.event,
+            ctx.accounts.event.key(),
+            ctx.accounts.attendee.key(),
+        )?;
+        ctx.accounts.organizer_stats.record_pass()?;
+        append_guest(
+            &mut ctx.accounts.guest_list,
+            ctx.accounts.event_pass.key(),
+            &ctx.accounts.attendee,
+            &ctx.accounts.system_program,
+        )?;
 
-        let tier_price = {
-            let tier = event
-                .tiers
-                .iter_mut()
-                .find(|t| t.tier_id == tier_id)
-                .ok_or(EventFluxError::TierNotFound)?;
+        // The fee is charged in the event's token, to the collector's
+        // account for that mint.
+        let (fee, fee_collector) = protocol_fee(&ctx.accounts.protocol_config, tier_price)?;
+        if fee > 0 {
+            let collector_tokens = ctx
+                .accounts
+                .fee_collector_token_account
+                .as_ref()
+                .filter(|account| account.owner == fee_collector)
+                .ok_or(EventFluxError::InvalidFeeCollector)?;
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.attendee_token_account.to_account_info(),
+                        to: collector_tokens.to_account_info(),
+                        authority: ctx.accounts.attendee.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+        ctx.accounts.event_pass.fee_paid = fee;
+        ctx.accounts.vault_state.record_fee(fee)?;
+        let net_price = ctx.accounts.event_pass.treasury_share()?;
 
-            require!(tier.sold < tier.max_supply, EventFluxError::TierSoldOut);
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.attendee_token_account.to_account_info(),
+                    to: ctx.accounts.token_vault.to_account_info(),
+                    authority: ctx.accounts.attendee.to_account_info(),
+                },
+            ),
+            net_price,
+        )?;
 
-            tier.sold = tier
-                .sold
-                .checked_add(1)
-                .ok_or(EventFluxError::MathOverflow)?;
-            tier.price_lamports
-        };
+        record_deposit(&mut ctx.accounts.vault_state, net_price, now)
+    }
 
-        event.total_passes = event
-            .total_passes
-            .checked_add(1)
+    /// Mints a pass owned by `beneficiary` but held by the signing custodian,
+    /// for platforms onboarding users who don't have a wallet yet. The
+    /// custodian pays and can present the pass at check-in; only the
+    /// beneficiary can transfer it or end custody with `claim_custody`.
+    pub fn mint_pass_in_custody(
+        ctx: Context<MintPassInCustody>,
+        tier_id: u8,
+        max_lamports: u64,
+        beneficiary: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.vault_state.currency.require_sol()?;
+        let now = Clock::get()?.unix_timestamp;
+        let tier_price = record_pass_mint(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.event_pass,
+            ctx.bumps.event_pass,
+            beneficiary,
+            tier_id,
+            max_lamports,
+            now,
+            None,
+        )?;
+        ctx.accounts.event_pass.custodian = Some(ctx.accounts.custodian.key());
+        // The cap follows the owner, not whoever pays for the pass.
+        record_wallet_mint(
+            &mut ctx.accounts.attendee_record,
+            ctx.bumps.attendee_record,
+            &ctx.accounts.event,
+            ctx.accounts.event.key(),
+            beneficiary,
+        )?;
+        ctx.accounts.organizer_stats.record_pass()?;
+        append_guest(
+            &mut ctx.accounts.guest_list,
+            ctx.accounts.event_pass.key(),
+            &ctx.accounts.custodian,
+            &ctx.accounts.system_program,
+        )?;
+        let fee = pay_protocol_fee(
+            &ctx.accounts.protocol_config,
+            ctx.accounts.fee_collector.as_ref(),
+            &ctx.accounts.custodian.to_account_info(),
+            tier_price,
+        )?;
+        ctx.accounts.event_pass.fee_paid = fee;
+        ctx.accounts.vault_state.record_fee(fee)?;
+        let net_price = ctx.accounts.event_pass.treasury_share()?;
+        let deposit = ctx.accounts.event_pass.deposit_lamports;
+        let charge = net_price
+            .checked_add(deposit)
             .ok_or(EventFluxError::MathOverflow)?;
 
-        let event_pass = &mut ctx.accounts.event_pass;
-        event_pass.bump = ctx.bumps.event_pass;
-        event_pass.event = event.key();
-        event_pass.owner = ctx.accounts.attendee.key();
-        event_pass.tier_id = tier_id;
-        event_pass.price_paid = tier_price;
-        event_pass.minted_at = now;
-        event_pass.checked_in = false;
-        event_pass.checked_in_at = None;
-        event_pass.loyalty_mint = None;
-
-        invoke_signed(
-            &system_instruction::transfer(
-                &ctx.accounts.attendee.key(),
-                &ctx.accounts.vault_treasury.key(),
-                tier_price,
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.custodian.to_account_info(),
+                    to: ctx.accounts.vault_treasury.to_account_info(),
+                },
             ),
-            &[
-                ctx.accounts.attendee.to_account_info(),
-                ctx.accounts.vault_treasury.to_account_info(),
-            ],
-            &[],
+            charge,
         )?;
 
-        vault_state.total_deposited = vault_state
-            .total_deposited
-            .checked_add(tier_price)
-            .ok_or(EventFluxError::MathOverflow)?;
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.hold_deposit(deposit)?;
+        record_deposit(vault_state, net_price, now)
+    }
 
+    /// Ends custody so only the beneficial owner controls the pass.
+    pub fn claim_custody(ctx: Context<ClaimCustody>) -> Result<()> {
+        let event_pass = &mut ctx.accounts.event_pass;
+        require!(event_pass.custodian.is_some(), EventFluxError::NotInCustody);
+        event_pass.custodian = None;
         Ok(())
     }
 
-    pub fn check_in(ctx: Context<CheckIn>) -> Result<()> {
+    pub fn deposit_credit(ctx: Context<DepositCredit>, amount: u64) -> Result<()> {
+        require!(amount > 0, EventFluxError::InvalidCreditAmount);
+        ctx.accounts.vault_state.currency.require_sol()?;
+
         let event = &ctx.accounts.event;
-        let event_pass = &mut ctx.accounts.event_pass;
         let now = Clock::get()?.unix_timestamp;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(now < event.end_ts, EventFluxError::EventEnded);
 
-        require!(now >= event.start_ts, EventFluxError::EventNotStarted);
-        require!(now <= event.end_ts, EventFluxError::EventEnded);
-
-        let verifier = ctx.accounts.verifier.key();
-        let is_authorized = verifier == event.organizer
-            || event.authorized_verifiers.iter().any(|v| v == &verifier)
-            || verifier == event_pass.owner;
-        require!(is_authorized, EventFluxError::UnauthorizedVerifier);
-        require!(!event_pass.checked_in, EventFluxError::AlreadyCheckedIn);
+        let credit = &mut ctx.accounts.credit_account;
+        credit.bump = ctx.bumps.credit_account;
+        credit.event = event.key();
+        credit.owner = ctx.accounts.attendee.key();
+        credit.balance = credit
+            .balance
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
 
-        event_pass.checked_in = true;
-        event_pass.checked_in_at = Some(now);
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.attendee.to_account_info(),
+                    to: ctx.accounts.vault_treasury.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
 
-        Ok(())
+        record_deposit(&mut ctx.accounts.vault_state, amount, now)
     }
 
-    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>) -> Result<()> {
-        let event = &mut ctx.accounts.event;
-        let vault_state = &mut ctx.accounts.vault_state;
+    /// Same as `mint_pass`, but paid from the attendee's prepaid credit. The
+    /// lamports already sit in the treasury and were counted in
+    /// `total_deposited` when the credit was deposited.
+    pub fn mint_pass_with_credit(
+        ctx: Context<MintPassWithCredit>,
+        tier_id: u8,
+        max_lamports: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let tier_price = record_pass_mint(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.event_pass,
+            ctx.bumps.event_pass,
+            ctx.accounts.attendee.key(),
+            tier_id,
+            max_lamports,
+            now,
+            None,
+        )?;
+        record_wallet_mint(
+            &mut ctx.accounts.attendee_record,
+            ctx.bumps.attendee_record,
+            &ctx.accounts.event,
+            ctx.accounts.event.key(),
+            ctx.accounts.attendee.key(),
+        )?;
+        ctx.accounts.organizer_stats.record_pass()?;
+        append_guest(
+            &mut ctx.accounts.guest_list,
+            ctx.accounts.event_pass.key(),
+            &ctx.accounts.attendee,
+            &ctx.accounts.system_program,
+        )?;
+        let deposit = ctx.accounts.event_pass.deposit_lamports;
+        let charge = tier_price
+            .checked_add(deposit)
+            .ok_or(EventFluxError::MathOverflow)?;
 
-        require!(!event.settled, EventFluxError::AlreadySettled);
-        require!(
-            Clock::get()?.unix_timestamp >= event.end_ts,
-            EventFluxError::EventNotEnded
-        );
+        let credit = &mut ctx.accounts.credit_account;
+        credit.balance = credit
+            .balance
+            .checked_sub(charge)
+            .ok_or(EventFluxError::InsufficientCredit)?;
 
-        let balance = ctx.accounts.vault_treasury.lamports();
-        require!(balance > 0, EventFluxError::NothingToWithdraw);
+        // The credit already sits in the treasury, so the fee is paid from
+        // there.
+        let fee = pay_protocol_fee(
+            &ctx.accounts.protocol_config,
+            ctx.accounts.fee_collector.as_ref(),
+            &ctx.accounts.vault_treasury.to_account_info(),
+            tier_price,
+        )?;
+        ctx.accounts.event_pass.fee_paid = fee;
+        ctx.accounts.vault_state.record_fee(fee)?;
+
+        // The whole credit was counted as revenue when it was deposited; the
+        // fee has left the treasury and the deposit moves over to the
+        // refundable bucket.
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.total_deposited = vault_state
+            .total_deposited
+            .checked_sub(deposit)
+            .and_then(|total| total.checked_sub(fee))
+            .ok_or(EventFluxError::MathOverflow)?;
+        vault_state.hold_deposit(deposit)
+    }
 
+    /// Returns a pass's refundable deposit to its owner once they have
+    /// checked in. Deposits still held at settlement are forfeited to the
+    /// organizer.
+    pub fn reclaim_deposit(ctx: Context<ReclaimDeposit>) -> Result<()> {
+        require_top_level(&ctx.accounts.event)?;
+        require!(!ctx.accounts.event.settled, EventFluxError::AlreadySettled);
+        let event_pass = &mut ctx.accounts.event_pass;
+        require!(event_pass.checked_in, EventFluxError::PassNotCheckedIn);
+        let deposit = event_pass.deposit_lamports;
+        require!(deposit > 0, EventFluxError::NoDepositToReclaim);
+        event_pass.deposit_lamports = 0;
+
+        debit_treasury(&ctx.accounts.vault_treasury.to_account_info(), deposit)?;
         **ctx
             .accounts
-            .vault_treasury
+            .owner
             .to_account_info()
-            .try_borrow_mut_lamports()? -= balance;
+            .try_borrow_mut_lamports()? += deposit;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.deposits_held = vault_state
+            .deposits_held
+            .checked_sub(deposit)
+            .ok_or(EventFluxError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn withdraw_credit(ctx: Context<WithdrawCredit>, amount: u64) -> Result<()> {
+        require_top_level(&ctx.accounts.event)?;
+        require!(amount > 0, EventFluxError::InvalidCreditAmount);
+        require!(!ctx.accounts.event.settled, EventFluxError::AlreadySettled);
+
+        let credit = &mut ctx.accounts.credit_account;
+        credit.balance = credit
+            .balance
+            .checked_sub(amount)
+            .ok_or(EventFluxError::InsufficientCredit)?;
+
+        debit_treasury(&ctx.accounts.vault_treasury.to_account_info(), amount)?;
         **ctx
             .accounts
-            .destination
+            .attendee
             .to_account_info()
-            .try_borrow_mut_lamports()? += balance;
+            .try_borrow_mut_lamports()? += amount;
 
-        vault_state.total_withdrawn = vault_state
-            .total_withdrawn
-            .checked_add(balance)
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.total_deposited = vault_state
+            .total_deposited
+            .checked_sub(amount)
             .ok_or(EventFluxError::MathOverflow)?;
-        event.settled = true;
 
         Ok(())
     }
 
-    pub fn harvest_yield(ctx: Context<HarvestYield>, amount: u64) -> Result<()> {
-        require!(amount > 0, EventFluxError::InvalidHarvestAmount);
+    pub fn check_in<'info>(ctx: Context<'_, '_, '_, 'info, CheckIn<'info>>) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let now = Clock::get()?.unix_timestamp;
+
+        require_keys_eq!(
+            ctx.accounts.event_pass.event,
+            event.key(),
+            EventFluxError::PassEventMismatch
+        );
+        if let Some(denial) = check_in_denial(
+            event,
+            &ctx.accounts.event_pass,
+            &ctx.accounts.verifier.to_account_info(),
+            ctx.remaining_accounts,
+            now,
+        )? {
+            return Err(denial.to_error().into());
+        }
+
+        let event_key = event.key();
+        record_entry(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.event_pass,
+            &mut ctx.accounts.organizer_stats,
+            now,
+        )?;
+        emit!(PassCheckedIn {
+            event: event_key,
+            pass: ctx.accounts.event_pass.key(),
+            verifier: ctx.accounts.verifier.key(),
+            ts: now,
+        });
+
+        Ok(())
+    }
 
+    /// Uploads a scan made offline, stamping the pass with when the scan
+    /// actually happened. Every `check_in` rule is evaluated as of that time,
+    /// and only a verifier other than the holder may upload, so holders can't
+    /// backdate their own entry.
+    pub fn check_in_with_time<'info>(
+        ctx: Context<'_, '_, '_, 'info, CheckIn<'info>>,
+        actual_checkin_ts: i64,
+    ) -> Result<()> {
         let event = &ctx.accounts.event;
-        require_keys_eq!(ctx.accounts.organizer.key(), event.organizer);
-        require!(!event.settled, EventFluxError::AlreadySettled);
+        let verifier_key = ctx.accounts.verifier.key();
         require!(
-            !matches!(event.yield_strategy, YieldStrategy::None),
-            EventFluxError::NoYieldStrategy,
+            actual_checkin_ts <= Clock::get()?.unix_timestamp,
+            EventFluxError::CheckinInFuture
         );
+        require!(
+            !ctx.accounts.event_pass.held_by(&verifier_key),
+            EventFluxError::UnauthorizedVerifier
+        );
+        if let Some(denial) = check_in_denial(
+            event,
+            &ctx.accounts.event_pass,
+            &ctx.accounts.verifier.to_account_info(),
+            ctx.remaining_accounts,
+            actual_checkin_ts,
+        )? {
+            return Err(denial.to_error().into());
+        }
 
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.vault_adapter_program.to_account_info(),
-            vault_stub::cpi::accounts::Harvest {
-                adapter: ctx.accounts.adapter_reserve.to_account_info(),
-                destination: ctx.accounts.vault_treasury.to_account_info(),
-            },
+        record_entry(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.event_pass,
+            &mut ctx.accounts.organizer_stats,
+            actual_checkin_ts,
+        )
+    }
+
+    /// Admits a pass on a verifier's ed25519 signature instead of their
+    /// transaction signature, so scans collected offline can be relayed by
+    /// anyone. The instruction right before this one must be an ed25519
+    /// program check of `event_pass || pass_index (u64 LE) || checkin_nonce
+    /// (u32 LE) || signed_at (i64 LE)` by the organizer or an authorized
+    /// verifier. Only first entries can be signed, so a relayed signature can
+    /// never be replayed as a re-entry, and undoing a check-in bumps the
+    /// nonce so it can't be replayed after a reset either.
+    pub fn check_in_signed(ctx: Context<CheckInSigned>) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let pass_key = ctx.accounts.event_pass.key();
+        let (verifier, signed_at) = signed_check_in(
+            &ctx.accounts.instructions_sysvar.to_account_info(),
+            &pass_key,
+            &ctx.accounts.event_pass,
+        )?;
+        require!(
+            signed_at <= Clock::get()?.unix_timestamp,
+            EventFluxError::CheckinInFuture
+        );
+        require!(
+            !ctx.accounts.event_pass.checked_in,
+            EventFluxError::AlreadyCheckedIn
+        );
+        require!(
+            verifier == event.organizer || event.authorized_verifiers.contains(&verifier),
+            EventFluxError::UnauthorizedVerifier
+        );
+        require!(
+            !ctx.accounts.event_pass.held_by(&verifier),
+            EventFluxError::UnauthorizedVerifier
         );
+        if let Some(denial) = schedule_denial(event, signed_at) {
+            return Err(denial.to_error().into());
+        }
+        if let Some(denial) = pass_denial(event, &ctx.accounts.event_pass, signed_at)? {
+            return Err(denial.to_error().into());
+        }
 
-        vault_stub::cpi::harvest(cpi_ctx, amount)?;
+        let event_key = event.key();
+        record_entry(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.event_pass,
+            &mut ctx.accounts.organizer_stats,
+            signed_at,
+        )?;
+        emit!(PassCheckedIn {
+            event: event_key,
+            pass: pass_key,
+            verifier,
+            ts: signed_at,
+        });
 
-        let vault_state = &mut ctx.accounts.vault_state;
-        vault_state.total_yield_harvested = vault_state
-            .total_yield_harvested
-            .checked_add(amount)
-            .ok_or(EventFluxError::MathOverflow)?;
-        vault_state.last_harvest_ts = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Admits up to `MAX_BATCH_CHECK_INS` passes passed as writable remaining
+    /// accounts. Only the organizer or, on events without an auth program, a
+    /// listed verifier may batch. Passes already checked in are skipped rather
+    /// than failing the batch; any other broken rule aborts it.
+    pub fn batch_check_in<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchCheckIn<'info>>,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        let verifier = ctx.accounts.verifier.key();
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            !ctx.remaining_accounts.is_empty()
+                && ctx.remaining_accounts.len() <= MAX_BATCH_CHECK_INS,
+            EventFluxError::InvalidBatchSize
+        );
+        require!(
+            verifier == event.organizer
+                || (event.auth_program.is_none() && event.authorized_verifiers.contains(&verifier)),
+            EventFluxError::UnauthorizedVerifier
+        );
+        if let Some(denial) = schedule_denial(event, now) {
+            return Err(denial.to_error().into());
+        }
+
+        let mut checked_in: u8 = 0;
+        for pass_info in ctx.remaining_accounts {
+            let mut event_pass = Account::<EventPass>::try_from(pass_info)?;
+            require_keys_eq!(
+                event_pass.event,
+                event.key(),
+                EventFluxError::PassEventMismatch
+            );
+            if event_pass.checked_in {
+                continue;
+            }
+            if let Some(denial) = pass_denial(event, &event_pass, now)? {
+                return Err(denial.to_error().into());
+            }
+
+            record_entry(
+                event,
+                &mut event_pass,
+                &mut ctx.accounts.organizer_stats,
+                now,
+            )?;
+            // Written back before the next pass so a duplicate entry in the
+            // batch reads it as checked in and is skipped.
+            event_pass.exit(&crate::ID)?;
+            checked_in += 1;
+            emit!(PassCheckedIn {
+                event: event.key(),
+                pass: pass_info.key(),
+                verifier,
+                ts: now,
+            });
+        }
 
+        emit!(BatchCheckedIn {
+            event: event.key(),
+            verifier,
+            checked_in,
+            skipped: ctx.remaining_accounts.len() as u8 - checked_in,
+        });
         Ok(())
     }
 
-    pub fn issue_loyalty_nft(ctx: Context<IssueLoyaltyNft>) -> Result<()> {
-        let event_pass = &mut ctx.accounts.event_pass;
+    /// Runs every `check_in` rule without marking the pass, so scanner apps
+    /// can show the attendee why entry would be refused.
+    pub fn check_in_dry_run<'info>(
+        ctx: Context<'_, '_, '_, 'info, CheckInDryRun<'info>>,
+    ) -> Result<CheckInEligibility> {
+        let denial = check_in_denial(
+            &ctx.accounts.event,
+            &ctx.accounts.event_pass,
+            &ctx.accounts.verifier.to_account_info(),
+            ctx.remaining_accounts,
+            Clock::get()?.unix_timestamp,
+        )?;
 
-        require!(event_pass.checked_in, EventFluxError::PassNotCheckedIn);
+        Ok(CheckInEligibility {
+            eligible: denial.is_none(),
+            denial,
+        })
+    }
+
+    /// Door sale: mints a pass to `attendee` and admits it in the same
+    /// instruction. `operator` must be able to scan other people's passes,
+    /// and `payer` (the attendee or the operator) covers the price.
+    pub fn pay_and_checkin<'info>(
+        ctx: Context<'_, '_, '_, 'info, PayAndCheckin<'info>>,
+        tier_id: u8,
+    ) -> Result<()> {
+        ctx.accounts.vault_state.currency.require_sol()?;
+        let now = Clock::get()?.unix_timestamp;
         require!(
-            event_pass.loyalty_mint.is_none(),
-            EventFluxError::LoyaltyAlreadyIssued
+            now >= ctx.accounts.event.start_ts,
+            EventFluxError::EventNotStarted
         );
+        require_keys_neq!(
+            ctx.accounts.operator.key(),
+            ctx.accounts.attendee.key(),
+            EventFluxError::UnauthorizedVerifier
+        );
+
+        let tier_price = record_pass_mint(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.event_pass,
+            ctx.bumps.event_pass,
+            ctx.accounts.attendee.key(),
+            tier_id,
+            0,
+            now,
+            None,
+        )?;
+        record_wallet_mint(
+            &mut ctx.accounts.attendee_record,
+            ctx.bumps.attendee_record,
+            &ctx.accounts.event,
+            ctx.accounts.event.key(),
+            ctx.accounts.attendee.key(),
+        )?;
+        if let Some(denial) = check_in_denial(
+            &ctx.accounts.event,
+            &ctx.accounts.event_pass,
+            &ctx.accounts.operator.to_account_info(),
+            ctx.remaining_accounts,
+            now,
+        )? {
+            return Err(denial.to_error().into());
+        }
+
+        ctx.accounts.organizer_stats.record_pass()?;
+        record_entry(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.event_pass,
+            &mut ctx.accounts.organizer_stats,
+            now,
+        )?;
+        append_guest(
+            &mut ctx.accounts.guest_list,
+            ctx.accounts.event_pass.key(),
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+        let fee = pay_protocol_fee(
+            &ctx.accounts.protocol_config,
+            ctx.accounts.fee_collector.as_ref(),
+            &ctx.accounts.payer.to_account_info(),
+            tier_price,
+        )?;
+        ctx.accounts.event_pass.fee_paid = fee;
+        ctx.accounts.vault_state.record_fee(fee)?;
+        let net_price = ctx.accounts.event_pass.treasury_share()?;
+        let deposit = ctx.accounts.event_pass.deposit_lamports;
+        let charge = net_price
+            .checked_add(deposit)
+            .ok_or(EventFluxError::MathOverflow)?;
 
-        token::mint_to(
+        system_program::transfer(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    mint: ctx.accounts.loyalty_mint.to_account_info(),
-                    to: ctx.accounts.loyalty_token_account.to_account_info(),
-                    authority: ctx.accounts.organizer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.vault_treasury.to_account_info(),
                 },
             ),
-            1,
+            charge,
         )?;
 
-        event_pass.loyalty_mint = Some(ctx.accounts.loyalty_mint.key());
-
-        Ok(())
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.hold_deposit(deposit)?;
+        record_deposit(vault_state, net_price, now)
     }
-}
 
-fn create_vault_treasury_if_needed<'info>(
-    payer: &Signer<'info>,
-    vault_treasury: &UncheckedAccount<'info>,
-    rent: u64,
-    system_program: &Program<'info, System>,
-    signer_seeds: &[&[u8]],
-) -> Result<()> {
-    if vault_treasury.to_account_info().owner == &crate::ID {
-        return Ok(());
-    }
+    /// Settles the event by paying the whole treasury, rent reserve
+    /// included, to `destination`. The emptied treasury is closed on purpose
+    /// and handed back to the system program rather than left below
+    /// rent-exemption; settlement state lives on `event` and `vault_state`.
+    /// When the event shares yield with attendees, their dividends and the
+    /// rent reserve stay behind instead and the treasury remains open for
+    /// `claim_yield_dividend`. Cancelled events never settle: their treasury
+    /// backs the refunds owed to holders.
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        let vault_state = &mut ctx.accounts.vault_state;
 
-    let ix = system_instruction::create_account(
-        &payer.key(),
-        &vault_treasury.key(),
-        rent,
-        0,
+        require_top_level(event)?;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(!event.cancelled, EventFluxError::EventCancelled);
+        require!(!event.disputed, EventFluxError::EventDisputed);
+        require!(
+            Clock::get()?.unix_timestamp >= event.end_ts,
+            EventFluxError::EventNotEnded
+        );
+        require!(
+            vault_state.deployed_principal == 0,
+            EventFluxError::PrincipalStillDeployed
+        );
+
+        let breakdown = settlement_breakdown(
+            event,
+            vault_state,
+            ctx.accounts.vault_treasury.lamports(),
+            Rent::get()?.minimum_balance(0),
+        )?;
+        let payout = breakdown.total_payout;
+
+        let treasury = ctx.accounts.vault_treasury.to_account_info();
+        **treasury.try_borrow_mut_lamports()? -= payout;
+        **ctx
+            .accounts
+            .destination
+            .to_account_info()
+            .try_borrow_mut_lamports()? += payout;
+        if breakdown.attendee_dividends == 0 {
+            treasury.assign(&system_program::ID);
+        }
+        vault_state.dividend_per_pass = event.dividend_per_pass(vault_state.unclaimed_yield());
+
+        vault_state.total_withdrawn = vault_state
+            .total_withdrawn
+            .checked_add(payout)
+            .ok_or(EventFluxError::MathOverflow)?;
+        let organizer_stats = &mut ctx.accounts.organizer_stats;
+        organizer_stats.lifetime_revenue = organizer_stats
+            .lifetime_revenue
+            .checked_add(payout)
+            .ok_or(EventFluxError::MathOverflow)?;
+        event.settled = true;
+        emit!(TreasurySettled {
+            event: event.key(),
+            amount: payout,
+            destination: ctx.accounts.destination.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Pays the treasury's share of a pass's price and any deposit still held
+    /// back to its owner, releasing its seat in the tier. The pass is closed
+    /// to the owner, so it can't be refunded twice. Every pass is refundable
+    /// once the event is cancelled, until its `refund_claim_window` runs out;
+    /// before that, only unused passes in
+    /// refundable tiers and only until the organizer's `refund_deadline`.
+    pub fn refund_pass(ctx: Context<RefundPass>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require_top_level(event)?;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        let now = Clock::get()?.unix_timestamp;
+        if event.cancelled {
+            require!(
+                !event.refund_claim_closed(now),
+                EventFluxError::RefundWindowClosed
+            );
+        } else {
+            require!(
+                event.refund_deadline.is_some_and(|deadline| now < deadline),
+                EventFluxError::EventNotCancelled
+            );
+            let event_pass = &ctx.accounts.event_pass;
+            require!(!event_pass.checked_in, EventFluxError::AlreadyCheckedIn);
+            let tier = event
+                .tiers
+                .iter()
+                .find(|t| t.tier_id == event_pass.tier_id)
+                .ok_or(EventFluxError::TierNotFound)?;
+            require!(tier.refundable, EventFluxError::TierNonRefundable);
+        }
+
+        refund_to_owner(
+            event,
+            &mut ctx.accounts.vault_state,
+            &mut ctx.accounts.organizer_stats,
+            &mut ctx.accounts.guest_list,
+            &ctx.accounts.vault_treasury.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.event_pass,
+        )?;
+        Ok(())
+    }
+
+    /// Permissionless crank that refunds up to `MAX_BATCH_REFUNDS` passes on
+    /// a cancelled event, so holders don't each have to sign `refund_pass`.
+    /// Remaining accounts come in `[event_pass, owner]` pairs; each pass is
+    /// refunded and closed to its owner. Passes already refunded (closed) or
+    /// checked in are skipped instead of failing the batch.
+    pub fn refund_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundBatch<'info>>,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require_top_level(event)?;
+        require!(event.cancelled, EventFluxError::EventNotCancelled);
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(
+            !event.refund_claim_closed(Clock::get()?.unix_timestamp),
+            EventFluxError::RefundWindowClosed
+        );
+        let accounts = ctx.remaining_accounts;
+        require!(
+            !accounts.is_empty()
+                && accounts.len().is_multiple_of(2)
+                && accounts.len() / 2 <= MAX_BATCH_REFUNDS,
+            EventFluxError::InvalidBatchSize
+        );
+
+        let treasury = ctx.accounts.vault_treasury.to_account_info();
+        let mut refunded: u8 = 0;
+        let mut amount: u64 = 0;
+        for pair in accounts.chunks(2) {
+            let (pass_info, owner_info) = (&pair[0], &pair[1]);
+            // A pass closed by an earlier refund, here or in this batch.
+            if pass_info.owner != &crate::ID || pass_info.data_is_empty() {
+                continue;
+            }
+            let event_pass = Account::<EventPass>::try_from(pass_info)?;
+            require_keys_eq!(
+                event_pass.event,
+                event.key(),
+                EventFluxError::PassEventMismatch
+            );
+            require_keys_eq!(
+                owner_info.key(),
+                event_pass.owner,
+                EventFluxError::PassOwnerMismatch
+            );
+            if event_pass.checked_in {
+                continue;
+            }
+
+            let refund = refund_to_owner(
+                event,
+                &mut ctx.accounts.vault_state,
+                &mut ctx.accounts.organizer_stats,
+                &mut ctx.accounts.guest_list,
+                &treasury,
+                owner_info,
+                &event_pass,
+            )?;
+            event_pass.close(owner_info.clone())?;
+            refunded += 1;
+            amount = amount
+                .checked_add(refund)
+                .ok_or(EventFluxError::MathOverflow)?;
+        }
+
+        emit!(BatchRefunded {
+            event: event.key(),
+            refunded,
+            skipped: (accounts.len() / 2) as u8 - refunded,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Draws `amount` of the proceeds without settling, so organizers can take
+    /// revenue in stages. The rent reserve and any deposits holders can still
+    /// reclaim stay in the treasury; `withdraw_treasury` settles the rest.
+    pub fn withdraw_treasury_partial(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        let event = &ctx.accounts.event;
+        require_top_level(event)?;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(!event.disputed, EventFluxError::EventDisputed);
+        require!(!event.cancelled, EventFluxError::EventCancelled);
+        require!(
+            Clock::get()?.unix_timestamp >= event.end_ts,
+            EventFluxError::EventNotEnded
+        );
+        require!(amount > 0, EventFluxError::NothingToWithdraw);
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        require!(
+            vault_state.deployed_principal == 0,
+            EventFluxError::PrincipalStillDeployed
+        );
+        let treasury = ctx.accounts.vault_treasury.to_account_info();
+        let available = treasury
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(treasury.data_len()))
+            .saturating_sub(vault_state.deposits_held);
+        require!(
+            amount <= available,
+            EventFluxError::WithdrawalExceedsAvailable
+        );
+
+        debit_treasury(&treasury, amount)?;
+        **ctx
+            .accounts
+            .destination
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+
+        vault_state.record_tranche(Tranche {
+            amount,
+            ts: Clock::get()?.unix_timestamp,
+            destination: ctx.accounts.destination.key(),
+        });
+        vault_state.total_withdrawn = vault_state
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
+        let organizer_stats = &mut ctx.accounts.organizer_stats;
+        organizer_stats.lifetime_revenue = organizer_stats
+            .lifetime_revenue
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Pays a checked-in pass its share of the vault's yield once the event
+    /// is settled: `attendee_share_bps` of the unclaimed yield at settlement,
+    /// split evenly across every checked-in pass. Each pass claims once.
+    pub fn claim_yield_dividend(ctx: Context<ClaimYieldDividend>) -> Result<()> {
+        let event = &ctx.accounts.event;
+        require_top_level(event)?;
+        require!(event.settled, EventFluxError::EventNotSettled);
+
+        let event_pass = &mut ctx.accounts.event_pass;
+        require!(event_pass.checked_in, EventFluxError::PassNotCheckedIn);
+        require!(
+            !event_pass.dividend_claimed,
+            EventFluxError::DividendAlreadyClaimed
+        );
+        let vault_state = &mut ctx.accounts.vault_state;
+        let amount = vault_state.dividend_per_pass;
+        require!(amount > 0, EventFluxError::NothingToWithdraw);
+
+        debit_treasury(&ctx.accounts.vault_treasury.to_account_info(), amount)?;
+        **ctx
+            .accounts
+            .owner
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+
+        event_pass.dividend_claimed = true;
+        vault_state.total_withdrawn = vault_state
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
+        emit!(YieldDividendClaimed {
+            event: event.key(),
+            pass: event_pass.key(),
+            owner: event_pass.owner,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Sweeps lamports that land in the treasury after settlement to the
+    /// settlement treasury, closing the account. Only balances up to the
+    /// rent-exempt minimum count as dust; anything larger is a real deposit
+    /// that needs looking at.
+    pub fn withdraw_dust(ctx: Context<WithdrawDust>) -> Result<u64> {
+        require_top_level(&ctx.accounts.event)?;
+        require!(ctx.accounts.event.settled, EventFluxError::EventNotSettled);
+
+        let treasury = ctx.accounts.vault_treasury.to_account_info();
+        let dust = treasury.lamports();
+        require!(dust > 0, EventFluxError::NothingToWithdraw);
+        require!(
+            dust <= Rent::get()?.minimum_balance(0),
+            EventFluxError::DustAboveThreshold
+        );
+
+        // Settlement closes the treasury and hands the address back to the
+        // system program, so later transfers leave it system-owned. Either
+        // way the sweep empties the account, so there's no rent to keep.
+        if treasury.owner == &crate::ID {
+            let remaining = treasury
+                .lamports()
+                .checked_sub(dust)
+                .ok_or(EventFluxError::MathOverflow)?;
+            **treasury.try_borrow_mut_lamports()? = remaining;
+            **ctx
+                .accounts
+                .destination
+                .to_account_info()
+                .try_borrow_mut_lamports()? += dust;
+        } else {
+            let event_key = ctx.accounts.event.key();
+            let signer_seeds: &[&[u8]] = &[
+                VAULT_TREASURY_SEED,
+                event_key.as_ref(),
+                &[ctx.accounts.vault_state.vault_treasury_bump],
+            ];
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: treasury,
+                        to: ctx.accounts.destination.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                dust,
+            )?;
+        }
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.total_withdrawn = vault_state
+            .total_withdrawn
+            .checked_add(dust)
+            .ok_or(EventFluxError::MathOverflow)?;
+
+        Ok(dust)
+    }
+
+    /// Sets who receives no-show deposits swept by `sweep_forfeitures`. The
+    /// shares must add up to 10_000 bps; an empty split leaves forfeited
+    /// deposits in the treasury to be paid out at settlement.
+    pub fn set_forfeiture_split(
+        ctx: Context<SetForfeitureSplit>,
+        split: Vec<ForfeitureShare>,
+    ) -> Result<()> {
+        require!(
+            split.len() <= MAX_FORFEITURE_SHARES,
+            EventFluxError::InvalidForfeitureSplit
+        );
+        require!(
+            split.is_empty()
+                || split
+                    .iter()
+                    .map(|share| u128::from(share.bps))
+                    .sum::<u128>()
+                    == BPS_DENOMINATOR,
+            EventFluxError::InvalidForfeitureSplit
+        );
+
+        let event = &mut ctx.accounts.event;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        require!(!event.settled, EventFluxError::AlreadySettled);
+
+        event.forfeiture_split = split;
+        let new_space = event.space();
+
+        resize_account(
+            &event.to_account_info(),
+            &ctx.accounts.organizer,
+            &ctx.accounts.system_program,
+            new_space,
+        )
+    }
+
+    /// Pays forfeited money out to the event's forfeiture split. Remaining
+    /// accounts are the split recipients in order, followed by the passes to
+    /// sweep.
+    ///
+    /// Once the event has ended, no-show passes forfeit their deposits;
+    /// deposits of checked-in passes stay reclaimable until settlement. On a
+    /// cancelled event nothing is swept until `refund_claim_window` has run
+    /// out; then every pass still unrefunded forfeits its whole refund. Those
+    /// passes come as `[event_pass, owner]` pairs and are closed to their
+    /// owners, like `refund_batch`.
+    pub fn sweep_forfeitures<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepForfeitures<'info>>,
+    ) -> Result<u64> {
+        let event = &mut ctx.accounts.event;
+        require_top_level(event)?;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        let now = Clock::get()?.unix_timestamp;
+        if event.cancelled {
+            require!(
+                event.refund_claim_closed(now),
+                EventFluxError::RefundWindowOpen
+            );
+        } else {
+            require!(now > event.end_ts, EventFluxError::EventNotEnded);
+        }
+        let split = event.forfeiture_split.clone();
+        require!(!split.is_empty(), EventFluxError::InvalidForfeitureSplit);
+        require!(
+            ctx.remaining_accounts.len() > split.len(),
+            EventFluxError::InvalidForfeitureSplit
+        );
+        let (recipients, passes) = ctx.remaining_accounts.split_at(split.len());
+        for (recipient, share) in recipients.iter().zip(&split) {
+            require_keys_eq!(
+                recipient.key(),
+                share.recipient,
+                EventFluxError::InvalidForfeitureSplit
+            );
+        }
+
+        let mut swept: u64 = 0;
+        let mut swept_passes: u32 = 0;
+        if event.cancelled {
+            require!(
+                passes.len().is_multiple_of(2),
+                EventFluxError::InvalidBatchSize
+            );
+            for pair in passes.chunks(2) {
+                let (pass_info, owner_info) = (&pair[0], &pair[1]);
+                let event_pass = Account::<EventPass>::try_from(pass_info)?;
+                require_keys_eq!(
+                    event_pass.event,
+                    event.key(),
+                    EventFluxError::PassEventMismatch
+                );
+                require_keys_eq!(
+                    owner_info.key(),
+                    event_pass.owner,
+                    EventFluxError::PassOwnerMismatch
+                );
+                let stale = release_pass(
+                    event,
+                    &mut ctx.accounts.vault_state,
+                    &mut ctx.accounts.organizer_stats,
+                    &mut ctx.accounts.guest_list,
+                    &event_pass,
+                )?;
+                event_pass.close(owner_info.clone())?;
+                swept = swept
+                    .checked_add(stale)
+                    .ok_or(EventFluxError::MathOverflow)?;
+                swept_passes += 1;
+            }
+        } else {
+            for pass_info in passes {
+                let mut event_pass = Account::<EventPass>::try_from(pass_info)?;
+                require_keys_eq!(
+                    event_pass.event,
+                    event.key(),
+                    EventFluxError::PassEventMismatch
+                );
+                require!(!event_pass.checked_in, EventFluxError::AlreadyCheckedIn);
+                require!(
+                    event_pass.deposit_lamports > 0,
+                    EventFluxError::NoDepositToReclaim
+                );
+                swept = swept
+                    .checked_add(event_pass.deposit_lamports)
+                    .ok_or(EventFluxError::MathOverflow)?;
+                event_pass.deposit_lamports = 0;
+                event_pass.exit(&crate::ID)?;
+                swept_passes += 1;
+            }
+            let vault_state = &mut ctx.accounts.vault_state;
+            vault_state.deposits_held = vault_state
+                .deposits_held
+                .checked_sub(swept)
+                .ok_or(EventFluxError::MathOverflow)?;
+        }
+
+        debit_treasury(&ctx.accounts.vault_treasury.to_account_info(), swept)?;
+        let mut remaining = swept;
+        for (index, (recipient, share)) in recipients.iter().zip(&split).enumerate() {
+            // The last recipient also takes the rounding remainder.
+            let amount = if index + 1 == split.len() {
+                remaining
+            } else {
+                (u128::from(swept) * u128::from(share.bps) / BPS_DENOMINATOR) as u64
+            };
+            remaining -= amount;
+            **recipient.try_borrow_mut_lamports()? += amount;
+        }
+
+        emit!(ForfeituresSwept {
+            event: event.key(),
+            swept,
+            passes: swept_passes,
+        });
+
+        Ok(swept)
+    }
+
+    /// Dry run of `withdraw_treasury`: returns what settlement would pay out
+    /// right now without moving any lamports.
+    pub fn preview_settlement(ctx: Context<PreviewSettlement>) -> Result<SettlementBreakdown> {
+        let event = &ctx.accounts.event;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+
+        settlement_breakdown(
+            event,
+            &ctx.accounts.vault_state,
+            ctx.accounts.vault_treasury.lamports(),
+            Rent::get()?.minimum_balance(0),
+        )
+    }
+
+    pub fn harvest_yield(ctx: Context<HarvestYield>, amount: u64) -> Result<()> {
+        require!(amount > 0, EventFluxError::InvalidHarvestAmount);
+
+        let event = &ctx.accounts.event;
+        let vault_state = &ctx.accounts.vault_state;
+        require!(
+            event.yield_strategy == vault_state.strategy,
+            EventFluxError::StrategyMismatch
+        );
+        require_keys_eq!(ctx.accounts.organizer.key(), event.organizer);
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(
+            !matches!(event.yield_strategy, YieldStrategy::None),
+            EventFluxError::NoYieldStrategy,
+        );
+        require!(!vault_state.harvest_paused, EventFluxError::HarvestPaused);
+        // A single harvest can't report more yield than the principal still
+        // in the vault; anything larger points at a faulty adapter.
+        require!(
+            amount
+                <= vault_state
+                    .idle_balance()
+                    .saturating_add(vault_state.at_risk_balance()),
+            EventFluxError::HarvestExceedsPrincipal
+        );
+        // Only what the reserve holds beyond the deployed principal is yield;
+        // principal comes back through `recall_from_adapter`.
+        let reserve = ctx.accounts.adapter_reserve.to_account_info();
+        let reserve_yield = reserve
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(reserve.data_len()))
+            .saturating_sub(vault_state.deployed_principal);
+        require!(amount <= reserve_yield, EventFluxError::HarvestExceedsYield);
+
+        let event_key = event.key();
+        let signer_seeds: &[&[u8]] = &[VAULT_STATE_SEED, event_key.as_ref(), &[vault_state.bump]];
+        vault_stub::cpi::harvest(
+            CpiContext::new_with_signer(
+                ctx.accounts.vault_adapter_program.to_account_info(),
+                vault_stub::cpi::accounts::Harvest {
+                    authority: ctx.accounts.vault_state.to_account_info(),
+                    adapter: reserve,
+                    destination: ctx.accounts.vault_treasury.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
+        )?;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.total_yield_harvested = vault_state
+            .total_yield_harvested
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
+        vault_state.last_harvest_ts = Clock::get()?.unix_timestamp;
+        vault_state.effective_apy_bps = vault_state.compute_apy_bps(vault_state.last_harvest_ts);
+
+        Ok(())
+    }
+
+    /// Pays up to the harvested yield not yet withdrawn to `yield_destination`,
+    /// so yield can go somewhere other than the settlement treasury. Principal
+    /// is never touched; whatever yield is left is paid out at settlement.
+    /// Cancelled events keep their yield in the treasury alongside the
+    /// refunds it may have to cover.
+    pub fn withdraw_yield(ctx: Context<WithdrawYield>, amount: u64) -> Result<()> {
+        let event = &ctx.accounts.event;
+        require_top_level(event)?;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(!event.cancelled, EventFluxError::EventCancelled);
+        require!(amount > 0, EventFluxError::NothingToWithdraw);
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.currency.require_sol()?;
+        require!(
+            amount <= vault_state.unclaimed_yield(),
+            EventFluxError::InsufficientYield
+        );
+        let treasury = ctx.accounts.vault_treasury.to_account_info();
+        require!(
+            amount <= spendable_balance(&treasury, vault_state)?,
+            EventFluxError::TreasuryDeficit
+        );
+
+        debit_treasury(&treasury, amount)?;
+        **ctx
+            .accounts
+            .yield_destination
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+
+        vault_state.yield_withdrawn = vault_state
+            .yield_withdrawn
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
+        let organizer_stats = &mut ctx.accounts.organizer_stats;
+        organizer_stats.lifetime_revenue = organizer_stats
+            .lifetime_revenue
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Opens this event's own reserve in the vault's adapter, with the vault
+    /// state PDA as its authority so only this program can move lamports out
+    /// of it. Required once before `deposit_to_adapter` or `harvest_yield`.
+    pub fn initialize_adapter_reserve(ctx: Context<InitializeAdapterReserve>) -> Result<()> {
+        let vault_state = &ctx.accounts.vault_state;
+        let event_key = ctx.accounts.event.key();
+        let signer_seeds: &[&[u8]] = &[VAULT_STATE_SEED, event_key.as_ref(), &[vault_state.bump]];
+        vault_stub::cpi::initialize(CpiContext::new_with_signer(
+            ctx.accounts.vault_adapter_program.to_account_info(),
+            vault_stub::cpi::accounts::Initialize {
+                adapter: ctx.accounts.adapter_reserve.to_account_info(),
+                payer: ctx.accounts.organizer.to_account_info(),
+                authority: vault_state.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            &[signer_seeds],
+        ))
+    }
+
+    /// Moves `amount` of idle proceeds from the treasury into the event's
+    /// adapter reserve so it can earn yield, recording it as deployed
+    /// principal. The treasury's rent reserve, refundable deposits, held fee
+    /// refunds and harvested yield always stay behind, and nothing is
+    /// deployed once the event is cancelled. The treasury is owned by this
+    /// program, so the lamports are moved directly rather than through a
+    /// system transfer.
+    pub fn deposit_to_adapter(ctx: Context<DepositToAdapter>, amount: u64) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let vault_state = &mut ctx.accounts.vault_state;
+        require_top_level(event)?;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(!event.cancelled, EventFluxError::EventCancelled);
+        require!(!vault_state.harvest_paused, EventFluxError::HarvestPaused);
+        require!(
+            !matches!(event.yield_strategy, YieldStrategy::None),
+            EventFluxError::NoYieldStrategy
+        );
+        require!(
+            event.yield_strategy == vault_state.strategy,
+            EventFluxError::StrategyMismatch
+        );
+
+        let treasury = ctx.accounts.vault_treasury.to_account_info();
+        let available = spendable_balance(&treasury, vault_state)?
+            .saturating_sub(vault_state.unclaimed_yield());
+        require!(
+            amount > 0 && amount <= available,
+            EventFluxError::InvalidAdapterDeposit
+        );
+
+        debit_treasury(&treasury, amount)?;
+        **ctx
+            .accounts
+            .adapter_reserve
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+
+        vault_state.deployed_principal = vault_state
+            .deployed_principal
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
+        emit!(AdapterDeposited {
+            event: event.key(),
+            amount,
+            deployed_principal: vault_state.deployed_principal,
+        });
+
+        Ok(())
+    }
+
+    /// Brings `amount` of deployed principal back from the adapter into the
+    /// treasury. Settlement waits until all of it has been recalled, so
+    /// principal is never stranded in the adapter or booked as yield.
+    pub fn recall_from_adapter(ctx: Context<RecallFromAdapter>, amount: u64) -> Result<()> {
+        let event = &ctx.accounts.event;
+        require_top_level(event)?;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        let vault_state = &ctx.accounts.vault_state;
+        require!(
+            amount > 0 && amount <= vault_state.deployed_principal,
+            EventFluxError::InvalidAdapterRecall
+        );
+
+        let event_key = event.key();
+        let signer_seeds: &[&[u8]] = &[VAULT_STATE_SEED, event_key.as_ref(), &[vault_state.bump]];
+        vault_stub::cpi::withdraw(
+            CpiContext::new_with_signer(
+                ctx.accounts.vault_adapter_program.to_account_info(),
+                vault_stub::cpi::accounts::Harvest {
+                    authority: vault_state.to_account_info(),
+                    adapter: ctx.accounts.adapter_reserve.to_account_info(),
+                    destination: ctx.accounts.vault_treasury.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
+        )?;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.deployed_principal -= amount;
+        emit!(AdapterRecalled {
+            event: event.key(),
+            amount,
+            deployed_principal: vault_state.deployed_principal,
+        });
+
+        Ok(())
+    }
+
+    /// Stops or resumes yield harvesting and adapter deposits, e.g. during
+    /// adapter maintenance. Sales and check-in are unaffected.
+    pub fn set_harvest_paused(ctx: Context<SetHarvestPaused>, paused: bool) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.harvest_paused = paused;
+
+        emit!(HarvestPauseToggled {
+            event: vault_state.event,
+            paused,
+        });
+
+        Ok(())
+    }
+
+    /// Changes the yield strategy after creation. Only allowed before any
+    /// yield has been harvested, so the recorded yield always came from a
+    /// single strategy.
+    pub fn switch_yield_strategy(
+        ctx: Context<SwitchYieldStrategy>,
+        new_strategy: YieldStrategy,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        let vault_state = &mut ctx.accounts.vault_state;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(
+            vault_state.total_yield_harvested == 0,
+            EventFluxError::YieldAlreadyHarvested
+        );
+        require!(
+            event.yield_strategy != new_strategy,
+            EventFluxError::StrategyUnchanged
+        );
+
+        event.yield_strategy = new_strategy.clone();
+        vault_state.strategy = new_strategy.clone();
+
+        emit!(YieldStrategySwitched {
+            event: event.key(),
+            strategy: new_strategy,
+        });
+
+        Ok(())
+    }
+
+    pub fn reconcile_treasury(ctx: Context<ReconcileTreasury>) -> Result<u64> {
+        require!(!ctx.accounts.event.settled, EventFluxError::AlreadySettled);
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        let expected = vault_state
+            .expected_treasury_balance(Rent::get()?.minimum_balance(0))
+            .ok_or(EventFluxError::MathOverflow)?;
+        let balance = ctx.accounts.vault_treasury.lamports();
+        require!(balance >= expected, EventFluxError::TreasuryDeficit);
+
+        let surplus = balance - expected;
+        vault_state.surplus_lamports = vault_state
+            .surplus_lamports
+            .checked_add(surplus)
+            .ok_or(EventFluxError::MathOverflow)?;
+
+        Ok(surplus)
+    }
+
+    pub fn simulate_mint_price(ctx: Context<SimulateMintPrice>, tier_id: u8) -> Result<u64> {
+        let event = &ctx.accounts.event;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < event.end_ts, EventFluxError::EventEnded);
+
+        let tier = event
+            .tiers
+            .iter()
+            .find(|t| t.tier_id == tier_id)
+            .ok_or(EventFluxError::TierNotFound)?;
+        require!(!tier.is_sold_out(), EventFluxError::TierSoldOut);
+
+        Ok(tier.current_price(now))
+    }
+
+    pub fn get_event_summary(ctx: Context<GetEventSummary>) -> Result<EventSummary> {
+        let event = &ctx.accounts.event;
+        let vault_state = &ctx.accounts.vault_state;
+        let now = Clock::get()?.unix_timestamp;
+        let status = if event.settled {
+            EventStatus::Settled
+        } else if event.cancelled {
+            EventStatus::Cancelled
+        } else if now < event.start_ts {
+            EventStatus::Upcoming
+        } else if now <= event.end_ts {
+            EventStatus::Live
+        } else {
+            EventStatus::Ended
+        };
+
+        Ok(EventSummary {
+            event_id: event.event_id,
+            organizer: event.organizer,
+            name: event.name.clone(),
+            venue: event.venue.clone(),
+            image_uri: event.image_uri.clone(),
+            thumbnail_uri: event.thumbnail_uri.clone(),
+            start_ts: event.start_ts,
+            end_ts: event.end_ts,
+            total_passes: event.total_passes,
+            tier_count: event.tiers.len() as u8,
+            status,
+            checked_in_count: event.checked_in_count,
+            remaining_supply: event.total_remaining_supply(),
+            total_deposited: vault_state.total_deposited,
+            net_treasury_balance: ctx
+                .accounts
+                .vault_treasury
+                .lamports()
+                .saturating_sub(Rent::get()?.minimum_balance(0)),
+        })
+    }
+
+    pub fn get_organizer_dashboard(
+        ctx: Context<GetOrganizerDashboard>,
+    ) -> Result<OrganizerDashboard> {
+        let stats = &ctx.accounts.organizer_stats;
+        Ok(OrganizerDashboard {
+            organizer: stats.organizer,
+            lifetime_passes: stats.lifetime_passes,
+            lifetime_revenue: stats.lifetime_revenue,
+            lifetime_checkins: stats.lifetime_checkins,
+        })
+    }
+
+    pub fn get_yield_metrics(ctx: Context<GetYieldMetrics>) -> Result<YieldMetrics> {
+        let vault_state = &ctx.accounts.vault_state;
+        Ok(YieldMetrics {
+            total_deposited: vault_state.total_deposited,
+            total_yield_harvested: vault_state.total_yield_harvested,
+            last_harvest_ts: vault_state.last_harvest_ts,
+            first_deposit_ts: vault_state.first_deposit_ts,
+            effective_apy_bps: vault_state.effective_apy_bps,
+        })
+    }
+
+    /// Rent-exempt lamports for every account `create_event` with `args`,
+    /// `initialize_event_treasury` and each `mint_pass` allocate, so clients
+    /// can fund the organizer and buyers exactly.
+    pub fn estimate_rent(
+        _ctx: Context<EstimateRent>,
+        args: CreateEventArgs,
+    ) -> Result<RentEstimate> {
+        let rent = Rent::get()?;
+        let event_space = Event::checked_size_with_args(&args)?;
+        let guest_list = rent.minimum_balance(GuestList::space_for(0));
+
+        Ok(RentEstimate {
+            event: rent.minimum_balance(event_space),
+            vault_state: rent.minimum_balance(VaultState::SPACE),
+            organizer_stats: rent.minimum_balance(OrganizerStats::SPACE),
+            guest_list,
+            vault_treasury: rent.minimum_balance(0),
+            event_pass: rent.minimum_balance(EventPass::SPACE),
+            guest_list_entry: rent.minimum_balance(GuestList::space_for(1)) - guest_list,
+        })
+    }
+
+    /// Appends a tier, growing the event account at the organizer's expense.
+    /// Before `finalize_tiers` this builds the launch line-up; afterwards it
+    /// releases a new tier that sells straight away, leaving the tiers buyers
+    /// already priced against untouched. `freeze_event_config` ends it.
+    pub fn add_tier(ctx: Context<AddTier>, tier: TierInput) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        require!(
+            event.tiers.len() < MAX_TIER_COUNT,
+            EventFluxError::TooManyTiers
+        );
+        require!(
+            event.tiers.iter().all(|t| t.tier_id != tier.tier_id),
+            EventFluxError::InvalidTierSet
+        );
+        tier.validate(event.start_ts, event.end_ts)?;
+
+        event.tiers.push(TierConfig::from_input(tier)?);
+        if event.strict_tier_order {
+            validate_tier_order(event.tiers.iter().map(|t| (t.tier_id, t.price_lamports)))?;
+        }
+        let new_space = event.space();
+
+        resize_account(
+            &event.to_account_info(),
+            &ctx.accounts.organizer,
+            &ctx.accounts.system_program,
+            new_space,
+        )
+    }
+
+    pub fn finalize_tiers(ctx: Context<FinalizeTiers>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.tiers_finalized, EventFluxError::TiersFinalized);
+        require!(!event.tiers.is_empty(), EventFluxError::InvalidTierSet);
+
+        event.tiers_finalized = true;
+        Ok(())
+    }
+
+    /// Permanently locks tiers, prices, schedule and metadata so buyers can
+    /// rely on what they were sold. Check-in, sales and settlement keep
+    /// working.
+    pub fn freeze_event_config(ctx: Context<FreezeEventConfig>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        require!(event.tiers_finalized, EventFluxError::TiersNotFinalized);
+
+        event.config_frozen = true;
+        emit!(EventConfigFrozen { event: event.key() });
+
+        Ok(())
+    }
+
+    /// Calls the event off. New mints and check-ins are refused and holders
+    /// can reclaim their payment with `refund_pass` for the event's
+    /// `refund_claim_window`.
+    pub fn cancel_event(ctx: Context<CancelEvent>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(!event.cancelled, EventFluxError::EventCancelled);
+
+        event.cancelled = true;
+        event.cancelled_at = Some(Clock::get()?.unix_timestamp);
+        emit!(EventCancelled { event: event.key() });
+
+        Ok(())
+    }
+
+    /// Halts or resumes pass sales without cancelling the event. Check-in
+    /// and treasury withdrawals are unaffected.
+    pub fn set_sales_paused(ctx: Context<SetSalesPaused>, paused: bool) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        event.sales_paused = paused;
+
+        emit!(SalesPauseToggled {
+            event: event.key(),
+            paused,
+        });
+
+        Ok(())
+    }
+
+    /// Opens or closes voluntary refunds: until `deadline` holders of
+    /// refundable tiers can return unused passes with `refund_pass`. The
+    /// deadline can't fall after the event starts.
+    pub fn set_refund_deadline(
+        ctx: Context<SetRefundDeadline>,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        require!(
+            deadline.is_none_or(|deadline| deadline <= event.start_ts),
+            EventFluxError::InvalidSchedule
+        );
+        event.refund_deadline = deadline;
+        Ok(())
+    }
+
+    /// Sets how long holders have to claim their refund once the event is
+    /// cancelled. Fixed at cancellation, so holders know their window.
+    pub fn set_refund_claim_window(ctx: Context<SetRefundClaimWindow>, window: i64) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        require!(!event.cancelled, EventFluxError::EventCancelled);
+        require!(window > 0, EventFluxError::InvalidSchedule);
+        event.refund_claim_window = window;
+        Ok(())
+    }
+
+    /// Sets the protocol admin. Only the program's upgrade authority can do
+    /// this, and only once.
+    pub fn initialize_protocol_config(
+        ctx: Context<InitializeProtocolConfig>,
+        admin: Pubkey,
+    ) -> Result<()> {
+        let protocol_config = &mut ctx.accounts.protocol_config;
+        protocol_config.bump = ctx.bumps.protocol_config;
+        protocol_config.admin = admin;
+        protocol_config.fee_bps = 0;
+        protocol_config.fee_collector = Pubkey::default();
+        Ok(())
+    }
+
+    /// Sets the platform's cut of every paid mint.
+    pub fn set_protocol_fee(
+        ctx: Context<SetProtocolFee>,
+        fee_bps: u16,
+        fee_collector: Pubkey,
+    ) -> Result<()> {
+        require!(
+            u128::from(fee_bps) <= BPS_DENOMINATOR,
+            EventFluxError::InvalidProtocolFee
+        );
+        require!(
+            fee_bps == 0 || fee_collector != Pubkey::default(),
+            EventFluxError::InvalidProtocolFee
+        );
+
+        let protocol_config = &mut ctx.accounts.protocol_config;
+        protocol_config.fee_bps = fee_bps;
+        protocol_config.fee_collector = fee_collector;
+        Ok(())
+    }
+
+    /// Sends platform fees charged on an event back to its treasury, for
+    /// organizers exempted after the fact or fees charged by mistake. The
+    /// collector signs for the lamports, and the total can't exceed what this
+    /// event was actually charged. Refunded fees count as event revenue,
+    /// except on a cancelled event: there they are held for `refund_pass` and
+    /// `refund_batch` to hand each buyer back the fee they paid.
+    pub fn refund_fee(ctx: Context<RefundFee>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.event.settled, EventFluxError::AlreadySettled);
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.currency.require_sol()?;
+        require!(
+            amount > 0 && amount <= vault_state.refundable_fees(),
+            EventFluxError::FeeRefundExceedsCollected
+        );
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.fee_collector.to_account_info(),
+                    to: ctx.accounts.vault_treasury.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        vault_state.fees_refunded = vault_state
+            .fees_refunded
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
+        if ctx.accounts.event.cancelled {
+            vault_state.fee_refunds_held = vault_state
+                .fee_refunds_held
+                .checked_add(amount)
+                .ok_or(EventFluxError::MathOverflow)?;
+        } else {
+            vault_state.total_deposited = vault_state
+                .total_deposited
+                .checked_add(amount)
+                .ok_or(EventFluxError::MathOverflow)?;
+        }
+
+        emit!(FeeRefunded {
+            event: ctx.accounts.event.key(),
+            amount,
+            fees_refunded: vault_state.fees_refunded,
+        });
+
+        Ok(())
+    }
+
+    /// Lets a pass holder report a problem with the event, holding the payout
+    /// until the protocol admin resolves it. Each pass can raise one dispute,
+    /// and only once a protocol admin exists to hear it.
+    pub fn raise_dispute(ctx: Context<RaiseDispute>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(!event.disputed, EventFluxError::EventDisputed);
+        let event_pass = &mut ctx.accounts.event_pass;
+        require!(
+            !event_pass.dispute_raised,
+            EventFluxError::DisputeAlreadyRaised
+        );
+
+        event_pass.dispute_raised = true;
+        event.disputed = true;
+        emit!(DisputeRaised {
+            event: event.key(),
+            raised_by: ctx.accounts.holder.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Rules on a dispute. Siding with the organizer clears it so they can
+    /// settle again; with `refund` the buyers win instead, and the event is
+    /// cancelled so every holder can take their money back with
+    /// `refund_pass`.
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, refund: bool) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.disputed, EventFluxError::NotDisputed);
+
+        event.disputed = false;
+        if refund && !event.cancelled {
+            event.cancelled = true;
+            event.cancelled_at = Some(Clock::get()?.unix_timestamp);
+            emit!(EventCancelled { event: event.key() });
+        }
+        emit!(DisputeResolved {
+            event: event.key(),
+            refunded: refund,
+        });
+
+        Ok(())
+    }
+
+    pub fn close_empty_tier(ctx: Context<CloseEmptyTier>, tier_id: u8) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        let position = event
+            .tiers
+            .iter()
+            .position(|t| t.tier_id == tier_id)
+            .ok_or(EventFluxError::TierNotFound)?;
+        require!(
+            event.tiers[position].sold == 0,
+            EventFluxError::TierNotEmpty
+        );
+        if event.strict_tier_order {
+            require!(
+                position + 1 == event.tiers.len(),
+                EventFluxError::TiersNotOrdered
+            );
+        }
+
+        event.tiers.remove(position);
+        let new_space = event.space();
+
+        resize_account(
+            &event.to_account_info(),
+            &ctx.accounts.organizer,
+            &ctx.accounts.system_program,
+            new_space,
+        )
+    }
+
+    /// Pushes `end_ts` later for an event that is running long. Shortening is
+    /// never allowed so attendees can rely on the advertised settlement time.
+    pub fn extend_event(ctx: Context<ExtendEvent>, new_end_ts: i64) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.config_frozen, EventFluxError::ConfigFrozen);
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(new_end_ts > event.end_ts, EventFluxError::InvalidSchedule);
+        require!(
+            new_end_ts.saturating_sub(event.start_ts) <= MAX_EVENT_DURATION,
+            EventFluxError::EventTooLong
+        );
+
+        let previous_end_ts = event.end_ts;
+        event.end_ts = new_end_ts;
+
+        emit!(EventExtended {
+            event: event.key(),
+            previous_end_ts,
+            new_end_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Corrects a tier's price or supply before any of it has sold. The label
+    /// is left alone so the event account never needs resizing here. Supply
+    /// can't drop to zero; retire an unsold tier with `close_empty_tier`.
+    pub fn update_tier(
+        ctx: Context<UpdateTier>,
+        tier_id: u8,
+        new_price: Option<u64>,
+        new_max_supply: Option<u32>,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.event.config_frozen,
+            EventFluxError::ConfigFrozen
+        );
+
+        let tier = ctx
+            .accounts
+            .event
+            .tiers
+            .iter_mut()
+            .find(|t| t.tier_id == tier_id)
+            .ok_or(EventFluxError::TierNotFound)?;
+        if let Some(max_supply) = new_max_supply {
+            require!(max_supply >= tier.sold, EventFluxError::SupplyBelowSold);
+            require!(max_supply > 0, EventFluxError::InvalidTierSet);
+        }
+        require!(tier.sold == 0, EventFluxError::TierNotEmpty);
+
+        if let Some(price) = new_price {
+            tier.price_lamports = price;
+        }
+        if let Some(max_supply) = new_max_supply {
+            tier.max_supply = max_supply;
+        }
+
+        let event = &ctx.accounts.event;
+        if event.strict_tier_order {
+            validate_tier_order(event.tiers.iter().map(|t| (t.tier_id, t.price_lamports)))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn increase_tier_supply(
+        ctx: Context<IncreaseTierSupply>,
+        tier_id: u8,
+        additional: u32,
+    ) -> Result<()> {
+        require!(additional > 0, EventFluxError::InvalidTierSet);
+        require!(
+            !ctx.accounts.event.config_frozen,
+            EventFluxError::ConfigFrozen
+        );
+
+        let tier = ctx
+            .accounts
+            .event
+            .tiers
+            .iter_mut()
+            .find(|t| t.tier_id == tier_id)
+            .ok_or(EventFluxError::TierNotFound)?;
+        tier.max_supply = tier
+            .max_supply
+            .checked_add(additional)
+            .ok_or(EventFluxError::MathOverflow)?;
+        tier.sold_out_at = None;
+
+        Ok(())
+    }
+
+    /// Adds a gate verifier. The account already reserves room for
+    /// `max_verifiers` entries, so no reallocation is needed.
+    pub fn add_verifier(ctx: Context<ManageVerifiers>, verifier: Pubkey) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(
+            !event.authorized_verifiers.contains(&verifier),
+            EventFluxError::VerifierAlreadyAuthorized
+        );
+        require!(
+            event.authorized_verifiers.len() < usize::from(event.max_verifiers),
+            EventFluxError::TooManyVerifiers
+        );
+
+        event.authorized_verifiers.push(verifier);
+        Ok(())
+    }
+
+    /// Revokes one verifier. Its slot stays reserved, so the account keeps
+    /// its size.
+    pub fn remove_verifier(ctx: Context<ManageVerifiers>, verifier: Pubkey) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        let position = event
+            .authorized_verifiers
+            .iter()
+            .position(|v| v == &verifier)
+            .ok_or(EventFluxError::VerifierNotFound)?;
+
+        event.authorized_verifiers.remove(position);
+        Ok(())
+    }
+
+    /// Kill switch for compromised staff keys: drops every authorized
+    /// verifier at once. Allowed even after the config is frozen. The account
+    /// keeps its size because verifier slots stay reserved up to
+    /// `max_verifiers` for later `add_verifier` calls.
+    pub fn revoke_verifier_all(ctx: Context<ManageVerifiers>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        let revoked = event.authorized_verifiers.len() as u8;
+        event.authorized_verifiers.clear();
+
+        emit!(VerifiersCleared {
+            event: event.key(),
+            revoked,
+        });
+        Ok(())
+    }
+
+    pub fn ban_buyer(ctx: Context<ManageBanList>, buyer: Pubkey) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.is_banned(&buyer), EventFluxError::BuyerBanned);
+        require!(
+            event.banned.len() < MAX_BANNED_COUNT,
+            EventFluxError::TooManyBannedBuyers
+        );
+
+        event.banned.push(buyer);
+        let new_space = event.space();
+
+        resize_account(
+            &event.to_account_info(),
+            &ctx.accounts.organizer,
+            &ctx.accounts.system_program,
+            new_space,
+        )
+    }
+
+    pub fn unban_buyer(ctx: Context<ManageBanList>, buyer: Pubkey) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        let position = event
+            .banned
+            .iter()
+            .position(|b| b == &buyer)
+            .ok_or(EventFluxError::BuyerNotBanned)?;
+
+        event.banned.remove(position);
+        let new_space = event.space();
+
+        resize_account(
+            &event.to_account_info(),
+            &ctx.accounts.organizer,
+            &ctx.accounts.system_program,
+            new_space,
+        )
+    }
+
+    pub fn authorize_renewal(
+        ctx: Context<AuthorizeRenewal>,
+        max_per_period: u64,
+        period_secs: i64,
+        escrow_lamports: u64,
+    ) -> Result<()> {
+        require!(period_secs > 0, EventFluxError::InvalidMandate);
+        require!(max_per_period > 0, EventFluxError::InvalidMandate);
+
+        let mandate = &mut ctx.accounts.mandate;
+        mandate.bump = ctx.bumps.mandate;
+        mandate.owner = ctx.accounts.owner.key();
+        mandate.event_pass = ctx.accounts.event_pass.key();
+        mandate.max_per_period = max_per_period;
+        mandate.period_secs = period_secs;
+        mandate.last_charged_at = Clock::get()?.unix_timestamp;
+
+        if escrow_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.owner.to_account_info(),
+                        to: ctx.accounts.mandate.to_account_info(),
+                    },
+                ),
+                escrow_lamports,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn renew_membership(ctx: Context<RenewMembership>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event = &ctx.accounts.event;
+        let event_pass = &mut ctx.accounts.event_pass;
+        let mandate = &mut ctx.accounts.mandate;
+
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(now < event.end_ts, EventFluxError::EventEnded);
+        ctx.accounts.vault_state.currency.require_sol()?;
+
+        let tier = event
+            .tiers
+            .iter()
+            .find(|t| t.tier_id == event_pass.tier_id)
+            .ok_or(EventFluxError::TierNotFound)?;
+        let renewal_period = tier
+            .renewal_period
+            .ok_or(EventFluxError::TierNotRenewable)?;
+        let price = tier.current_price(now);
+
+        let next_charge_at = mandate
+            .last_charged_at
+            .checked_add(mandate.period_secs)
+            .ok_or(EventFluxError::MathOverflow)?;
+        require!(
+            now >= next_charge_at,
+            EventFluxError::MandatePeriodNotElapsed
+        );
+        require!(
+            price <= mandate.max_per_period,
+            EventFluxError::MandateLimitExceeded
+        );
+
+        let mandate_info = mandate.to_account_info();
+        let reserve = Rent::get()?.minimum_balance(mandate_info.data_len());
+        let available = mandate_info.lamports().saturating_sub(reserve);
+        require!(available >= price, EventFluxError::InsufficientMandateFunds);
+
+        **mandate_info.try_borrow_mut_lamports()? -= price;
+        **ctx
+            .accounts
+            .vault_treasury
+            .to_account_info()
+            .try_borrow_mut_lamports()? += price;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.total_deposited = vault_state
+            .total_deposited
+            .checked_add(price)
+            .ok_or(EventFluxError::MathOverflow)?;
+
+        let base = event_pass.valid_until.unwrap_or(now).max(now);
+        event_pass.valid_until = Some(
+            base.checked_add(renewal_period)
+                .ok_or(EventFluxError::MathOverflow)?,
+        );
+        mandate.last_charged_at = now;
+
+        Ok(())
+    }
+
+    pub fn revoke_mandate(_ctx: Context<RevokeMandate>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Moves a pass to `new_owner` after the organizer has verified the holder
+    /// off-chain. The old pass is closed and the new one keeps its index, tier
+    /// and price, so nothing is charged twice.
+    pub fn reissue_pass(ctx: Context<ReissuePass>, new_owner: Pubkey) -> Result<()> {
+        require!(
+            !ctx.accounts.event.is_banned(&new_owner),
+            EventFluxError::BuyerBanned
+        );
+        let old_pass = &ctx.accounts.old_pass;
+        require!(!old_pass.checked_in, EventFluxError::AlreadyCheckedIn);
+
+        let new_pass = &mut ctx.accounts.new_pass;
+        new_pass.reissue_from(old_pass, ctx.bumps.new_pass, new_owner);
+        ctx.accounts
+            .guest_list
+            .replace(old_pass.pass_index, new_pass.key())?;
+
+        emit!(PassReissued {
+            event: old_pass.event,
+            old_pass: old_pass.key(),
+            new_pass: new_pass.key(),
+            old_owner: old_pass.owner,
+            new_owner,
+        });
+
+        Ok(())
+    }
+
+    /// Reverses a mistaken scan: the pass is no longer checked in and drops
+    /// out of the check-in counts. Refused once the pass has a loyalty NFT,
+    /// which was only issuable because of the check-in, and after
+    /// settlement, when dividends are fixed by the check-in count.
+    pub fn unset_check_in(ctx: Context<UnsetCheckIn>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        let event_pass = &mut ctx.accounts.event_pass;
+        require!(event_pass.checked_in, EventFluxError::PassNotCheckedIn);
+        require!(
+            event_pass.loyalty_mint.is_none(),
+            EventFluxError::LoyaltyAlreadyIssued
+        );
+
+        clear_entry(event, event_pass, &mut ctx.accounts.organizer_stats)?;
+        event_pass.checkin_nonce = event_pass
+            .checkin_nonce
+            .checked_add(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+        emit!(CheckInUnset {
+            event: event.key(),
+            pass: event_pass.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Reissues a pass to the owner it already has, e.g. after a mistaken
+    /// scan or when the holder lost access to their ticket but not their
+    /// wallet. The pass keeps its address, tier, price and custodian while
+    /// its attendance state starts over. Passes that already earned a
+    /// loyalty NFT can't be reset.
+    pub fn reissue_pass_to_owner(ctx: Context<ReissuePassToOwner>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        let event_pass = &mut ctx.accounts.event_pass;
+        require!(
+            event_pass.loyalty_mint.is_none(),
+            EventFluxError::LoyaltyAlreadyIssued
+        );
+        require!(
+            !event.is_banned(&event_pass.owner),
+            EventFluxError::BuyerBanned
+        );
+
+        clear_entry(event, event_pass, &mut ctx.accounts.organizer_stats)?;
+        let old: EventPass = (**event_pass).clone();
+        event_pass.reissue_from(&old, old.bump, old.owner);
+        event_pass.custodian = old.custodian;
+
+        emit!(PassReissued {
+            event: event.key(),
+            old_pass: event_pass.key(),
+            new_pass: event_pass.key(),
+            old_owner: old.owner,
+            new_owner: old.owner,
+        });
+
+        Ok(())
+    }
+
+    /// Gives an unused pass to `new_owner`. The owner is part of the pass
+    /// address, so the old account is closed and a new one created under the
+    /// recipient's key. The owner pays the new account's rent and gets the
+    /// old account's rent back, so the transfer costs them only the fee. The
+    /// rent then travels with the pass to the recipient.
+    pub fn transfer_pass(ctx: Context<TransferPass>, new_owner: Pubkey) -> Result<()> {
+        let event = &ctx.accounts.event;
+        require!(
+            Clock::get()?.unix_timestamp < event.end_ts,
+            EventFluxError::EventEnded
+        );
+        require!(!event.is_banned(&new_owner), EventFluxError::BuyerBanned);
+
+        let old_pass = &ctx.accounts.old_pass;
+        require!(!old_pass.checked_in, EventFluxError::AlreadyCheckedIn);
+
+        let new_pass = &mut ctx.accounts.new_pass;
+        new_pass.reissue_from(old_pass, ctx.bumps.new_pass, new_owner);
+        ctx.accounts
+            .guest_list
+            .replace(old_pass.pass_index, new_pass.key())?;
+
+        emit!(PassTransferred {
+            event: event.key(),
+            old_pass: old_pass.key(),
+            new_pass: new_pass.key(),
+            old_owner: old_pass.owner,
+            new_owner,
+        });
+
+        Ok(())
+    }
+
+    /// Closes a pass once the event is over and returns its rent to the
+    /// owner. Loyalty tokens already issued live in their own token accounts
+    /// and are untouched; without the pass nothing more can be issued for it.
+    /// Any deposit not yet reclaimed is forfeited to the treasury as revenue,
+    /// and the pass stops counting towards the event's totals.
+    pub fn burn_pass(ctx: Context<BurnPass>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(
+            Clock::get()?.unix_timestamp > event.end_ts,
+            EventFluxError::EventNotEnded
+        );
+        let event_pass = &ctx.accounts.event_pass;
+        let organizer_stats = &mut ctx.accounts.organizer_stats;
+        event.total_passes = event
+            .total_passes
+            .checked_sub(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+        organizer_stats.unrecord_pass()?;
+        if event_pass.checked_in {
+            event.checked_in_count = event
+                .checked_in_count
+                .checked_sub(1)
+                .ok_or(EventFluxError::MathOverflow)?;
+            organizer_stats.unrecord_checkin()?;
+        }
+
+        // Settlement already forfeited every held deposit.
+        let deposit = event_pass.deposit_lamports;
+        if deposit > 0 && !event.settled {
+            let vault_state = &mut ctx.accounts.vault_state;
+            vault_state.deposits_held = vault_state
+                .deposits_held
+                .checked_sub(deposit)
+                .ok_or(EventFluxError::MathOverflow)?;
+            vault_state.total_deposited = vault_state
+                .total_deposited
+                .checked_add(deposit)
+                .ok_or(EventFluxError::MathOverflow)?;
+        }
+
+        ctx.accounts
+            .guest_list
+            .replace(event_pass.pass_index, Pubkey::default())
+    }
+
+    /// Peer-to-peer sale of an unused pass. Buyer and seller both sign, so
+    /// payment and the ownership change land together or not at all. The
+    /// organizer's `resale_royalty_bps` cut of `price` goes to the treasury.
+    pub fn transfer_pass_with_payment(
+        ctx: Context<TransferPassWithPayment>,
+        price: u64,
+    ) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.vault_state.currency.require_sol()?;
+        require!(!event.settled, EventFluxError::AlreadySettled);
+        require!(now < event.end_ts, EventFluxError::EventEnded);
+        require!(
+            !event.is_banned(&ctx.accounts.buyer.key()),
+            EventFluxError::BuyerBanned
+        );
+        require!(
+            event.max_resale_lamports.is_none_or(|cap| price <= cap),
+            EventFluxError::ResalePriceTooHigh
+        );
+
+        let old_pass = &ctx.accounts.old_pass;
+        require!(!old_pass.checked_in, EventFluxError::AlreadyCheckedIn);
+        let tier = event
+            .tiers
+            .iter()
+            .find(|t| t.tier_id == old_pass.tier_id)
+            .ok_or(EventFluxError::TierNotFound)?;
+        require!(
+            tier.max_resale_price().is_none_or(|cap| price <= cap),
+            EventFluxError::ResalePriceTooHigh
+        );
+
+        let royalty = u64::try_from(
+            u128::from(price) * u128::from(event.resale_royalty_bps) / BPS_DENOMINATOR,
+        )
+        .map_err(|_| EventFluxError::MathOverflow)?;
+        let seller_proceeds = price - royalty;
+
+        if seller_proceeds > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.buyer.to_account_info(),
+                        to: ctx.accounts.seller.to_account_info(),
+                    },
+                ),
+                seller_proceeds,
+            )?;
+        }
+        if royalty > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.buyer.to_account_info(),
+                        to: ctx.accounts.vault_treasury.to_account_info(),
+                    },
+                ),
+                royalty,
+            )?;
+            record_deposit(&mut ctx.accounts.vault_state, royalty, now)?;
+        }
+
+        let buyer = ctx.accounts.buyer.key();
+        let new_pass = &mut ctx.accounts.new_pass;
+        new_pass.reissue_from(old_pass, ctx.bumps.new_pass, buyer);
+        ctx.accounts
+            .guest_list
+            .replace(old_pass.pass_index, new_pass.key())?;
+
+        emit!(PassResold {
+            event: event.key(),
+            old_pass: old_pass.key(),
+            new_pass: new_pass.key(),
+            seller: old_pass.owner,
+            buyer,
+            price,
+            royalty,
+        });
+
+        Ok(())
+    }
+
+    pub fn issue_loyalty_nft(ctx: Context<IssueLoyaltyNft>) -> Result<()> {
+        let event_pass = &mut ctx.accounts.event_pass;
+        require_keys_eq!(
+            event_pass.event,
+            ctx.accounts.event.key(),
+            EventFluxError::PassEventMismatch
+        );
+
+        require_loyalty_eligible(
+            &ctx.accounts.event,
+            event_pass,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let event_key = ctx.accounts.event.key();
+        mint_locked_loyalty_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.loyalty_mint,
+            &ctx.accounts.loyalty_token_account,
+            &ctx.accounts.loyalty_authority,
+            &[
+                LOYALTY_AUTHORITY_SEED,
+                event_key.as_ref(),
+                &[ctx.bumps.loyalty_authority],
+            ],
+        )?;
+
+        event_pass.loyalty_mint = Some(ctx.accounts.loyalty_mint.key());
+
+        Ok(())
+    }
+
+    /// Self-service counterpart of `issue_loyalty_nft`: the pass owner pays
+    /// for their own mint.
+    pub fn claim_loyalty(ctx: Context<ClaimLoyalty>) -> Result<()> {
+        let event_pass = &mut ctx.accounts.event_pass;
+        require_loyalty_eligible(
+            &ctx.accounts.event,
+            event_pass,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let event_key = ctx.accounts.event.key();
+        mint_locked_loyalty_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.loyalty_mint,
+            &ctx.accounts.loyalty_token_account,
+            &ctx.accounts.loyalty_authority,
+            &[
+                LOYALTY_AUTHORITY_SEED,
+                event_key.as_ref(),
+                &[ctx.bumps.loyalty_authority],
+            ],
+        )?;
+
+        event_pass.loyalty_mint = Some(ctx.accounts.loyalty_mint.key());
+
+        Ok(())
+    }
+
+    /// Picks a random checked-in pass from the candidates in
+    /// `remaining_accounts` and marks it as a lucky-drop winner.
+    ///
+    /// Randomness comes from the latest SlotHashes entry, which the current
+    /// leader can influence, and the organizer chooses the candidate set.
+    /// Only use it for low-value rewards.
+    pub fn lucky_drop<'info>(ctx: Context<'_, '_, 'info, 'info, LuckyDrop<'info>>) -> Result<()> {
+        let event = &ctx.accounts.event;
+        require!(
+            Clock::get()?.unix_timestamp > event.end_ts,
+            EventFluxError::EventNotEnded
+        );
+
+        let mut candidates = Vec::new();
+        for info in ctx.remaining_accounts {
+            let event_pass = Account::<EventPass>::try_from(info)?;
+            require_keys_eq!(
+                event_pass.event,
+                event.key(),
+                EventFluxError::PassEventMismatch
+            );
+            if event_pass.checked_in && !event_pass.lucky_winner {
+                candidates.push(event_pass);
+            }
+        }
+        require!(
+            !candidates.is_empty(),
+            EventFluxError::NoLuckyDropCandidates
+        );
+
+        // Mixing in the event key keeps draws for different events in the same
+        // slot from landing on the same seed.
+        let mut seed = latest_slot_hash(&ctx.accounts.slot_hashes)?;
+        for (byte, key_byte) in seed.iter_mut().zip(event.key().to_bytes()) {
+            *byte ^= key_byte;
+        }
+        let mut seed_word = [0u8; 8];
+        seed_word.copy_from_slice(&seed[..8]);
+        let index = (u64::from_le_bytes(seed_word) % candidates.len() as u64) as usize;
+        let candidate_count = candidates.len() as u32;
+
+        let winner = &mut candidates[index];
+        winner.lucky_winner = true;
+        winner.exit(&crate::ID)?;
+
+        emit!(LuckyDropWon {
+            event: event.key(),
+            event_pass: winner.key(),
+            owner: winner.owner,
+            seed,
+            candidate_count,
+        });
+
+        Ok(())
+    }
+
+    /// Mints the 1-of-1 lucky-drop reward to a winning pass owner and locks
+    /// the mint so the reward stays unique.
+    pub fn claim_lucky_reward(ctx: Context<ClaimLuckyReward>) -> Result<()> {
+        require!(
+            ctx.accounts.event_pass.lucky_winner,
+            EventFluxError::NotLuckyWinner
+        );
+
+        let event_key = ctx.accounts.event.key();
+        let signer_seeds: &[&[u8]] = &[
+            LOYALTY_AUTHORITY_SEED,
+            event_key.as_ref(),
+            &[ctx.bumps.loyalty_authority],
+        ];
+
+        mint_locked_loyalty_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.lucky_mint,
+            &ctx.accounts.lucky_token_account,
+            &ctx.accounts.loyalty_authority,
+            signer_seeds,
+        )
+    }
+}
+
+/// Most recent entry of the SlotHashes sysvar: an 8-byte length prefix,
+/// then `(slot, hash)` pairs newest first.
+fn latest_slot_hash(slot_hashes: &AccountInfo) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    let hash = data
+        .get(16..48)
+        .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+    let mut out = [0u8; 32];
+    out.copy_from_slice(hash);
+    Ok(out)
+}
+
+/// Mints a pass's single loyalty or lucky-drop token and revokes the mint
+/// authority, so no further tokens can ever be created on that mint.
+fn mint_locked_loyalty_token<'info>(
+    token_program: &Program<'info, Token>,
+    loyalty_mint: &Account<'info, Mint>,
+    loyalty_token_account: &Account<'info, TokenAccount>,
+    loyalty_authority: &UncheckedAccount<'info>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    token::mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                mint: loyalty_mint.to_account_info(),
+                to: loyalty_token_account.to_account_info(),
+                authority: loyalty_authority.to_account_info(),
+            },
+            &[signer_seeds],
+        ),
+        1,
+    )?;
+    token::set_authority(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::SetAuthority {
+                current_authority: loyalty_authority.to_account_info(),
+                account_or_mint: loyalty_mint.to_account_info(),
+            },
+            &[signer_seeds],
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )
+}
+
+fn require_loyalty_eligible(event: &Event, event_pass: &EventPass, now: i64) -> Result<()> {
+    if event.loyalty_requires_checkin {
+        require!(event_pass.checked_in, EventFluxError::PassNotCheckedIn);
+    }
+    if let Some(checked_in_at) = event_pass.checked_in_at {
+        let earliest = checked_in_at
+            .checked_add(event.loyalty_delay_secs)
+            .ok_or(EventFluxError::MathOverflow)?;
+        require!(now >= earliest, EventFluxError::LoyaltyTooSoon);
+    }
+    require!(
+        event_pass.loyalty_mint.is_none(),
+        EventFluxError::LoyaltyAlreadyIssued
+    );
+    Ok(())
+}
+
+/// Evaluates the check-in rules in order and returns the first one the scan
+/// breaks, or `None` when the pass may enter.
+fn check_in_denial<'info>(
+    event: &Event,
+    event_pass: &Account<'info, EventPass>,
+    verifier: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    now: i64,
+) -> Result<Option<CheckInDenial>> {
+    if let Some(denial) = schedule_denial(event, now) {
+        return Ok(Some(denial));
+    }
+
+    let verifier_key = verifier.key();
+    let is_authorized = if verifier_key == event.organizer {
+        true
+    } else if let Some(auth_program) = event.auth_program {
+        authorize_via_program(
+            &auth_program,
+            verifier,
+            &event_pass.to_account_info(),
+            remaining_accounts,
+        )?
+    } else {
+        event
+            .authorized_verifiers
+            .iter()
+            .any(|v| v == &verifier_key)
+            || event_pass.held_by(&verifier_key)
+    };
+    if !is_authorized {
+        return Ok(Some(CheckInDenial::UnauthorizedVerifier));
+    }
+
+    pass_denial(event, event_pass, now)
+}
+
+/// The event-level check-in rules: not cancelled and `now` within the event.
+fn schedule_denial(event: &Event, now: i64) -> Option<CheckInDenial> {
+    if event.cancelled {
+        Some(CheckInDenial::EventCancelled)
+    } else if now < event.start_ts {
+        Some(CheckInDenial::EventNotStarted)
+    } else if now > event.end_ts {
+        Some(CheckInDenial::EventEnded)
+    } else {
+        None
+    }
+}
+
+/// The pass-level check-in rules: tier window, expiry and re-entry limits.
+fn pass_denial(event: &Event, event_pass: &EventPass, now: i64) -> Result<Option<CheckInDenial>> {
+    let tier = event
+        .tiers
+        .iter()
+        .find(|t| t.tier_id == event_pass.tier_id)
+        .ok_or(EventFluxError::TierNotFound)?;
+    let (window_start, window_end) = tier.checkin_window(event.start_ts, event.end_ts);
+    if now < window_start || now > window_end {
+        return Ok(Some(CheckInDenial::OutsideTierWindow));
+    }
+    if event_pass
+        .valid_until
+        .is_some_and(|valid_until| now > valid_until)
+    {
+        return Ok(Some(CheckInDenial::PassExpired));
+    }
+    if event_pass.checked_in {
+        if event.max_reentries == 0 {
+            return Ok(Some(CheckInDenial::AlreadyCheckedIn));
+        }
+        if event_pass.reentry_count >= event.max_reentries {
+            return Ok(Some(CheckInDenial::ReentryLimitReached));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Reads the ed25519 program instruction placed right before the current one
+/// and returns the key that signed `event_pass` and the timestamp it signed.
+/// The signature itself was already verified by the ed25519 program; this only
+/// checks that the instruction carries exactly one signature over the expected
+/// message, with every offset pointing into its own data.
+fn signed_check_in(
+    instructions_sysvar: &AccountInfo,
+    pass_key: &Pubkey,
+    event_pass: &EventPass,
+) -> Result<(Pubkey, i64)> {
+    // num_signatures, padding, then one Ed25519SignatureOffsets (7 x u16)
+    const HEADER_LEN: usize = 2 + 14;
+    const SIGNATURE_LEN: usize = 64;
+    const PREFIX_LEN: usize = 32 + 8 + 4;
+    const MESSAGE_LEN: usize = PREFIX_LEN + 8;
+
+    let ix = get_instruction_relative(-1, instructions_sysvar)
+        .map_err(|_| EventFluxError::InvalidCheckInSignature)?;
+    require_keys_eq!(
+        ix.program_id,
+        ed25519_program::ID,
+        EventFluxError::InvalidCheckInSignature
+    );
+
+    let data = &ix.data;
+    require!(
+        data.len() >= HEADER_LEN && data[0] == 1,
+        EventFluxError::InvalidCheckInSignature
+    );
+    let offset = |i: usize| u16::from_le_bytes([data[2 + 2 * i], data[3 + 2 * i]]);
+    let (signature_offset, signature_ix) = (offset(0) as usize, offset(1));
+    let (pubkey_offset, pubkey_ix) = (offset(2) as usize, offset(3));
+    let (message_offset, message_len, message_ix) =
+        (offset(4) as usize, offset(5) as usize, offset(6));
+    require!(
+        [signature_ix, pubkey_ix, message_ix]
+            .iter()
+            .all(|&i| i == u16::MAX)
+            && message_len == MESSAGE_LEN
+            && data.len() >= signature_offset + SIGNATURE_LEN
+            && data.len() >= pubkey_offset + 32
+            && data.len() >= message_offset + MESSAGE_LEN,
+        EventFluxError::InvalidCheckInSignature
+    );
+
+    let message = &data[message_offset..message_offset + MESSAGE_LEN];
+    require!(
+        message[..32] == pass_key.to_bytes()
+            && message[32..40] == event_pass.pass_index.to_le_bytes()
+            && message[40..PREFIX_LEN] == event_pass.checkin_nonce.to_le_bytes(),
+        EventFluxError::InvalidCheckInSignature
+    );
+    let verifier = Pubkey::try_from(&data[pubkey_offset..pubkey_offset + 32])
+        .map_err(|_| EventFluxError::InvalidCheckInSignature)?;
+    let signed_at = i64::from_le_bytes(
+        message[PREFIX_LEN..]
+            .try_into()
+            .map_err(|_| EventFluxError::InvalidCheckInSignature)?,
+    );
+    Ok((verifier, signed_at))
+}
+
+/// Marks an admitted scan at `at`: the first entry checks the pass in, later
+/// ones count as re-entries.
+fn record_entry(
+    event: &mut Event,
+    event_pass: &mut EventPass,
+    organizer_stats: &mut OrganizerStats,
+    at: i64,
+) -> Result<()> {
+    if event_pass.checked_in {
+        event_pass.reentry_count = event_pass
+            .reentry_count
+            .checked_add(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+    } else {
+        event_pass.checked_in = true;
+        event_pass.checked_in_at = Some(at);
+        event.checked_in_count = event
+            .checked_in_count
+            .checked_add(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+        organizer_stats.record_checkin()?;
+    }
+    event_pass.last_entry_at = Some(at);
+    Ok(())
+}
+
+/// Undoes `record_entry`: the pass is no longer checked in and stops counting
+/// toward the event's and the organizer's check-ins.
+fn clear_entry(
+    event: &mut Event,
+    event_pass: &mut EventPass,
+    organizer_stats: &mut OrganizerStats,
+) -> Result<()> {
+    if event_pass.checked_in {
+        event.checked_in_count = event
+            .checked_in_count
+            .checked_sub(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+        organizer_stats.unrecord_checkin()?;
+    }
+    event_pass.checked_in = false;
+    event_pass.checked_in_at = None;
+    event_pass.reentry_count = 0;
+    event_pass.last_entry_at = None;
+    Ok(())
+}
+
+/// Asks the event's external authorization program whether `verifier` may scan
+/// `event_pass`. The first remaining account must be that program; any further
+/// remaining accounts are forwarded to it read-only.
+fn authorize_via_program<'info>(
+    auth_program: &Pubkey,
+    verifier: &AccountInfo<'info>,
+    event_pass: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<bool> {
+    let (program_info, extra_accounts) = remaining_accounts
+        .split_first()
+        .ok_or(EventFluxError::AuthProgramMissing)?;
+    require_keys_eq!(
+        program_info.key(),
+        *auth_program,
+        EventFluxError::AuthProgramMismatch
+    );
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(verifier.key(), true),
+        AccountMeta::new_readonly(event_pass.key(), false),
+    ];
+    metas.extend(
+        extra_accounts
+            .iter()
+            .map(|account| AccountMeta::new_readonly(account.key(), account.is_signer)),
+    );
+
+    let mut infos = vec![verifier.clone(), event_pass.clone()];
+    infos.extend_from_slice(extra_accounts);
+    infos.push(program_info.clone());
+
+    invoke(
+        &Instruction {
+            program_id: *auth_program,
+            accounts: metas,
+            data: AUTHORIZE_IX_DISCRIMINATOR.to_vec(),
+        },
+        &infos,
+    )?;
+
+    let (returning_program, data) =
+        get_return_data().ok_or(EventFluxError::AuthProgramNoResponse)?;
+    require_keys_eq!(
+        returning_program,
+        *auth_program,
+        EventFluxError::AuthProgramNoResponse
+    );
+
+    Ok(data.first() == Some(&1))
+}
+
+/// Books a new pass against `tier_id`: bumps the tier and event counters and
+/// fills in `event_pass`. Returns the price the caller must collect.
+/// Shared body of `mint_pass` and `mint_pass_allowlisted`.
+fn mint_sol_pass(
+    ctx: Context<MintPass>,
+    tier_id: u8,
+    max_lamports: u64,
+    proof: Option<&[[u8; 32]]>,
+    referrer: Option<Pubkey>,
+) -> Result<()> {
+    ctx.accounts.vault_state.currency.require_sol()?;
+    let now = Clock::get()?.unix_timestamp;
+    let tier_price = record_pass_mint(
+        &mut ctx.accounts.event,
+        &mut ctx.accounts.event_pass,
+        ctx.bumps.event_pass,
+        ctx.accounts.attendee.key(),
+        tier_id,
+        max_lamports,
+        now,
+        proof,
+    )?;
+    record_wallet_mint(
+        &mut ctx.accounts.attendee_record,
+        ctx.bumps.attendee_record,
+        &ctx.accounts.event,
+        ctx.accounts.event.key(),
+        ctx.accounts.attendee.key(),
+    )?;
+    ctx.accounts.organizer_stats.record_pass()?;
+    append_guest(
+        &mut ctx.accounts.guest_list,
+        ctx.accounts.event_pass.key(),
+        &ctx.accounts.attendee,
+        &ctx.accounts.system_program,
+    )?;
+    let fee = pay_protocol_fee(
+        &ctx.accounts.protocol_config,
+        ctx.accounts.fee_collector.as_ref(),
+        &ctx.accounts.attendee.to_account_info(),
+        tier_price,
+    )?;
+    let referral = match referrer {
+        Some(referrer) => {
+            require_keys_neq!(
+                referrer,
+                ctx.accounts.attendee.key(),
+                EventFluxError::SelfReferral
+            );
+            let referrer = ctx
+                .accounts
+                .referrer
+                .as_ref()
+                .filter(|account| account.key() == referrer)
+                .ok_or(EventFluxError::InvalidReferral)?;
+            let referral = u64::try_from(
+                u128::from(tier_price) * u128::from(ctx.accounts.event.referral_bps)
+                    / BPS_DENOMINATOR,
+            )
+            .map_err(|_| EventFluxError::MathOverflow)?;
+            if referral > 0 {
+                invoke(
+                    &system_instruction::transfer(
+                        &ctx.accounts.attendee.key(),
+                        &referrer.key(),
+                        referral,
+                    ),
+                    &[
+                        ctx.accounts.attendee.to_account_info(),
+                        referrer.to_account_info(),
+                    ],
+                )?;
+            }
+            referral
+        }
+        None => 0,
+    };
+    ctx.accounts.vault_state.record_fee(fee)?;
+    let event_pass = &mut ctx.accounts.event_pass;
+    event_pass.fee_paid = fee;
+    event_pass.referral_paid = referral;
+    let net_price = event_pass.treasury_share()?;
+
+    let deposit = ctx.accounts.event_pass.deposit_lamports;
+    let charge = net_price
+        .checked_add(deposit)
+        .ok_or(EventFluxError::MathOverflow)?;
+
+    invoke_signed(
+        &system_instruction::transfer(
+            &ctx.accounts.attendee.key(),
+            &ctx.accounts.vault_treasury.key(),
+            charge,
+        ),
+        &[
+            ctx.accounts.attendee.to_account_info(),
+            ctx.accounts.vault_treasury.to_account_info(),
+        ],
+        &[],
+    )?;
+
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.hold_deposit(deposit)?;
+    record_deposit(vault_state, net_price, now)?;
+    emit!(PassMinted {
+        event: ctx.accounts.event.key(),
+        attendee: ctx.accounts.attendee.key(),
+        tier_id,
+        price_paid: tier_price,
+    });
+
+    Ok(())
+}
+
+/// Platform fee due on `price` and where it goes. Until the protocol config
+/// is initialized there is no fee, so existing events are unaffected.
+fn protocol_fee(protocol_config: &AccountInfo, price: u64) -> Result<(u64, Pubkey)> {
+    if protocol_config.data_is_empty() {
+        return Ok((0, Pubkey::default()));
+    }
+    let protocol_config =
+        ProtocolConfig::try_deserialize(&mut &protocol_config.try_borrow_data()?[..])?;
+    let fee =
+        u64::try_from(u128::from(price) * u128::from(protocol_config.fee_bps) / BPS_DENOMINATOR)
+            .map_err(|_| EventFluxError::MathOverflow)?;
+    Ok((fee, protocol_config.fee_collector))
+}
+
+/// Charges the platform fee on `price` from `payer` to the configured
+/// collector and returns it. Every paid SOL mint path goes through here, so
+/// none of them can skip the fee. `payer` is a wallet, or the program-owned
+/// treasury when the pass is paid from prepaid credit.
+fn pay_protocol_fee<'info>(
+    protocol_config: &UncheckedAccount<'info>,
+    fee_collector: Option<&UncheckedAccount<'info>>,
+    payer: &AccountInfo<'info>,
+    price: u64,
+) -> Result<u64> {
+    let (fee, collector_key) = protocol_fee(protocol_config, price)?;
+    if fee == 0 {
+        return Ok(0);
+    }
+    let collector = fee_collector
+        .filter(|collector| collector.key() == collector_key)
+        .ok_or(EventFluxError::InvalidFeeCollector)?;
+    if payer.owner == &crate::ID {
+        let remaining = payer
+            .lamports()
+            .checked_sub(fee)
+            .ok_or(EventFluxError::TreasuryDeficit)?;
+        **payer.try_borrow_mut_lamports()? = remaining;
+        **collector.try_borrow_mut_lamports()? += fee;
+    } else {
+        invoke(
+            &system_instruction::transfer(payer.key, &collector.key(), fee),
+            &[payer.clone(), collector.to_account_info()],
+        )?;
+    }
+    Ok(fee)
+}
+
+/// Counts a mint against the wallet's cap, initialising the record on the
+/// wallet's first mint.
+fn record_wallet_mint(
+    record: &mut Account<AttendeeRecord>,
+    record_bump: u8,
+    event: &Event,
+    event_key: Pubkey,
+    owner: Pubkey,
+) -> Result<()> {
+    record.bump = record_bump;
+    record.event = event_key;
+    record.owner = owner;
+    record.minted = record
+        .minted
+        .checked_add(1)
+        .ok_or(EventFluxError::MathOverflow)?;
+    require!(
+        event.max_passes_per_wallet == 0 || record.minted <= u32::from(event.max_passes_per_wallet),
+        EventFluxError::WalletLimitReached
+    );
+    Ok(())
+}
+
+/// Leaves are `sha256(owner)`; each level hashes the sorted pair, so proofs
+/// don't need to carry left/right positions.
+fn allowlist_contains(root: &[u8; 32], proof: &[[u8; 32]], owner: &Pubkey) -> bool {
+    let leaf = hashv(&[owner.as_ref()]).to_bytes();
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
+
+/// `proof` is only supplied by `mint_pass_allowlisted`; every other mint path
+/// passes `None` and is closed while the event has an allowlist.
+#[allow(clippy::too_many_arguments)]
+fn record_pass_mint(
+    event: &mut Account<Event>,
+    event_pass: &mut Account<EventPass>,
+    pass_bump: u8,
+    owner: Pubkey,
+    tier_id: u8,
+    max_lamports: u64,
+    now: i64,
+    proof: Option<&[[u8; 32]]>,
+) -> Result<u64> {
+    require!(!event.cancelled, EventFluxError::EventCancelled);
+    require!(!event.sales_paused, EventFluxError::SalesPaused);
+    require!(now >= event.sale_start_ts, EventFluxError::SaleNotOpen);
+    if let Some(root) = &event.allowlist_root {
+        require!(
+            proof.is_some_and(|proof| allowlist_contains(root, proof, &owner)),
+            EventFluxError::NotOnAllowlist
+        );
+    }
+    require!(now < event.end_ts, EventFluxError::EventEnded);
+    require!(event.tiers_finalized, EventFluxError::TiersNotFinalized);
+    require!(!event.is_banned(&owner), EventFluxError::BuyerBanned);
+    let event_key = event.key();
+
+    let (tier_price, deposit, benefits, renewal_period) = {
+        let tier = event
+            .tiers
+            .iter_mut()
+            .find(|t| t.tier_id == tier_id)
+            .ok_or(EventFluxError::TierNotFound)?;
+
+        require!(!tier.is_sold_out(), EventFluxError::TierSoldOut);
+
+        tier.sold = tier
+            .sold
+            .checked_add(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+        if tier.sold == tier.max_supply {
+            tier.sold_out_at = Some(now);
+            emit!(TierSoldOut {
+                event: event_key,
+                tier_id,
+                sold_out_at: now,
+            });
+        }
+        (
+            tier.current_price(now),
+            tier.deposit_lamports,
+            tier.benefits,
+            tier.renewal_period,
+        )
+    };
+    require!(
+        max_lamports == 0 || tier_price <= max_lamports,
+        EventFluxError::PriceSlippageExceeded
+    );
+
+    event.total_passes = event
+        .total_passes
+        .checked_add(1)
+        .ok_or(EventFluxError::MathOverflow)?;
+    let pass_index = event.next_pass_index;
+    event.next_pass_index = pass_index
+        .checked_add(1)
+        .ok_or(EventFluxError::MathOverflow)?;
+
+    event_pass.bump = pass_bump;
+    event_pass.event = event_key;
+    event_pass.owner = owner;
+    event_pass.pass_index = pass_index;
+    event_pass.tier_id = tier_id;
+    event_pass.benefits = benefits;
+    event_pass.price_paid = tier_price;
+    event_pass.fee_paid = 0;
+    event_pass.referral_paid = 0;
+    event_pass.deposit_lamports = deposit;
+    event_pass.minted_at = now;
+    event_pass.checked_in = false;
+    event_pass.checked_in_at = None;
+    event_pass.reentry_count = 0;
+    event_pass.last_entry_at = None;
+    event_pass.loyalty_mint = None;
+    event_pass.lucky_winner = false;
+    event_pass.valid_until = renewal_period.map(|period| now + period);
+    event_pass.custodian = None;
+    event_pass.dividend_claimed = false;
+
+    Ok(tier_price)
+}
+
+/// Splits the treasury balance into what settlement pays out and where it
+/// came from. `withdraw_treasury` and `preview_settlement` both use this so
+/// the preview always matches the real payout.
+fn settlement_breakdown(
+    event: &Event,
+    vault_state: &VaultState,
+    treasury_balance: u64,
+    rent_reserve: u64,
+) -> Result<SettlementBreakdown> {
+    require!(treasury_balance > 0, EventFluxError::NothingToWithdraw);
+
+    let unclaimed_yield = vault_state.unclaimed_yield();
+    let attendee_dividends = event
+        .dividend_per_pass(unclaimed_yield)
+        .checked_mul(event.checked_in_count)
+        .ok_or(EventFluxError::MathOverflow)?;
+    // Dividends keep the treasury open, so its rent reserve stays as well.
+    let retained = if attendee_dividends > 0 {
+        attendee_dividends
+            .checked_add(rent_reserve)
+            .ok_or(EventFluxError::MathOverflow)?
+    } else {
+        0
+    };
+    let payable = treasury_balance
+        .checked_sub(retained)
+        .ok_or(EventFluxError::TreasuryDeficit)?;
+
+    // Deployed principal is still in the adapter, so only idle principal is
+    // counted against what the treasury actually holds.
+    let principal = vault_state.idle_balance().min(payable);
+    let forfeited_deposits = vault_state.deposits_held.min(payable - principal);
+    let yield_harvested =
+        (unclaimed_yield - attendee_dividends).min(payable - principal - forfeited_deposits);
+
+    Ok(SettlementBreakdown {
+        destination: event.settlement_treasury,
+        currency: vault_state.currency.clone(),
+        principal,
+        forfeited_deposits,
+        yield_harvested,
+        other: payable - principal - forfeited_deposits - yield_harvested,
+        attendee_dividends,
+        total_payout: payable,
+    })
+}
+
+fn record_deposit(vault_state: &mut VaultState, amount: u64, now: i64) -> Result<()> {
+    vault_state.total_deposited = vault_state
+        .total_deposited
+        .checked_add(amount)
+        .ok_or(EventFluxError::MathOverflow)?;
+    if vault_state.first_deposit_ts == 0 {
+        vault_state.first_deposit_ts = now;
+    }
+    Ok(())
+}
+
+/// Pays a pass's treasury share and held deposit back to `owner` and gives
+/// its seat back to the tier. On a cancelled event the platform fee is paid
+/// back too, as far as `refund_fee` has returned fees to the treasury; the
+/// referral share isn't recovered. The caller closes the pass. Returns what
+/// was paid.
+fn refund_to_owner(
+    event: &mut Event,
+    vault_state: &mut VaultState,
+    organizer_stats: &mut OrganizerStats,
+    guest_list: &mut GuestList,
+    treasury: &AccountInfo,
+    owner: &AccountInfo,
+    event_pass: &EventPass,
+) -> Result<u64> {
+    let refund = release_pass(event, vault_state, organizer_stats, guest_list, event_pass)?;
+    debit_treasury(treasury, refund)?;
+    **owner.try_borrow_mut_lamports()? += refund;
+
+    vault_state.total_refunded = vault_state
+        .total_refunded
+        .checked_add(refund)
+        .ok_or(EventFluxError::MathOverflow)?;
+    Ok(refund)
+}
+
+/// Takes a pass off the books as if it was never sold and returns the refund
+/// it is owed, leaving the lamports for the caller to move.
+fn release_pass(
+    event: &mut Event,
+    vault_state: &mut VaultState,
+    organizer_stats: &mut OrganizerStats,
+    guest_list: &mut GuestList,
+    event_pass: &EventPass,
+) -> Result<u64> {
+    let treasury_share = event_pass.treasury_share()?;
+    let deposit = event_pass.deposit_lamports;
+    let fee_back = if event.cancelled {
+        event_pass.fee_paid.min(vault_state.fee_refunds_held)
+    } else {
+        0
+    };
+    let refund = treasury_share
+        .checked_add(deposit)
+        .and_then(|refund| refund.checked_add(fee_back))
+        .ok_or(EventFluxError::MathOverflow)?;
+    vault_state.fee_refunds_held -= fee_back;
+
+    let tier = event
+        .tiers
+        .iter_mut()
+        .find(|t| t.tier_id == event_pass.tier_id)
+        .ok_or(EventFluxError::TierNotFound)?;
+    tier.sold = tier
+        .sold
+        .checked_sub(1)
+        .ok_or(EventFluxError::MathOverflow)?;
+    tier.sold_out_at = None;
+    event.total_passes = event
+        .total_passes
+        .checked_sub(1)
+        .ok_or(EventFluxError::MathOverflow)?;
+    organizer_stats.unrecord_pass()?;
+    guest_list.replace(event_pass.pass_index, Pubkey::default())?;
+
+    vault_state.total_deposited = vault_state
+        .total_deposited
+        .checked_sub(treasury_share)
+        .ok_or(EventFluxError::MathOverflow)?;
+    vault_state.deposits_held = vault_state
+        .deposits_held
+        .checked_sub(deposit)
+        .ok_or(EventFluxError::MathOverflow)?;
+    Ok(refund)
+}
+
+/// Takes `amount` out of a program-owned treasury without touching its
+/// rent-exempt minimum. Only settlement, which closes the account, may go
+/// below it.
+fn debit_treasury(treasury: &AccountInfo, amount: u64) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(treasury.data_len());
+    require!(
+        amount <= treasury.lamports().saturating_sub(rent),
+        EventFluxError::TreasuryDeficit
+    );
+    let remaining = treasury
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(EventFluxError::MathOverflow)?;
+    **treasury.try_borrow_mut_lamports()? = remaining;
+    Ok(())
+}
+
+/// Treasury lamports free to move: the balance above the rent reserve, less
+/// the deposits and fee refunds held back for pass holders.
+fn spendable_balance(treasury: &AccountInfo, vault_state: &VaultState) -> Result<u64> {
+    Ok(treasury
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(treasury.data_len()))
+        .saturating_sub(vault_state.deposits_held)
+        .saturating_sub(vault_state.fee_refunds_held))
+}
+
+/// Instructions that pay lamports out of the treasury must be called directly
+/// by the transaction, not through another program, unless the event opted
+/// into composability with `allow_cpi`. Otherwise a program that ends up
+/// holding the right signers could drain funds from inside its own logic.
+fn require_top_level(event: &Event) -> Result<()> {
+    require!(
+        event.allow_cpi || get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT,
+        EventFluxError::CpiNotAllowed
+    );
+    Ok(())
+}
+
+/// Records a newly minted pass on the event's guest list, growing the account
+/// by one entry at the minter's expense.
+fn append_guest<'info>(
+    guest_list: &mut Account<'info, GuestList>,
+    event_pass: Pubkey,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    guest_list.passes.push(event_pass);
+    let new_space = guest_list.space();
+    resize_account(
+        &guest_list.to_account_info(),
+        payer,
+        system_program,
+        new_space,
+    )
+}
+
+/// Reallocates a program-owned account to `new_space`, topping up rent from
+/// `payer` when growing and returning the excess to `payer` when shrinking.
+fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_space: usize,
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(new_space);
+    let current = account.lamports();
+
+    if required > current {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            required - current,
+        )?;
+    } else if current > required {
+        let excess = current - required;
+        **account.try_borrow_mut_lamports()? -= excess;
+        **payer.to_account_info().try_borrow_mut_lamports()? += excess;
+    }
+
+    account.resize(new_space)?;
+    Ok(())
+}
+
+fn create_vault_treasury_if_needed<'info>(
+    payer: &Signer<'info>,
+    vault_treasury: &UncheckedAccount<'info>,
+    rent: u64,
+    system_program: &Program<'info, System>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    if vault_treasury.to_account_info().owner == &crate::ID {
+        return Ok(());
+    }
+
+    let ix = system_instruction::create_account(
+        &payer.key(),
+        &vault_treasury.key(),
+        rent,
+        0,
         &crate::ID,
     );
 
-    invoke_signed(
-        &ix,
-        &[
-            payer.to_account_info(),
-            vault_treasury.to_account_info(),
-            system_program.to_account_info(),
-        ],
-        &[signer_seeds],
-    )
-    .map_err(|_| error!(EventFluxError::VaultCreationFailed))?;
+    invoke_signed(
+        &ix,
+        &[
+            payer.to_account_info(),
+            vault_treasury.to_account_info(),
+            system_program.to_account_info(),
+        ],
+        &[signer_seeds],
+    )
+    .map_err(|_| error!(EventFluxError::VaultCreationFailed))?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(args: CreateEventArgs)]
+pub struct CreateEvent<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        init,
+        payer = organizer,
+        space = Event::checked_size_with_args(&args)?,
+        seeds = [EVENT_SEED, organizer.key().as_ref(), &args.event_id.to_le_bytes()],
+        bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        init,
+        payer = organizer,
+        space = VaultState::SPACE,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = OrganizerStats::SPACE,
+        seeds = [ORGANIZER_STATS_SEED, organizer.key().as_ref()],
+        bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        init,
+        payer = organizer,
+        space = GuestList::space_for(0),
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEventMetadata<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetEventImages<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeEventTreasury<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: derived PDA that temporarily stores ticket proceeds, only accessed within program
+    pub vault_treasury: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintPass<'info> {
+    #[account(mut)]
+    pub attendee: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+        constraint = vault_state.treasury_initialized @ EventFluxError::TreasuryNotInitialized,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        init,
+        payer = attendee,
+        space = EventPass::SPACE,
+        seeds = [
+            PASS_SEED,
+            event.key().as_ref(),
+            attendee.key().as_ref(),
+            &event.next_pass_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        init_if_needed,
+        payer = attendee,
+        space = AttendeeRecord::SPACE,
+        seeds = [ATTENDEE_RECORD_SEED, event.key().as_ref(), attendee.key().as_ref()],
+        bump,
+    )]
+    pub attendee_record: Account<'info, AttendeeRecord>,
+    #[account(seeds = [PROTOCOL_CONFIG_SEED], bump)]
+    /// CHECK: read by `protocol_fee` only once initialized; required so a
+    /// buyer can't leave it out to skip the fee
+    pub protocol_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: must match `ProtocolConfig::fee_collector` when a fee is due
+    pub fee_collector: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    /// CHECK: must match the `referrer` argument when one is given
+    pub referrer: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintPassSpl<'info> {
+    #[account(mut)]
+    pub attendee: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program; owns the token vault
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        constraint = vault_state.currency == Currency::Spl(payment_mint.key())
+            @ EventFluxError::CurrencyMismatch,
+    )]
+    pub payment_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = attendee,
+    )]
+    pub attendee_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = attendee,
+        seeds = [TOKEN_VAULT_SEED, event.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = vault_treasury,
+    )]
+    pub token_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        init,
+        payer = attendee,
+        space = EventPass::SPACE,
+        seeds = [
+            PASS_SEED,
+            event.key().as_ref(),
+            attendee.key().as_ref(),
+            &event.next_pass_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        init_if_needed,
+        payer = attendee,
+        space = AttendeeRecord::SPACE,
+        seeds = [ATTENDEE_RECORD_SEED, event.key().as_ref(), attendee.key().as_ref()],
+        bump,
+    )]
+    pub attendee_record: Account<'info, AttendeeRecord>,
+    #[account(seeds = [PROTOCOL_CONFIG_SEED], bump)]
+    /// CHECK: read by `protocol_fee` only once initialized; required so a
+    /// buyer can't leave it out to skip the fee
+    pub protocol_config: UncheckedAccount<'info>,
+    #[account(mut, token::mint = payment_mint)]
+    pub fee_collector_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier_id: u8, max_lamports: u64, beneficiary: Pubkey)]
+pub struct MintPassInCustody<'info> {
+    #[account(mut)]
+    pub custodian: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+        constraint = vault_state.treasury_initialized @ EventFluxError::TreasuryNotInitialized,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        init,
+        payer = custodian,
+        space = EventPass::SPACE,
+        seeds = [
+            PASS_SEED,
+            event.key().as_ref(),
+            beneficiary.as_ref(),
+            &event.next_pass_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        init_if_needed,
+        payer = custodian,
+        space = AttendeeRecord::SPACE,
+        seeds = [ATTENDEE_RECORD_SEED, event.key().as_ref(), beneficiary.as_ref()],
+        bump,
+    )]
+    pub attendee_record: Account<'info, AttendeeRecord>,
+    #[account(seeds = [PROTOCOL_CONFIG_SEED], bump)]
+    /// CHECK: read by `protocol_fee` only once initialized; required so a
+    /// buyer can't leave it out to skip the fee
+    pub protocol_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: must match `ProtocolConfig::fee_collector` when a fee is due
+    pub fee_collector: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCustody<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.owner == owner.key(),
+    )]
+    pub event_pass: Account<'info, EventPass>,
+}
+
+#[derive(Accounts)]
+pub struct DepositCredit<'info> {
+    #[account(mut)]
+    pub attendee: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+        constraint = vault_state.treasury_initialized @ EventFluxError::TreasuryNotInitialized,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = attendee,
+        space = CreditAccount::SPACE,
+        seeds = [CREDIT_SEED, event.key().as_ref(), attendee.key().as_ref()],
+        bump,
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintPassWithCredit<'info> {
+    #[account(mut)]
+    pub attendee: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA holding the credit the pass is paid from
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [CREDIT_SEED, event.key().as_ref(), attendee.key().as_ref()],
+        bump = credit_account.bump,
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        init,
+        payer = attendee,
+        space = EventPass::SPACE,
+        seeds = [
+            PASS_SEED,
+            event.key().as_ref(),
+            attendee.key().as_ref(),
+            &event.next_pass_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        init_if_needed,
+        payer = attendee,
+        space = AttendeeRecord::SPACE,
+        seeds = [ATTENDEE_RECORD_SEED, event.key().as_ref(), attendee.key().as_ref()],
+        bump,
+    )]
+    pub attendee_record: Account<'info, AttendeeRecord>,
+    #[account(seeds = [PROTOCOL_CONFIG_SEED], bump)]
+    /// CHECK: read by `protocol_fee` only once initialized; required so a
+    /// buyer can't leave it out to skip the fee
+    pub protocol_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: must match `ProtocolConfig::fee_collector` when a fee is due
+    pub fee_collector: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCredit<'info> {
+    #[account(mut)]
+    pub attendee: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [CREDIT_SEED, event.key().as_ref(), attendee.key().as_ref()],
+        bump = credit_account.bump,
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimDeposit<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+        constraint = event_pass.owner == owner.key(),
+    )]
+    pub event_pass: Account<'info, EventPass>,
+}
+
+#[derive(Accounts)]
+pub struct CheckIn<'info> {
+    pub verifier: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+}
+
+#[derive(Accounts)]
+pub struct BatchCheckIn<'info> {
+    pub verifier: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+}
+
+#[derive(Accounts)]
+pub struct CheckInSigned<'info> {
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    /// CHECK: the instructions sysvar, read to find the ed25519 signature check
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PayAndCheckin<'info> {
+    pub operator: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: only the key is used, as the owner of the new pass
+    pub attendee: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+        constraint = vault_state.treasury_initialized @ EventFluxError::TreasuryNotInitialized,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        init,
+        payer = payer,
+        space = EventPass::SPACE,
+        seeds = [
+            PASS_SEED,
+            event.key().as_ref(),
+            attendee.key().as_ref(),
+            &event.next_pass_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = AttendeeRecord::SPACE,
+        seeds = [ATTENDEE_RECORD_SEED, event.key().as_ref(), attendee.key().as_ref()],
+        bump,
+    )]
+    pub attendee_record: Account<'info, AttendeeRecord>,
+    #[account(seeds = [PROTOCOL_CONFIG_SEED], bump)]
+    /// CHECK: read by `protocol_fee` only once initialized; required so a
+    /// buyer can't leave it out to skip the fee
+    pub protocol_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: must match `ProtocolConfig::fee_collector` when a fee is due
+    pub fee_collector: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckInDryRun<'info> {
+    pub verifier: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(mut, address = event.settlement_treasury)]
+    pub destination: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: settlement PDA scoped to this event, drained at withdrawal
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+}
+
+#[derive(Accounts)]
+pub struct RefundPass<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+        constraint = event_pass.owner == owner.key(),
+    )]
+    pub event_pass: Account<'info, EventPass>,
+}
+
+#[derive(Accounts)]
+pub struct RefundBatch<'info> {
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawDust<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(mut, address = event.settlement_treasury)]
+    pub destination: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: settlement PDA scoped to this event; may be program- or system-owned after settlement
+    pub vault_treasury: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimYieldDividend<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+        constraint = event_pass.owner == owner.key(),
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA holding the dividends after settlement
+    pub vault_treasury: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewSettlement<'info> {
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA whose balance settlement would drain
+    pub vault_treasury: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestYield<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: PDA receiving harvested yield before settlement
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [vault_stub::ADAPTER_RESERVE_SEED, vault_state.key().as_ref()],
+        bump,
+        seeds::program = vault_state.adapter_program,
+        constraint = adapter_reserve.owner == &vault_state.adapter_program
+            @ EventFluxError::AdapterMismatch,
+    )]
+    /// CHECK: this event's reserve in the vault's adapter; the adapter validates its contents
+    pub adapter_reserve: UncheckedAccount<'info>,
+    #[account(
+        executable,
+        constraint = vault_adapter_program.key() == vault_state.adapter_program
+            @ EventFluxError::AdapterMismatch,
+    )]
+    /// CHECK: must be the adapter recorded on the vault at creation
+    pub vault_adapter_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawYield<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub yield_destination: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToAdapter<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [vault_stub::ADAPTER_RESERVE_SEED, vault_state.key().as_ref()],
+        bump,
+        seeds::program = vault_state.adapter_program,
+        constraint = adapter_reserve.owner == &vault_state.adapter_program
+            @ EventFluxError::AdapterMismatch,
+    )]
+    /// CHECK: this event's reserve in the vault's adapter; only credited here
+    pub adapter_reserve: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAdapterReserve<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [vault_stub::ADAPTER_RESERVE_SEED, vault_state.key().as_ref()],
+        bump,
+        seeds::program = vault_state.adapter_program,
+    )]
+    /// CHECK: created by the adapter, which checks it is still empty
+    pub adapter_reserve: UncheckedAccount<'info>,
+    #[account(
+        executable,
+        constraint = vault_adapter_program.key() == vault_state.adapter_program
+            @ EventFluxError::AdapterMismatch,
+    )]
+    /// CHECK: must be the adapter recorded on the vault at creation
+    pub vault_adapter_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecallFromAdapter<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA receiving the recalled principal
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [vault_stub::ADAPTER_RESERVE_SEED, vault_state.key().as_ref()],
+        bump,
+        seeds::program = vault_state.adapter_program,
+        constraint = adapter_reserve.owner == &vault_state.adapter_program
+            @ EventFluxError::AdapterMismatch,
+    )]
+    /// CHECK: this event's reserve in the vault's adapter; the adapter validates its contents
+    pub adapter_reserve: UncheckedAccount<'info>,
+    #[account(
+        executable,
+        constraint = vault_adapter_program.key() == vault_state.adapter_program
+            @ EventFluxError::AdapterMismatch,
+    )]
+    /// CHECK: must be the adapter recorded on the vault at creation
+    pub vault_adapter_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SwitchYieldStrategy<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+#[derive(Accounts)]
+pub struct SetHarvestPaused<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileTreasury<'info> {
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA whose balance is compared against the ledger
+    pub vault_treasury: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SimulateMintPrice<'info> {
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct GetEventSummary<'info> {
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program; may not be initialized yet
+    pub vault_treasury: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetOrganizerDashboard<'info> {
+    #[account(
+        seeds = [ORGANIZER_STATS_SEED, organizer_stats.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+}
+
+#[derive(Accounts)]
+pub struct GetYieldMetrics<'info> {
+    #[account(
+        seeds = [VAULT_STATE_SEED, vault_state.event.as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+#[derive(Accounts)]
+pub struct EstimateRent {}
+
+#[derive(Accounts)]
+pub struct AddTier<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeTiers<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeEventConfig<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct CancelEvent<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetSalesPaused<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetRefundDeadline<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetRefundClaimWindow<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = ProtocolConfig::SPACE,
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::AnchorProject>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ EventFluxError::NotProtocolAdmin,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        constraint = protocol_config.admin == admin.key() @ EventFluxError::NotProtocolAdmin,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefundFee<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        constraint = protocol_config.admin == admin.key() @ EventFluxError::NotProtocolAdmin,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(
+        mut,
+        address = protocol_config.fee_collector @ EventFluxError::InvalidFeeCollector,
+    )]
+    pub fee_collector: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    pub holder: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(seeds = [PROTOCOL_CONFIG_SEED], bump = protocol_config.bump)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+        constraint = event_pass.held_by(&holder.key()),
+    )]
+    pub event_pass: Account<'info, EventPass>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        constraint = protocol_config.admin == admin.key() @ EventFluxError::NotProtocolAdmin,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEmptyTier<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendEvent<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTier<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct IncreaseTierSupply<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct ManageVerifiers<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct ManageBanList<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetForfeitureSplit<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepForfeitures<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+}
+
+#[derive(Accounts)]
+pub struct AuthorizeRenewal<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(constraint = event_pass.owner == owner.key())]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        init,
+        payer = owner,
+        space = AutoRenewMandate::SPACE,
+        seeds = [MANDATE_SEED, event_pass.key().as_ref()],
+        bump,
+    )]
+    pub mandate: Account<'info, AutoRenewMandate>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RenewMembership<'info> {
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event.key().as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key(),
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        mut,
+        seeds = [MANDATE_SEED, event_pass.key().as_ref()],
+        bump = mandate.bump,
+        constraint = mandate.owner == event_pass.owner,
+    )]
+    pub mandate: Account<'info, AutoRenewMandate>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeMandate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [MANDATE_SEED, mandate.event_pass.as_ref()],
+        bump = mandate.bump,
+        constraint = mandate.owner == owner.key(),
+    )]
+    pub mandate: Account<'info, AutoRenewMandate>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct ReissuePass<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        close = organizer,
+        seeds = [
+            PASS_SEED,
+            old_pass.event.as_ref(),
+            old_pass.owner.as_ref(),
+            &old_pass.pass_index.to_le_bytes(),
+        ],
+        bump = old_pass.bump,
+        constraint = old_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+    )]
+    pub old_pass: Account<'info, EventPass>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        init,
+        payer = organizer,
+        space = EventPass::SPACE,
+        seeds = [
+            PASS_SEED,
+            event.key().as_ref(),
+            new_owner.as_ref(),
+            &old_pass.pass_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub new_pass: Account<'info, EventPass>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnsetCheckIn<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+}
+
+#[derive(Accounts)]
+pub struct ReissuePassToOwner<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferPass<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            PASS_SEED,
+            old_pass.event.as_ref(),
+            old_pass.owner.as_ref(),
+            &old_pass.pass_index.to_le_bytes(),
+        ],
+        bump = old_pass.bump,
+        constraint = old_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+        constraint = old_pass.owner == owner.key(),
+    )]
+    pub old_pass: Account<'info, EventPass>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        init,
+        payer = owner,
+        space = EventPass::SPACE,
+        seeds = [
+            PASS_SEED,
+            event.key().as_ref(),
+            new_owner.as_ref(),
+            &old_pass.pass_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub new_pass: Account<'info, EventPass>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BurnPass<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [ORGANIZER_STATS_SEED, event.organizer.as_ref()],
+        bump = organizer_stats.bump,
+    )]
+    pub organizer_stats: Account<'info, OrganizerStats>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+        constraint = event_pass.owner == owner.key(),
+    )]
+    pub event_pass: Account<'info, EventPass>,
+}
+
+#[derive(Accounts)]
+pub struct TransferPassWithPayment<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.event == event.key(),
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
+        bump = vault_state.vault_treasury_bump,
+    )]
+    /// CHECK: lamports-only PDA controlled by this program
+    pub vault_treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            PASS_SEED,
+            old_pass.event.as_ref(),
+            old_pass.owner.as_ref(),
+            &old_pass.pass_index.to_le_bytes(),
+        ],
+        bump = old_pass.bump,
+        constraint = old_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+        constraint = old_pass.owner == seller.key(),
+    )]
+    pub old_pass: Account<'info, EventPass>,
+    #[account(
+        mut,
+        seeds = [GUEST_LIST_SEED, event.key().as_ref()],
+        bump = guest_list.bump,
+    )]
+    pub guest_list: Account<'info, GuestList>,
+    #[account(
+        init,
+        payer = buyer,
+        space = EventPass::SPACE,
+        seeds = [
+            PASS_SEED,
+            event.key().as_ref(),
+            buyer.key().as_ref(),
+            &old_pass.pass_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub new_pass: Account<'info, EventPass>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IssueLoyaltyNft<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    /// CHECK: ensures minted token authority matches pass owner
+    #[account(address = event_pass.owner)]
+    pub pass_owner: UncheckedAccount<'info>,
+    #[account(
+        seeds = [LOYALTY_AUTHORITY_SEED, event.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: signing-only PDA; mints the loyalty token, then gives up authority
+    pub loyalty_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        seeds = [LOYALTY_MINT_SEED, event_pass.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = loyalty_authority,
+    )]
+    pub loyalty_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        associated_token::mint = loyalty_mint,
+        associated_token::authority = pass_owner,
+    )]
+    pub loyalty_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLoyalty<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+        constraint = event_pass.owner == owner.key(),
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        seeds = [LOYALTY_AUTHORITY_SEED, event.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: signing-only PDA used as mint authority for self-claimed rewards
+    pub loyalty_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [LOYALTY_MINT_SEED, event_pass.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = loyalty_authority,
+    )]
+    pub loyalty_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = loyalty_mint,
+        associated_token::authority = owner,
+    )]
+    pub loyalty_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct LuckyDrop<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+        constraint = event.organizer == organizer.key(),
+    )]
+    pub event: Account<'info, Event>,
+    /// CHECK: SlotHashes sysvar, read raw because it is too large to deserialize
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLuckyReward<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        seeds = [
+            PASS_SEED,
+            event_pass.event.as_ref(),
+            event_pass.owner.as_ref(),
+            &event_pass.pass_index.to_le_bytes(),
+        ],
+        bump = event_pass.bump,
+        constraint = event_pass.event == event.key() @ EventFluxError::PassEventMismatch,
+        constraint = event_pass.owner == owner.key(),
+    )]
+    pub event_pass: Account<'info, EventPass>,
+    #[account(
+        seeds = [LOYALTY_AUTHORITY_SEED, event.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: signing-only PDA used as mint authority for program-issued rewards
+    pub loyalty_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [LUCKY_MINT_SEED, event_pass.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = loyalty_authority,
+    )]
+    pub lucky_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = lucky_mint,
+        associated_token::authority = owner,
+    )]
+    pub lucky_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[account]
+pub struct Event {
+    pub bump: u8,
+    pub event_id: u64,
+    pub organizer: Pubkey,
+    pub settlement_treasury: Pubkey,
+    pub name: String,
+    pub venue: String,
+    /// Hash of the off-chain event details, so clients can detect edits.
+    pub metadata_hash: [u8; 32],
+    /// Listing artwork, kept apart from the hashed metadata so UIs can show
+    /// it without fetching anything else.
+    pub image_uri: Option<String>,
+    pub thumbnail_uri: Option<String>,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub yield_strategy: YieldStrategy,
+    pub tiers: Vec<TierConfig>,
+    pub authorized_verifiers: Vec<Pubkey>,
+    /// Capacity reserved for `authorized_verifiers` when the account was
+    /// created.
+    pub max_verifiers: u8,
+    /// Wallets the organizer has barred from buying passes.
+    pub banned: Vec<Pubkey>,
+    pub strict_tier_order: bool,
+    /// Sales stay closed until the organizer has set up the launch tiers;
+    /// later tiers can still be released with `add_tier` until the config is
+    /// frozen.
+    pub tiers_finalized: bool,
+    pub auth_program: Option<Pubkey>,
+    /// When false, loyalty NFTs can be issued at purchase instead of only
+    /// after check-in.
+    pub loyalty_requires_checkin: bool,
+    /// Minimum seconds between check-in and loyalty issuance; zero disables.
+    pub loyalty_delay_secs: i64,
+    /// Re-entries allowed after the first check-in; zero means single entry.
+    pub max_reentries: u8,
+    /// Organizer cut of peer-to-peer resales, in basis points.
+    pub resale_royalty_bps: u16,
+    /// Highest price a pass may be resold for; `None` leaves it uncapped.
+    pub max_resale_lamports: Option<u64>,
+    /// Lets other programs invoke the withdrawal instructions through CPI.
+    pub allow_cpi: bool,
+    /// Set once by `freeze_event_config`; blocks every config mutator.
+    pub config_frozen: bool,
+    /// Raised by a pass holder; blocks settlement until the protocol admin
+    /// resolves it.
+    pub disputed: bool,
+    /// Lets holders take their money back with `refund_pass`.
+    pub cancelled: bool,
+    /// When the event was cancelled; starts `refund_claim_window`.
+    pub cancelled_at: Option<i64>,
+    /// Seconds holders of a cancelled event have to claim their refund.
+    pub refund_claim_window: i64,
+    /// Set by `set_sales_paused`; blocks new mints until cleared.
+    pub sales_paused: bool,
+    /// Until this time holders of refundable tiers may hand back an unused
+    /// pass with `refund_pass`; `None` allows refunds only on cancellation.
+    pub refund_deadline: Option<i64>,
+    /// Merkle root of invited wallets. When set, every tier sells only through
+    /// `mint_pass_allowlisted`; open and invite-only sales can't run side by
+    /// side on one event.
+    pub allowlist_root: Option<[u8; 32]>,
+    /// Passes one wallet may mint across all tiers; zero means unlimited.
+    pub max_passes_per_wallet: u8,
+    /// Share of the tier price paid to a buyer's referrer, in basis points.
+    pub referral_bps: u16,
+    /// No pass can be minted before this time; set it to the creation time
+    /// (or earlier) to open sales right away.
+    pub sale_start_ts: i64,
+    /// Share of the vault's yield paid back to checked-in attendees after
+    /// settlement, in basis points.
+    pub attendee_share_bps: u16,
+    /// Recipients of swept no-show deposits; empty leaves them to settlement.
+    pub forfeiture_split: Vec<ForfeitureShare>,
+    pub currency: Currency,
+    pub total_passes: u64,
+    /// Passes checked in at least once.
+    pub checked_in_count: u64,
+    /// Monotonic counter used as the seed of the next minted pass; unlike
+    /// `total_passes` it never decreases.
+    pub next_pass_index: u64,
+    pub vault_state: Pubkey,
+    pub settled: bool,
+}
+
+impl Event {
+    const BASE_SPACE: usize = 8 + // discriminator
+        1 + // bump
+        8 + // event_id
+        32 + // organizer
+        32 + // settlement treasury
+        32 + // metadata hash
+        8 + 8 + // timestamps
+        1 + // strategy enum
+        1 + // max verifiers
+        1 + // strict tier order
+        1 + // tiers finalized
+        1 + 32 + // auth program option
+        1 + // loyalty requires check-in
+        8 + // loyalty delay
+        1 + // max re-entries
+        2 + // resale royalty
+        1 + 8 + // resale price cap option
+        1 + // allow cpi
+        1 + // config frozen
+        1 + // disputed
+        1 + // cancelled
+        1 + 8 + // cancelled at option
+        8 + // refund claim window
+        1 + // sales paused
+        1 + 8 + // refund deadline option
+        1 + 32 + // allowlist root option
+        1 + // max passes per wallet
+        2 + // referral bps
+        8 + // sale start
+        2 + // attendee share bps
+        Currency::SPACE + // currency
+        32 + // vault state
+        8 + // total passes
+        8 + // checked-in count
+        8 + // next pass index
+        1; // settled
+
+    pub fn size_with_args(args: &CreateEventArgs) -> usize {
+        Self::BASE_SPACE +
+        4 + args.name.len() + // name
+        4 + args.venue.len() + // venue
+        option_string_space(&args.image_uri) + // image uri
+        option_string_space(&args.thumbnail_uri) + // thumbnail uri
+        4 + usize::from(args.max_verifiers) * 32 + // verifiers, reserved to the cap
+        4 + // banned (empty at creation)
+        4 + // forfeiture split (empty at creation)
+        TierConfig::space_for_inputs(&args.tiers)
+    }
+
+    /// `size_with_args` after the cheap bounds checks. Evaluated while the
+    /// accounts are loaded, so oversized args fail before anything is
+    /// allocated rather than in the handler's `validate`.
+    pub fn checked_size_with_args(args: &CreateEventArgs) -> Result<usize> {
+        args.check_bounds()?;
+        let space = Self::size_with_args(args);
+        require!(space <= MAX_EVENT_SPACE, EventFluxError::EventTooLarge);
+        Ok(space)
+    }
+
+    /// Exact account size for the event's current contents, used when
+    /// reallocating after tiers or the ban list change.
+    pub fn space(&self) -> usize {
+        Self::BASE_SPACE +
+        4 + self.name.len() + // name
+        4 + self.venue.len() + // venue
+        option_string_space(&self.image_uri) + // image uri
+        option_string_space(&self.thumbnail_uri) + // thumbnail uri
+        4 + usize::from(self.max_verifiers) * 32 + // verifiers, reserved to the cap
+        4 + self.banned.len() * 32 + // banned
+        4 + self.forfeiture_split.len() * ForfeitureShare::SPACE + // forfeiture split
+        4 + self.tiers.iter().map(TierConfig::space).sum::<usize>()
+    }
+
+    pub fn is_banned(&self, wallet: &Pubkey) -> bool {
+        self.banned.contains(wallet)
+    }
+
+    /// Whether a cancelled event's refund claim window has run out.
+    pub fn refund_claim_closed(&self, now: i64) -> bool {
+        self.cancelled_at
+            .is_some_and(|at| now >= at.saturating_add(self.refund_claim_window))
+    }
+
+    /// Passes still for sale across every tier. Summed as `u64` so many
+    /// large tiers can't overflow.
+    pub fn total_remaining_supply(&self) -> u64 {
+        self.tiers
+            .iter()
+            .map(|t| u64::from(t.available_supply()))
+            .sum()
+    }
+
+    /// Share of the passes still out that have been checked in, in basis
+    /// points; zero before any pass is sold.
+    pub fn check_in_rate(&self) -> u64 {
+        if self.total_passes == 0 {
+            return 0;
+        }
+        (u128::from(self.checked_in_count) * BPS_DENOMINATOR / u128::from(self.total_passes))
+            .min(BPS_DENOMINATOR) as u64
+    }
+
+    /// Each checked-in pass's cut of `unclaimed_yield` under
+    /// `attendee_share_bps`, rounded down so the pool always covers every
+    /// claim.
+    pub fn dividend_per_pass(&self, unclaimed_yield: u64) -> u64 {
+        if self.checked_in_count == 0 {
+            return 0;
+        }
+        (u128::from(unclaimed_yield) * u128::from(self.attendee_share_bps)
+            / (BPS_DENOMINATOR * u128::from(self.checked_in_count))) as u64
+    }
+}
+
+/// Borsh size of an optional string: the tag byte plus the string if present.
+fn option_string_space(value: &Option<String>) -> usize {
+    1 + value.as_ref().map_or(0, |value| 4 + value.len())
+}
+
+#[account]
+pub struct EventPass {
+    pub bump: u8,
+    pub event: Pubkey,
+    pub owner: Pubkey,
+    pub pass_index: u64,
+    pub tier_id: u8,
+    /// Copy of the tier's `BENEFIT_*` flags at mint time.
+    pub benefits: u32,
+    /// Full tier price the buyer paid, fees included.
+    pub price_paid: u64,
+    /// Part of `price_paid` that went to the protocol fee collector.
+    pub fee_paid: u64,
+    /// Part of `price_paid` that went to the referrer.
+    pub referral_paid: u64,
+    /// Refundable deposit still held for this pass; zero once reclaimed.
+    pub deposit_lamports: u64,
+    pub minted_at: i64,
+    pub checked_in: bool,
+    /// First entry; later re-entries only bump `reentry_count`.
+    pub checked_in_at: Option<i64>,
+    pub reentry_count: u8,
+    pub last_entry_at: Option<i64>,
+    pub loyalty_mint: Option<Pubkey>,
+    pub lucky_winner: bool,
+    /// Expiry for membership passes; `None` means valid for the whole event.
+    pub valid_until: Option<i64>,
+    /// Platform holding the pass for `owner` until they claim it.
+    pub custodian: Option<Pubkey>,
+    /// Set once `claim_yield_dividend` has paid this pass.
+    pub dividend_claimed: bool,
+    /// Set once this pass has raised a dispute; each pass gets one.
+    pub dispute_raised: bool,
+    /// Bumped whenever a check-in is undone so signed scans of the earlier
+    /// check-in can't be relayed again.
+    pub checkin_nonce: u32,
+}
+
+impl EventPass {
+    pub const SPACE: usize = 8 // discriminator
+        + 1 // bump
+        + 32 // event
+        + 32 // owner
+        + 8 // pass index
+        + 1 // tier
+        + 4 // benefits
+        + 8 // price
+        + 8 // fee paid
+        + 8 // referral paid
+        + 8 // deposit
+        + 8 // minted_at
+        + 1 // checked_in
+        + 1 // check-in option flag
+        + 8 // check-in timestamp
+        + 1 // re-entries
+        + 1 // last entry option flag
+        + 8 // last entry timestamp
+        + 1 // loyalty option flag
+        + 32 // loyalty mint pubkey
+        + 1 // lucky winner
+        + 1 // validity option flag
+        + 8 // valid until
+        + 1 // custodian option flag
+        + 32 // custodian
+        + 1 // dividend claimed
+        + 1 // dispute raised
+        + 4; // check-in nonce
+
+    /// Fills a freshly initialized pass with `old`'s purchase details under a
+    /// new owner. Attendance and reward state start over.
+    pub fn reissue_from(&mut self, old: &EventPass, bump: u8, owner: Pubkey) {
+        self.bump = bump;
+        self.event = old.event;
+        self.owner = owner;
+        self.pass_index = old.pass_index;
+        self.tier_id = old.tier_id;
+        self.benefits = old.benefits;
+        self.price_paid = old.price_paid;
+        self.fee_paid = old.fee_paid;
+        self.referral_paid = old.referral_paid;
+        self.deposit_lamports = old.deposit_lamports;
+        self.minted_at = old.minted_at;
+        self.checked_in = false;
+        self.checked_in_at = None;
+        self.reentry_count = 0;
+        self.last_entry_at = None;
+        self.loyalty_mint = None;
+        self.lucky_winner = false;
+        self.valid_until = old.valid_until;
+        self.custodian = None;
+        self.dividend_claimed = false;
+        // A new owner doesn't get a fresh dispute.
+        self.dispute_raised = old.dispute_raised;
+        // Carried forward, not reset: the pass may land back at the same
+        // address, where scans signed for `old` must stay unusable.
+        self.checkin_nonce = old.checkin_nonce.wrapping_add(1);
+    }
+
+    /// Part of `price_paid` that reached the treasury.
+    pub fn treasury_share(&self) -> Result<u64> {
+        self.price_paid
+            .checked_sub(self.fee_paid)
+            .and_then(|share| share.checked_sub(self.referral_paid))
+            .ok_or(error!(EventFluxError::MathOverflow))
+    }
+
+    /// Whether `key` may present this pass: the owner, or its custodian
+    /// while custody lasts.
+    pub fn held_by(&self, key: &Pubkey) -> bool {
+        self.owner == *key || self.custodian.as_ref() == Some(key)
+    }
+
+    pub fn find_address(event: &Pubkey, owner: &Pubkey, pass_index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                PASS_SEED,
+                event.as_ref(),
+                owner.as_ref(),
+                &pass_index.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+}
+
+/// Attendee-approved standing order for a membership pass. The attendee
+/// escrows lamports here up front, and `renew_membership` may draw at most
+/// `max_per_period` from the escrow once every `period_secs`.
+#[account]
+pub struct AutoRenewMandate {
+    pub bump: u8,
+    pub owner: Pubkey,
+    pub event_pass: Pubkey,
+    pub max_per_period: u64,
+    pub period_secs: i64,
+    pub last_charged_at: i64,
+}
+
+impl AutoRenewMandate {
+    pub const SPACE: usize = 8 // discriminator
+        + 1 // bump
+        + 32 // owner
+        + 32 // event pass
+        + 8 // max per period
+        + 8 // period
+        + 8; // last charged
+}
+
+/// Prepaid lamports an attendee holds with one event. Deposits land in the
+/// vault treasury straight away; the balance is what is left to spend on
+/// passes or to withdraw before settlement.
+#[account]
+pub struct CreditAccount {
+    pub bump: u8,
+    pub event: Pubkey,
+    pub owner: Pubkey,
+    pub balance: u64,
+}
+
+impl CreditAccount {
+    pub const SPACE: usize = 8 // discriminator
+        + 1 // bump
+        + 32 // event
+        + 32 // owner
+        + 8; // balance
+}
+
+/// Passes a wallet has minted for one event, checked against
+/// `Event::max_passes_per_wallet`. Refunds and burns don't give slots back.
+#[account]
+pub struct AttendeeRecord {
+    pub bump: u8,
+    pub event: Pubkey,
+    pub owner: Pubkey,
+    pub minted: u32,
+}
+
+impl AttendeeRecord {
+    pub const SPACE: usize = 8 // discriminator
+        + 1 // bump
+        + 32 // event
+        + 32 // owner
+        + 4; // minted
+}
+
+/// Every pass minted for an event, in `pass_index` order, so the full guest
+/// list can be read from the event alone. Entries follow reissues and
+/// resales to the pass's current address.
+///
+/// Clients page through it without loading the whole account by fetching
+/// with a data slice: entry `i` sits at byte `GuestList::ENTRIES_OFFSET + 32 * i`.
+#[account]
+pub struct GuestList {
+    pub bump: u8,
+    pub event: Pubkey,
+    pub passes: Vec<Pubkey>,
+}
+
+impl GuestList {
+    pub const ENTRIES_OFFSET: usize = 8 // discriminator
+        + 1 // bump
+        + 32 // event
+        + 4; // entries length
+
+    pub fn space_for(entries: usize) -> usize {
+        Self::ENTRIES_OFFSET + entries * 32
+    }
+
+    pub fn space(&self) -> usize {
+        Self::space_for(self.passes.len())
+    }
+
+    pub fn replace(&mut self, pass_index: u64, event_pass: Pubkey) -> Result<()> {
+        let entry = usize::try_from(pass_index)
+            .ok()
+            .and_then(|index| self.passes.get_mut(index))
+            .ok_or(EventFluxError::GuestListMismatch)?;
+        *entry = event_pass;
+        Ok(())
+    }
+
+    pub fn find_address(event: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[GUEST_LIST_SEED, event.as_ref()], &crate::ID)
+    }
+}
+
+/// Protocol-wide settings, a single PDA owned by the program.
+#[account]
+pub struct ProtocolConfig {
+    pub bump: u8,
+    /// Resolves disputes raised against events.
+    pub admin: Pubkey,
+    /// Platform cut of every paid mint, in basis points.
+    pub fee_bps: u16,
+    pub fee_collector: Pubkey,
+}
+
+impl ProtocolConfig {
+    pub const SPACE: usize = 8 // discriminator
+        + 1 // bump
+        + 32 // admin
+        + 2 // fee bps
+        + 32; // fee collector
+}
+
+/// Lifetime totals across all of an organizer's events. Passes count at
+/// mint and stop counting when refunded or burned, check-ins count first
+/// entries only, and revenue counts what settlement actually paid out.
+#[account]
+pub struct OrganizerStats {
+    pub bump: u8,
+    pub organizer: Pubkey,
+    pub lifetime_passes: u64,
+    pub lifetime_revenue: u64,
+    pub lifetime_checkins: u64,
+}
+
+impl OrganizerStats {
+    pub const SPACE: usize = 8 // discriminator
+        + 1 // bump
+        + 32 // organizer
+        + 8 // passes
+        + 8 // revenue
+        + 8; // check-ins
+
+    pub fn record_pass(&mut self) -> Result<()> {
+        self.lifetime_passes = self
+            .lifetime_passes
+            .checked_add(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_checkin(&mut self) -> Result<()> {
+        self.lifetime_checkins = self
+            .lifetime_checkins
+            .checked_add(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn unrecord_pass(&mut self) -> Result<()> {
+        self.lifetime_passes = self
+            .lifetime_passes
+            .checked_sub(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+        Ok(())
+    }
 
-    Ok(())
+    pub fn unrecord_checkin(&mut self) -> Result<()> {
+        self.lifetime_checkins = self
+            .lifetime_checkins
+            .checked_sub(1)
+            .ok_or(EventFluxError::MathOverflow)?;
+        Ok(())
+    }
 }
 
-#[derive(Accounts)]
-#[instruction(args: CreateEventArgs)]
-pub struct CreateEvent<'info> {
-    #[account(mut)]
-    pub organizer: Signer<'info>,
-    #[account(
-        init,
-        payer = organizer,
-        space = Event::size_with_args(&args),
-        seeds = [EVENT_SEED, organizer.key().as_ref(), &args.event_id.to_le_bytes()],
-        bump,
-    )]
-    pub event: Account<'info, Event>,
-    #[account(
-        init,
-        payer = organizer,
-        space = VaultState::SPACE,
-        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
-        bump,
-    )]
-    pub vault_state: Account<'info, VaultState>,
-    #[account(
-        mut,
-        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
-        bump,
-    )]
-    /// CHECK: derived PDA that temporarily stores ticket proceeds, only accessed within program
-    pub vault_treasury: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+#[account]
+pub struct VaultState {
+    pub bump: u8,
+    pub event: Pubkey,
+    pub strategy: YieldStrategy,
+    pub currency: Currency,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub total_yield_harvested: u64,
+    pub vault_treasury_bump: u8,
+    pub treasury_initialized: bool,
+    pub last_harvest_ts: i64,
+    /// Lamports that reached the treasury outside of mints and harvests
+    /// (direct transfers, donations). Recorded by `reconcile_treasury` and paid
+    /// out with everything else at settlement.
+    pub surplus_lamports: u64,
+    pub first_deposit_ts: i64,
+    /// Annualised yield on `total_deposited` since the first deposit, in basis
+    /// points, refreshed on every harvest.
+    pub effective_apy_bps: u64,
+    pub harvest_paused: bool,
+    /// Refundable pass deposits sitting in the treasury, kept out of
+    /// `total_deposited` so they never count as revenue.
+    pub deposits_held: u64,
+    /// Yield adapter `harvest_yield` is allowed to call, fixed at creation so
+    /// a different program can't be swapped in at harvest time.
+    pub adapter_program: Pubkey,
+    /// Principal moved into the adapter by `deposit_to_adapter`.
+    pub deployed_principal: u64,
+    /// Harvested yield already paid out by `withdraw_yield`.
+    pub yield_withdrawn: u64,
+    /// What each checked-in pass may claim, fixed at settlement.
+    pub dividend_per_pass: u64,
+    /// Platform fees charged on this event's mints, in the event's currency.
+    pub fees_collected: u64,
+    /// Part of `fees_collected` the admin has sent back with `refund_fee`.
+    pub fees_refunded: u64,
+    /// Paid back to holders by `refund_pass` and `refund_batch`.
+    pub total_refunded: u64,
+    /// Fees `refund_fee` returned for a cancelled event, waiting to be paid
+    /// back to the buyers who were charged them.
+    pub fee_refunds_held: u64,
+    /// The last `MAX_TRANCHES` partial withdrawals, oldest first.
+    pub tranches: Vec<Tranche>,
 }
 
-#[derive(Accounts)]
-#[instruction(tier_id: u8)]
-pub struct MintPass<'info> {
-    #[account(mut)]
-    pub attendee: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
-        bump = event.bump,
-    )]
-    pub event: Account<'info, Event>,
-    #[account(
-        mut,
-        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
-        bump = vault_state.bump,
-        constraint = vault_state.event == event.key(),
-    )]
-    pub vault_state: Account<'info, VaultState>,
-    #[account(
-        mut,
-        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
-        bump = vault_state.vault_treasury_bump,
-    )]
-    /// CHECK: lamports-only PDA controlled by this program
-    pub vault_treasury: UncheckedAccount<'info>,
-    #[account(
-        init,
-        payer = attendee,
-        space = EventPass::SPACE,
-        seeds = [PASS_SEED, event.key().as_ref(), attendee.key().as_ref(), &[tier_id]],
-        bump,
-    )]
-    pub event_pass: Account<'info, EventPass>,
-    pub system_program: Program<'info, System>,
+impl VaultState {
+    pub const SPACE: usize = 8 // discriminator
+        + 1 // bump
+        + 32 // event
+        + 1 // strategy enum
+        + Currency::SPACE // currency
+        + 8 // deposited
+        + 8 // withdrawn
+        + 8 // total yield
+        + 1 // treasury bump
+        + 1 // treasury initialized
+        + 8 // last harvest
+        + 8 // surplus
+        + 8 // first deposit
+        + 8 // effective apy
+        + 1 // harvest paused
+        + 8 // deposits held
+        + 32 // adapter program
+        + 8 // deployed principal
+        + 8 // yield withdrawn
+        + 8 // dividend per pass
+        + 8 // fees collected
+        + 8 // fees refunded
+        + 8 // total refunded
+        + 8 // fee refunds held
+        + 4 + MAX_TRANCHES * Tranche::SPACE; // tranches
+
+    /// Balance the treasury should hold given the ledger, including the rent
+    /// reserve funded at creation. Principal deployed to the adapter is not
+    /// expected back until it is harvested.
+    pub fn expected_treasury_balance(&self, rent_reserve: u64) -> Option<u64> {
+        rent_reserve
+            .checked_add(self.total_deposited)?
+            .checked_add(self.total_yield_harvested)?
+            .checked_add(self.surplus_lamports)?
+            .checked_add(self.deposits_held)?
+            .checked_add(self.fee_refunds_held)?
+            .checked_sub(self.total_withdrawn)?
+            .checked_sub(self.deployed_principal)?
+            .checked_sub(self.yield_withdrawn)
+    }
+
+    /// Principal still sitting in the treasury, which settlement can pay out
+    /// without recalling anything from the adapter.
+    pub fn idle_balance(&self) -> u64 {
+        self.total_deposited
+            .saturating_sub(self.total_withdrawn)
+            .saturating_sub(self.deployed_principal)
+    }
+
+    /// Principal deployed to the adapter and exposed to its losses.
+    pub fn at_risk_balance(&self) -> u64 {
+        self.deployed_principal
+    }
+
+    /// Harvested yield still in the treasury.
+    pub fn unclaimed_yield(&self) -> u64 {
+        self.total_yield_harvested
+            .saturating_sub(self.yield_withdrawn)
+    }
+
+    /// Logs a partial withdrawal, dropping the oldest once the history is
+    /// full.
+    pub fn record_tranche(&mut self, tranche: Tranche) {
+        if self.tranches.len() == MAX_TRANCHES {
+            self.tranches.remove(0);
+        }
+        self.tranches.push(tranche);
+    }
+
+    pub fn record_fee(&mut self, fee: u64) -> Result<()> {
+        self.fees_collected = self
+            .fees_collected
+            .checked_add(fee)
+            .ok_or(EventFluxError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Fees collected for this event that haven't been refunded yet.
+    pub fn refundable_fees(&self) -> u64 {
+        self.fees_collected.saturating_sub(self.fees_refunded)
+    }
+
+    pub fn hold_deposit(&mut self, amount: u64) -> Result<()> {
+        self.deposits_held = self
+            .deposits_held
+            .checked_add(amount)
+            .ok_or(EventFluxError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Annualised `total_yield_harvested / total_deposited` over the time since
+    /// the first deposit. Reports zero until there is both principal and
+    /// elapsed time to measure against.
+    pub fn compute_apy_bps(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.first_deposit_ts);
+        if self.total_deposited == 0 || self.first_deposit_ts == 0 || elapsed <= 0 {
+            return 0;
+        }
+
+        let apy = u128::from(self.total_yield_harvested) * BPS_DENOMINATOR * SECONDS_PER_YEAR
+            / (u128::from(self.total_deposited) * elapsed as u128);
+        u64::try_from(apy).unwrap_or(u64::MAX)
+    }
 }
 
-#[derive(Accounts)]
-pub struct CheckIn<'info> {
-    pub verifier: Signer<'info>,
-    #[account(
-        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
-        bump = event.bump,
-    )]
-    pub event: Account<'info, Event>,
-    #[account(
-        mut,
-        seeds = [PASS_SEED, event.key().as_ref(), event_pass.owner.as_ref(), &[event_pass.tier_id]],
-        bump = event_pass.bump,
-        constraint = event_pass.event == event.key(),
-    )]
-    pub event_pass: Account<'info, EventPass>,
+#[event]
+pub struct EventCreated {
+    pub event: Pubkey,
+    pub organizer: Pubkey,
+    pub metadata_hash: [u8; 32],
 }
 
-#[derive(Accounts)]
-pub struct WithdrawTreasury<'info> {
-    pub organizer: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
-        bump = event.bump,
-        constraint = event.organizer == organizer.key(),
-    )]
-    pub event: Account<'info, Event>,
-    #[account(
-        mut,
-        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
-        bump = vault_state.bump,
-        constraint = vault_state.event == event.key(),
-    )]
-    pub vault_state: Account<'info, VaultState>,
-    #[account(mut, address = event.settlement_treasury)]
-    pub destination: SystemAccount<'info>,
-    #[account(
-        mut,
-        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
-        bump = vault_state.vault_treasury_bump,
-    )]
-    /// CHECK: settlement PDA scoped to this event, drained at withdrawal
-    pub vault_treasury: UncheckedAccount<'info>,
+#[event]
+pub struct EventUpdated {
+    pub event: Pubkey,
+    pub metadata_hash: [u8; 32],
+}
+
+#[event]
+pub struct EventImagesUpdated {
+    pub event: Pubkey,
+    pub image_uri: Option<String>,
+    pub thumbnail_uri: Option<String>,
+}
+
+#[event]
+pub struct EventCancelled {
+    pub event: Pubkey,
+}
+
+#[event]
+pub struct SalesPauseToggled {
+    pub event: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct EventExtended {
+    pub event: Pubkey,
+    pub previous_end_ts: i64,
+    pub new_end_ts: i64,
+}
+
+#[event]
+pub struct YieldStrategySwitched {
+    pub event: Pubkey,
+    pub strategy: YieldStrategy,
+}
+
+#[event]
+pub struct AdapterDeposited {
+    pub event: Pubkey,
+    pub amount: u64,
+    pub deployed_principal: u64,
+}
+
+#[event]
+pub struct AdapterRecalled {
+    pub event: Pubkey,
+    pub amount: u64,
+    pub deployed_principal: u64,
+}
+
+#[event]
+pub struct HarvestPauseToggled {
+    pub event: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct LuckyDropWon {
+    pub event: Pubkey,
+    pub event_pass: Pubkey,
+    pub owner: Pubkey,
+    pub seed: [u8; 32],
+    pub candidate_count: u32,
+}
+
+#[event]
+pub struct TierSoldOut {
+    pub event: Pubkey,
+    pub tier_id: u8,
+    pub sold_out_at: i64,
+}
+
+#[event]
+pub struct EventConfigFrozen {
+    pub event: Pubkey,
+}
+
+#[event]
+pub struct ForfeituresSwept {
+    pub event: Pubkey,
+    pub swept: u64,
+    pub passes: u32,
+}
+
+#[event]
+pub struct DisputeRaised {
+    pub event: Pubkey,
+    pub raised_by: Pubkey,
+}
+
+#[event]
+pub struct BatchRefunded {
+    pub event: Pubkey,
+    pub refunded: u8,
+    pub skipped: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeeRefunded {
+    pub event: Pubkey,
+    pub amount: u64,
+    pub fees_refunded: u64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub event: Pubkey,
+    /// Whether the ruling cancelled the event so buyers can be refunded.
+    pub refunded: bool,
+}
+
+#[event]
+pub struct VerifiersCleared {
+    pub event: Pubkey,
+    pub revoked: u8,
+}
+
+#[event]
+pub struct PassResold {
+    pub event: Pubkey,
+    pub old_pass: Pubkey,
+    pub new_pass: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub royalty: u64,
+}
+
+#[event]
+pub struct PassReissued {
+    pub event: Pubkey,
+    pub old_pass: Pubkey,
+    pub new_pass: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct PassMinted {
+    pub event: Pubkey,
+    pub attendee: Pubkey,
+    pub tier_id: u8,
+    pub price_paid: u64,
+}
+
+#[event]
+pub struct PassCheckedIn {
+    pub event: Pubkey,
+    pub pass: Pubkey,
+    pub verifier: Pubkey,
+    pub ts: i64,
+}
+
+#[event]
+pub struct YieldDividendClaimed {
+    pub event: Pubkey,
+    pub pass: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BatchCheckedIn {
+    pub event: Pubkey,
+    pub verifier: Pubkey,
+    pub checked_in: u8,
+    pub skipped: u8,
+}
+
+#[event]
+pub struct CheckInUnset {
+    pub event: Pubkey,
+    pub pass: Pubkey,
+}
+
+#[event]
+pub struct TreasurySettled {
+    pub event: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+}
+
+#[event]
+pub struct PassTransferred {
+    pub event: Pubkey,
+    pub old_pass: Pubkey,
+    pub new_pass: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+/// Where settlement lamports go and what they are made of. `other` covers the
+/// treasury rent reserve plus any surplus that arrived outside mints and
+/// harvests.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SettlementBreakdown {
+    pub destination: Pubkey,
+    pub currency: Currency,
+    pub principal: u64,
+    /// Refundable deposits nobody reclaimed before settlement.
+    pub forfeited_deposits: u64,
+    pub yield_harvested: u64,
+    pub other: u64,
+    /// Yield left in the treasury for checked-in attendees to claim.
+    pub attendee_dividends: u64,
+    pub total_payout: u64,
+}
+
+/// Machine-readable reason a scan would be refused. Scanner apps map these to
+/// their own localized messages.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckInDenial {
+    EventNotStarted,
+    EventEnded,
+    UnauthorizedVerifier,
+    OutsideTierWindow,
+    PassExpired,
+    AlreadyCheckedIn,
+    ReentryLimitReached,
+    EventCancelled,
 }
 
-#[derive(Accounts)]
-pub struct HarvestYield<'info> {
-    pub organizer: Signer<'info>,
-    #[account(
-        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
-        bump = event.bump,
-        constraint = event.organizer == organizer.key(),
-    )]
-    pub event: Account<'info, Event>,
-    #[account(
-        mut,
-        seeds = [VAULT_STATE_SEED, event.key().as_ref()],
-        bump = vault_state.bump,
-        constraint = vault_state.event == event.key(),
-    )]
-    pub vault_state: Account<'info, VaultState>,
-    #[account(
-        mut,
-        seeds = [VAULT_TREASURY_SEED, event.key().as_ref()],
-        bump = vault_state.vault_treasury_bump,
-    )]
-    /// CHECK: PDA receiving harvested yield before settlement
-    pub vault_treasury: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        seeds = [ADAPTER_RESERVE_SEED],
-        seeds::program = vault_stub::ID,
-        bump = adapter_reserve.bump,
-    )]
-    pub adapter_reserve: Account<'info, vault_stub::AdapterReserve>,
-    #[account(address = vault_stub::ID)]
-    pub vault_adapter_program: Program<'info, vault_stub::program::VaultStub>,
+impl CheckInDenial {
+    pub fn to_error(self) -> EventFluxError {
+        match self {
+            CheckInDenial::EventNotStarted => EventFluxError::EventNotStarted,
+            CheckInDenial::EventEnded => EventFluxError::EventEnded,
+            CheckInDenial::UnauthorizedVerifier => EventFluxError::UnauthorizedVerifier,
+            CheckInDenial::OutsideTierWindow => EventFluxError::OutsideTierWindow,
+            CheckInDenial::PassExpired => EventFluxError::PassExpired,
+            CheckInDenial::AlreadyCheckedIn => EventFluxError::AlreadyCheckedIn,
+            CheckInDenial::ReentryLimitReached => EventFluxError::ReentryLimitReached,
+            CheckInDenial::EventCancelled => EventFluxError::EventCancelled,
+        }
+    }
 }
 
-#[derive(Accounts)]
-pub struct IssueLoyaltyNft<'info> {
-    #[account(mut)]
-    pub organizer: Signer<'info>,
-    #[account(
-        seeds = [EVENT_SEED, event.organizer.as_ref(), &event.event_id.to_le_bytes()],
-        bump = event.bump,
-        constraint = event.organizer == organizer.key(),
-    )]
-    pub event: Account<'info, Event>,
-    #[account(
-        mut,
-        seeds = [PASS_SEED, event.key().as_ref(), event_pass.owner.as_ref(), &[event_pass.tier_id]],
-        bump = event_pass.bump,
-        constraint = event_pass.event == event.key(),
-    )]
-    pub event_pass: Account<'info, EventPass>,
-    /// CHECK: ensures minted token authority matches pass owner
-    #[account(address = event_pass.owner)]
-    pub pass_owner: UncheckedAccount<'info>,
-    #[account(
-        init_if_needed,
-        payer = organizer,
-        seeds = [LOYALTY_MINT_SEED, event_pass.key().as_ref()],
-        bump,
-        mint::decimals = 0,
-        mint::authority = organizer,
-    )]
-    pub loyalty_mint: Account<'info, Mint>,
-    #[account(
-        init_if_needed,
-        payer = organizer,
-        associated_token::mint = loyalty_mint,
-        associated_token::authority = pass_owner,
-    )]
-    pub loyalty_token_account: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CheckInEligibility {
+    pub eligible: bool,
+    pub denial: Option<CheckInDenial>,
 }
 
-#[account]
-pub struct Event {
-    pub bump: u8,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventStatus {
+    Upcoming,
+    Live,
+    Ended,
+    Settled,
+    Cancelled,
+}
+
+/// Headline fields for listing pages. Leaves out tiers, verifiers and the ban
+/// list so the payload stays small as `Event` grows. `net_treasury_balance`
+/// is the treasury's lamports above its rent reserve.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EventSummary {
     pub event_id: u64,
     pub organizer: Pubkey,
-    pub settlement_treasury: Pubkey,
     pub name: String,
     pub venue: String,
+    pub image_uri: Option<String>,
+    pub thumbnail_uri: Option<String>,
     pub start_ts: i64,
     pub end_ts: i64,
-    pub yield_strategy: YieldStrategy,
-    pub tiers: Vec<TierConfig>,
-    pub authorized_verifiers: Vec<Pubkey>,
     pub total_passes: u64,
-    pub vault_state: Pubkey,
-    pub settled: bool,
-}
-
-impl Event {
-    pub fn size_with_args(args: &CreateEventArgs) -> usize {
-        8 + // discriminator
-        1 + // bump
-        8 + // event_id
-        32 + // organizer
-        32 + // settlement treasury
-        8 + 8 + // timestamps
-        1 + // strategy enum
-        32 + // vault state
-        8 + // total passes
-        1 + // settled
-        4 + args.name.len() +
-        4 + args.venue.len() +
-        4 + args.authorized_verifiers.len() * 32 +
-        TierConfig::space_for_inputs(&args.tiers)
-    }
+    pub tier_count: u8,
+    pub status: EventStatus,
+    pub checked_in_count: u64,
+    pub remaining_supply: u64,
+    pub total_deposited: u64,
+    pub net_treasury_balance: u64,
 }
 
-#[account]
-pub struct EventPass {
-    pub bump: u8,
-    pub event: Pubkey,
-    pub owner: Pubkey,
-    pub tier_id: u8,
-    pub price_paid: u64,
-    pub minted_at: i64,
-    pub checked_in: bool,
-    pub checked_in_at: Option<i64>,
-    pub loyalty_mint: Option<Pubkey>,
+/// Output of `estimate_rent`. `organizer_stats` is only charged on an
+/// organizer's first event; every mint costs `event_pass` plus
+/// `guest_list_entry` for the guest list growth.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RentEstimate {
+    pub event: u64,
+    pub vault_state: u64,
+    pub organizer_stats: u64,
+    pub guest_list: u64,
+    pub vault_treasury: u64,
+    pub event_pass: u64,
+    pub guest_list_entry: u64,
 }
 
-impl EventPass {
-    pub const SPACE: usize = 8 // discriminator
-        + 1 // bump
-        + 32 // event
-        + 32 // owner
-        + 1 // tier
-        + 8 // price
-        + 8 // minted_at
-        + 1 // checked_in
-        + 1 // check-in option flag
-        + 8 // check-in timestamp
-        + 1 // loyalty option flag
-        + 32; // loyalty mint pubkey
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OrganizerDashboard {
+    pub organizer: Pubkey,
+    pub lifetime_passes: u64,
+    pub lifetime_revenue: u64,
+    pub lifetime_checkins: u64,
 }
 
-#[account]
-pub struct VaultState {
-    pub bump: u8,
-    pub event: Pubkey,
-    pub strategy: YieldStrategy,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct YieldMetrics {
     pub total_deposited: u64,
-    pub total_withdrawn: u64,
     pub total_yield_harvested: u64,
-    pub vault_treasury_bump: u8,
     pub last_harvest_ts: i64,
-}
-
-impl VaultState {
-    pub const SPACE: usize = 8 // discriminator
-        + 1 // bump
-        + 32 // event
-        + 1 // strategy enum
-        + 8 // deposited
-        + 8 // withdrawn
-        + 8 // total yield
-        + 1 // treasury bump
-        + 8; // last harvest
+    /// When the vault first received funds; zero until the first deposit.
+    pub first_deposit_ts: i64,
+    pub effective_apy_bps: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
@@ -578,6 +6650,46 @@ pub enum YieldStrategy {
     Sanctum,
 }
 
+/// Asset the event's treasury is denominated in. Every instruction that moves
+/// funds checks it so SOL and token accounting never mix.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Currency {
+    Sol,
+    Spl(Pubkey),
+}
+
+impl Currency {
+    pub const SPACE: usize = 1 + 32;
+
+    /// Guard for the lamport transfer paths.
+    pub fn require_sol(&self) -> Result<()> {
+        require!(*self == Currency::Sol, EventFluxError::CurrencyMismatch);
+        Ok(())
+    }
+}
+
+/// One `withdraw_treasury_partial` payout, kept for bookkeeping.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Tranche {
+    pub amount: u64,
+    pub ts: i64,
+    pub destination: Pubkey,
+}
+
+impl Tranche {
+    pub const SPACE: usize = 8 + 8 + 32;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ForfeitureShare {
+    pub recipient: Pubkey,
+    pub bps: u16,
+}
+
+impl ForfeitureShare {
+    pub const SPACE: usize = 32 + 2;
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
 pub struct TierConfig {
     pub tier_id: u8,
@@ -585,6 +6697,27 @@ pub struct TierConfig {
     pub price_lamports: u64,
     pub max_supply: u32,
     pub sold: u32,
+    pub checkin_start: Option<i64>,
+    pub checkin_end: Option<i64>,
+    /// Membership tiers expire this many seconds after purchase or renewal.
+    pub renewal_period: Option<i64>,
+    /// When the last unit sold; cleared if the supply is raised again.
+    pub sold_out_at: Option<i64>,
+    /// Refundable deposit charged on top of the price, returned after
+    /// check-in via `reclaim_deposit`.
+    pub deposit_lamports: u64,
+    /// `BENEFIT_*` perks granted to holders of this tier.
+    pub benefits: u32,
+    /// Most a resale may exceed `price_lamports` by, in basis points;
+    /// `None` leaves resales of this tier uncapped.
+    pub max_resale_bps: Option<u16>,
+    /// Charged instead of `price_lamports` while `now < early_until_ts`;
+    /// a zero cutoff means the tier has no early-bird window.
+    pub early_price_lamports: u64,
+    pub early_until_ts: i64,
+    /// When false, holders can't take the voluntary refund before
+    /// `Event::refund_deadline`. Cancellation refunds every tier regardless.
+    pub refundable: bool,
 }
 
 impl TierConfig {
@@ -599,15 +6732,71 @@ impl TierConfig {
             price_lamports: input.price_lamports,
             max_supply: input.max_supply,
             sold: 0,
+            checkin_start: input.checkin_start,
+            checkin_end: input.checkin_end,
+            renewal_period: input.renewal_period,
+            sold_out_at: None,
+            deposit_lamports: input.deposit_lamports.unwrap_or(0),
+            benefits: input.benefits.unwrap_or(0),
+            max_resale_bps: input.max_resale_bps,
+            early_price_lamports: input.early_price_lamports.unwrap_or(0),
+            early_until_ts: input.early_until_ts.unwrap_or(0),
+            refundable: input.refundable.unwrap_or(true),
         })
     }
 
+    pub fn space_for_label(label_len: usize) -> usize {
+        1 + 4 + label_len + 8 + 4 + 4 + 9 + 9 + 9 + 9 + 8 + 4 + 3 + 8 + 8 + 1
+    }
+
     pub fn space_for_inputs(inputs: &[TierInput]) -> usize {
         4 + inputs
             .iter()
-            .map(|input| 1 + 4 + input.label.len() + 8 + 4 + 4)
+            .map(|input| Self::space_for_label(input.label.len()))
             .sum::<usize>()
     }
+
+    pub fn space(&self) -> usize {
+        Self::space_for_label(self.label.len())
+    }
+
+    /// Lamports a mint in this tier costs at `now`. Every purchase path and
+    /// `simulate_mint_price` go through here so quotes match real charges.
+    pub fn current_price(&self, now: i64) -> u64 {
+        if now < self.early_until_ts {
+            self.early_price_lamports
+        } else {
+            self.price_lamports
+        }
+    }
+
+    /// Passes left in this tier; zero rather than underflowing if `sold`
+    /// ever exceeds `max_supply`.
+    pub fn available_supply(&self) -> u32 {
+        self.max_supply.saturating_sub(self.sold)
+    }
+
+    pub fn is_sold_out(&self) -> bool {
+        self.available_supply() == 0
+    }
+
+    /// Highest resale price the tier's anti-scalping cap allows, if any.
+    pub fn max_resale_price(&self) -> Option<u64> {
+        self.max_resale_bps.map(|bps| {
+            let cap = u128::from(self.price_lamports) * (BPS_DENOMINATOR + u128::from(bps))
+                / BPS_DENOMINATOR;
+            u64::try_from(cap).unwrap_or(u64::MAX)
+        })
+    }
+
+    /// Check-in window for this tier, falling back to the event window for
+    /// any bound the tier doesn't override.
+    pub fn checkin_window(&self, event_start: i64, event_end: i64) -> (i64, i64) {
+        (
+            self.checkin_start.unwrap_or(event_start),
+            self.checkin_end.unwrap_or(event_end),
+        )
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
@@ -616,6 +6805,45 @@ pub struct TierInput {
     pub label: String,
     pub price_lamports: u64,
     pub max_supply: u32,
+    pub checkin_start: Option<i64>,
+    pub checkin_end: Option<i64>,
+    pub renewal_period: Option<i64>,
+    pub deposit_lamports: Option<u64>,
+    pub benefits: Option<u32>,
+    pub max_resale_bps: Option<u16>,
+    /// Early-bird price and its cutoff; set both or neither.
+    pub early_price_lamports: Option<u64>,
+    pub early_until_ts: Option<i64>,
+    /// Defaults to true.
+    pub refundable: Option<bool>,
+}
+
+impl TierInput {
+    /// Checks that apply to a single tier regardless of how it is added.
+    pub fn validate(&self, event_start: i64, event_end: i64) -> Result<()> {
+        let start = self.checkin_start.unwrap_or(event_start);
+        let end = self.checkin_end.unwrap_or(event_end);
+        require!(
+            start >= event_start && end <= event_end && start < end,
+            EventFluxError::InvalidTierWindow
+        );
+        require!(
+            self.renewal_period.is_none_or(|period| period > 0),
+            EventFluxError::InvalidTierSet
+        );
+        require!(
+            self.early_price_lamports.is_some() == self.early_until_ts.is_some()
+                && self
+                    .early_price_lamports
+                    .is_none_or(|price| price <= self.price_lamports),
+            EventFluxError::InvalidTierSet
+        );
+        require!(
+            self.early_until_ts.is_none_or(|until| until <= event_end),
+            EventFluxError::InvalidTierWindow
+        );
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
@@ -629,12 +6857,46 @@ pub struct CreateEventArgs {
     pub yield_strategy: YieldStrategy,
     pub authorized_verifiers: Vec<Pubkey>,
     pub tiers: Vec<TierInput>,
+    pub strict_tier_order: bool,
+    pub auth_program: Option<Pubkey>,
+    pub loyalty_requires_checkin: bool,
+    pub currency: Currency,
+    /// Pass `false` to append more tiers with `add_tier` before sales open.
+    pub tiers_finalized: bool,
+    pub metadata_hash: [u8; 32],
+    pub loyalty_delay_secs: i64,
+    pub max_reentries: u8,
+    /// Verifier slots to reserve, up to 32; more slots cost more rent.
+    pub max_verifiers: u8,
+    pub resale_royalty_bps: u16,
+    pub max_resale_lamports: Option<u64>,
+    pub allow_cpi: bool,
+    pub image_uri: Option<String>,
+    pub thumbnail_uri: Option<String>,
+    /// Makes the event invite-only; see `Event::allowlist_root`.
+    pub allowlist_root: Option<[u8; 32]>,
+    pub max_passes_per_wallet: u8,
+    pub referral_bps: u16,
+    pub sale_start_ts: i64,
+    pub attendee_share_bps: u16,
 }
 
 impl CreateEventArgs {
-    pub fn validate(&self) -> Result<()> {
-        require!(!self.name.is_empty(), EventFluxError::InvalidMetadata);
-        require!(!self.venue.is_empty(), EventFluxError::InvalidMetadata);
+    /// Count and length limits. These only look at lengths, so they run
+    /// before anything iterates or maps the tiers.
+    pub fn check_bounds(&self) -> Result<()> {
+        require!(
+            self.tiers.len() <= MAX_TIER_COUNT,
+            EventFluxError::TooManyTiers
+        );
+        require!(
+            usize::from(self.max_verifiers) <= MAX_VERIFIER_COUNT,
+            EventFluxError::TooManyVerifiers
+        );
+        require!(
+            self.authorized_verifiers.len() <= usize::from(self.max_verifiers),
+            EventFluxError::TooManyVerifiers
+        );
         require!(
             self.name.len() <= MAX_NAME_LEN,
             EventFluxError::MetadataTooLong
@@ -643,20 +6905,98 @@ impl CreateEventArgs {
             self.venue.len() <= MAX_VENUE_LEN,
             EventFluxError::MetadataTooLong
         );
+        validate_image_uris(&self.image_uri, &self.thumbnail_uri)?;
+        require!(
+            self.tiers
+                .iter()
+                .all(|tier| tier.label.len() <= MAX_TIER_LABEL_LEN),
+            EventFluxError::TierLabelTooLong
+        );
+        Ok(())
+    }
+
+    /// `now` is the cluster time at creation; events that have already
+    /// ended can never sell or admit a pass.
+    pub fn validate(&self, now: i64) -> Result<()> {
+        self.check_bounds()?;
+        require!(!self.name.is_empty(), EventFluxError::InvalidMetadata);
+        require!(!self.venue.is_empty(), EventFluxError::InvalidMetadata);
         require!(self.start_ts < self.end_ts, EventFluxError::InvalidSchedule);
-        require!(!self.tiers.is_empty(), EventFluxError::InvalidTierSet);
+        require!(self.end_ts > now, EventFluxError::EventInPast);
         require!(
-            self.tiers.len() <= MAX_TIER_COUNT,
-            EventFluxError::TooManyTiers
+            self.settlement_treasury != Pubkey::default(),
+            EventFluxError::InvalidSettlementTreasury
         );
         require!(
-            self.authorized_verifiers.len() <= MAX_VERIFIER_COUNT,
-            EventFluxError::TooManyVerifiers
+            self.end_ts.saturating_sub(self.start_ts) <= MAX_EVENT_DURATION,
+            EventFluxError::EventTooLong
+        );
+        require!(
+            self.sale_start_ts <= self.end_ts,
+            EventFluxError::InvalidSchedule
+        );
+        require!(
+            self.loyalty_delay_secs >= 0,
+            EventFluxError::InvalidSchedule
+        );
+        require!(
+            u128::from(self.resale_royalty_bps) <= BPS_DENOMINATOR,
+            EventFluxError::InvalidResaleTerms
+        );
+        require!(
+            u128::from(self.referral_bps) <= BPS_DENOMINATOR,
+            EventFluxError::InvalidReferral
+        );
+        require!(
+            u128::from(self.attendee_share_bps) <= BPS_DENOMINATOR,
+            EventFluxError::InvalidAttendeeShare
+        );
+        require!(
+            !self.tiers.is_empty() || !self.tiers_finalized,
+            EventFluxError::InvalidTierSet
         );
+        for tier in &self.tiers {
+            tier.validate(self.start_ts, self.end_ts)?;
+        }
+        if self.strict_tier_order {
+            validate_tier_order(self.tiers.iter().map(|t| (t.tier_id, t.price_lamports)))?;
+        }
         Ok(())
     }
 }
 
+fn validate_image_uris(image_uri: &Option<String>, thumbnail_uri: &Option<String>) -> Result<()> {
+    require!(
+        image_uri
+            .as_ref()
+            .is_none_or(|uri| uri.len() <= MAX_IMAGE_URI_LEN),
+        EventFluxError::MetadataTooLong
+    );
+    require!(
+        thumbnail_uri
+            .as_ref()
+            .is_none_or(|uri| uri.len() <= MAX_THUMBNAIL_URI_LEN),
+        EventFluxError::MetadataTooLong
+    );
+    Ok(())
+}
+
+/// Strict mode requires tier ids to run 0, 1, 2, ... and prices to never
+/// decrease, so clients can index tiers by id and render them as listed.
+/// Takes `(tier_id, price_lamports)` pairs so both `TierInput`s at creation
+/// and stored `TierConfig`s can be checked.
+fn validate_tier_order(tiers: impl IntoIterator<Item = (u8, u64)>) -> Result<()> {
+    let mut previous_price = 0;
+    for (index, (tier_id, price_lamports)) in tiers.into_iter().enumerate() {
+        require!(
+            usize::from(tier_id) == index && previous_price <= price_lamports,
+            EventFluxError::TiersNotOrdered
+        );
+        previous_price = price_lamports;
+    }
+    Ok(())
+}
+
 #[error_code]
 pub enum EventFluxError {
     #[msg("Bump not found")]
@@ -705,4 +7045,288 @@ pub enum EventFluxError {
     PassNotCheckedIn,
     #[msg("Loyalty NFT already issued for this pass")]
     LoyaltyAlreadyIssued,
+    #[msg("Tiers must use contiguous ids from 0 with non-decreasing prices")]
+    TiersNotOrdered,
+    #[msg("Vault treasury holds less than the ledger expects")]
+    TreasuryDeficit,
+    #[msg("Event requires its authorization program as the first remaining account")]
+    AuthProgramMissing,
+    #[msg("Authorization program does not match the event")]
+    AuthProgramMismatch,
+    #[msg("Authorization program returned no decision")]
+    AuthProgramNoResponse,
+    #[msg("Tier check-in window must fall within the event window")]
+    InvalidTierWindow,
+    #[msg("Pass scanned outside its tier's check-in window")]
+    OutsideTierWindow,
+    #[msg("Tier still has outstanding passes")]
+    TierNotEmpty,
+    #[msg("Vault treasury has not been initialized")]
+    TreasuryNotInitialized,
+    #[msg("Vault treasury already initialized")]
+    TreasuryAlreadyInitialized,
+    #[msg("Membership pass has expired")]
+    PassExpired,
+    #[msg("Renewal mandate needs a positive limit and period")]
+    InvalidMandate,
+    #[msg("Tier does not offer renewable memberships")]
+    TierNotRenewable,
+    #[msg("Renewal period has not elapsed yet")]
+    MandatePeriodNotElapsed,
+    #[msg("Renewal price exceeds the mandate limit")]
+    MandateLimitExceeded,
+    #[msg("Mandate escrow cannot cover the renewal")]
+    InsufficientMandateFunds,
+    #[msg("Pass belongs to a different event")]
+    PassEventMismatch,
+    #[msg("Credit amount must be positive")]
+    InvalidCreditAmount,
+    #[msg("Credit balance too low")]
+    InsufficientCredit,
+    #[msg("Buyer is banned from this event")]
+    BuyerBanned,
+    #[msg("Ban list is full")]
+    TooManyBannedBuyers,
+    #[msg("Buyer is not on the ban list")]
+    BuyerNotBanned,
+    #[msg("Payment path does not match the event currency")]
+    CurrencyMismatch,
+    #[msg("Tiers are already finalized")]
+    TiersFinalized,
+    #[msg("Tiers must be finalized before passes can be sold")]
+    TiersNotFinalized,
+    #[msg("Mint price exceeds the buyer's maximum")]
+    PriceSlippageExceeded,
+    #[msg("Loyalty cannot be issued this soon after check-in")]
+    LoyaltyTooSoon,
+    #[msg("Yield harvesting is paused for this vault")]
+    HarvestPaused,
+    #[msg("Pass has used all of its re-entries")]
+    ReentryLimitReached,
+    #[msg("No eligible passes for the lucky drop")]
+    NoLuckyDropCandidates,
+    #[msg("Pass did not win a lucky drop")]
+    NotLuckyWinner,
+    #[msg("Verifier is already authorized")]
+    VerifierAlreadyAuthorized,
+    #[msg("Event has not been settled yet")]
+    EventNotSettled,
+    #[msg("Treasury balance is above the dust threshold")]
+    DustAboveThreshold,
+    #[msg("Pass has no deposit left to reclaim")]
+    NoDepositToReclaim,
+    #[msg("Resale royalty must be at most 10000 bps")]
+    InvalidResaleTerms,
+    #[msg("Resale price exceeds the event's cap")]
+    ResalePriceTooHigh,
+    #[msg("Guest list has no entry for this pass")]
+    GuestListMismatch,
+    #[msg("Instruction cannot be invoked through CPI for this event")]
+    CpiNotAllowed,
+    #[msg("Adapter program or reserve does not match the vault")]
+    AdapterMismatch,
+    #[msg("Pass is not held by a custodian")]
+    NotInCustody,
+    #[msg("Event configuration is frozen")]
+    ConfigFrozen,
+    #[msg("Event configuration exceeds the maximum account size")]
+    EventTooLarge,
+    #[msg("Event is disputed; settlement is on hold")]
+    EventDisputed,
+    #[msg("Event is not disputed")]
+    NotDisputed,
+    #[msg("Signer is not the protocol admin")]
+    NotProtocolAdmin,
+    #[msg("Check-in time is in the future")]
+    CheckinInFuture,
+    #[msg("Forfeiture split is missing, malformed or does not match the recipients")]
+    InvalidForfeitureSplit,
+    #[msg("Event has not been cancelled")]
+    EventNotCancelled,
+    #[msg("Event has been cancelled")]
+    EventCancelled,
+    #[msg("Withdrawal exceeds the treasury balance available to withdraw")]
+    WithdrawalExceedsAvailable,
+    #[msg("Verifier is not authorized for this event")]
+    VerifierNotFound,
+    #[msg("Max supply cannot drop below the number already sold")]
+    SupplyBelowSold,
+    #[msg("Pass sales are paused")]
+    SalesPaused,
+    #[msg("Attendee is not on the event allowlist")]
+    NotOnAllowlist,
+    #[msg("Wallet has reached the event's pass limit")]
+    WalletLimitReached,
+    #[msg("Protocol fee must be at most 10_000 bps and have a collector")]
+    InvalidProtocolFee,
+    #[msg("Fee collector is missing or does not match the protocol config")]
+    InvalidFeeCollector,
+    #[msg("Buyers cannot refer themselves")]
+    SelfReferral,
+    #[msg("Referral rate exceeds 10_000 bps or the referrer account does not match")]
+    InvalidReferral,
+    #[msg("Yield has already been harvested under the current strategy")]
+    YieldAlreadyHarvested,
+    #[msg("Event already uses this yield strategy")]
+    StrategyUnchanged,
+    #[msg("Event and vault yield strategies disagree")]
+    StrategyMismatch,
+    #[msg("Harvest amount exceeds the principal held in the vault")]
+    HarvestExceedsPrincipal,
+    #[msg("Missing or malformed ed25519 check-in signature")]
+    InvalidCheckInSignature,
+    #[msg("Batch must hold between 1 and 20 passes")]
+    InvalidBatchSize,
+    #[msg("Pass sales have not opened yet")]
+    SaleNotOpen,
+    #[msg("Adapter deposit must be positive and within the idle treasury balance")]
+    InvalidAdapterDeposit,
+    #[msg("Amount exceeds the harvested yield not yet withdrawn")]
+    InsufficientYield,
+    #[msg("Attendee yield share exceeds 10_000 bps")]
+    InvalidAttendeeShare,
+    #[msg("Yield dividend already claimed for this pass")]
+    DividendAlreadyClaimed,
+    #[msg("Event has already ended")]
+    EventInPast,
+    #[msg("Event may last at most a year")]
+    EventTooLong,
+    #[msg("Settlement treasury can't be the zero key or the event's own treasury")]
+    InvalidSettlementTreasury,
+    #[msg("Harvest would pay out principal deployed to the adapter")]
+    HarvestExceedsYield,
+    #[msg("Recall must be positive and within the principal deployed to the adapter")]
+    InvalidAdapterRecall,
+    #[msg("Recall all principal from the adapter before settling")]
+    PrincipalStillDeployed,
+    #[msg("This pass has already raised a dispute")]
+    DisputeAlreadyRaised,
+    #[msg("Refund exceeds the platform fees collected for this event")]
+    FeeRefundExceedsCollected,
+    #[msg("Account does not own this pass")]
+    PassOwnerMismatch,
+    #[msg("Passes in this tier can only be refunded if the event is cancelled")]
+    TierNonRefundable,
+    #[msg("Holders can still claim their refunds")]
+    RefundWindowOpen,
+    #[msg("The refund claim window has closed")]
+    RefundWindowClosed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(tier_id: u8, max_supply: u32, sold: u32) -> TierConfig {
+        let mut tier = TierConfig::from_input(TierInput {
+            tier_id,
+            label: "GA".to_string(),
+            price_lamports: 1,
+            max_supply,
+            checkin_start: None,
+            checkin_end: None,
+            renewal_period: None,
+            deposit_lamports: None,
+            benefits: None,
+            max_resale_bps: None,
+            early_price_lamports: None,
+            early_until_ts: None,
+            refundable: None,
+        })
+        .unwrap();
+        tier.sold = sold;
+        tier
+    }
+
+    fn event_with(tiers: Vec<TierConfig>) -> Event {
+        // An all-zero buffer decodes to empty strings and vectors, so only the
+        // tiers need filling in.
+        let zeroed = vec![0u8; Event::BASE_SPACE + 64];
+        let mut event = Event::deserialize(&mut &zeroed[..]).unwrap();
+        event.tiers = tiers;
+        event
+    }
+
+    #[test]
+    fn seed_helpers_match_the_raw_derivation() {
+        let organizer = Pubkey::new_unique();
+        let event_id = 42u64;
+        let (event, bump) = seeds::event_pda(&organizer, event_id);
+        assert_eq!(
+            (event, bump),
+            Pubkey::find_program_address(
+                &[b"event", organizer.as_ref(), &event_id.to_le_bytes()],
+                &ID,
+            )
+        );
+        assert_eq!(
+            seeds::vault_treasury_pda(&event).0,
+            Pubkey::find_program_address(&[b"vault-treasury", event.as_ref()], &ID).0
+        );
+        assert_ne!(
+            seeds::vault_state_pda(&event).0,
+            seeds::vault_treasury_pda(&event).0
+        );
+    }
+
+    #[test]
+    fn tier_sells_out_on_its_last_pass() {
+        let almost = tier(1, 5, 4);
+        assert_eq!(almost.available_supply(), 1);
+        assert!(!almost.is_sold_out());
+
+        let full = tier(1, 5, 5);
+        assert_eq!(full.available_supply(), 0);
+        assert!(full.is_sold_out());
+
+        assert!(tier(1, 0, 0).is_sold_out());
+    }
+
+    #[test]
+    fn oversold_tier_saturates_at_zero() {
+        let oversold = tier(1, 3, 7);
+        assert_eq!(oversold.available_supply(), 0);
+        assert!(oversold.is_sold_out());
+    }
+
+    #[test]
+    fn total_remaining_supply_sums_tiers_without_overflow() {
+        let event = event_with(vec![
+            tier(1, u32::MAX, 0),
+            tier(2, u32::MAX, 1),
+            tier(3, 10, 10),
+        ]);
+        assert_eq!(event.total_remaining_supply(), 2 * u64::from(u32::MAX) - 1);
+        assert_eq!(event_with(vec![]).total_remaining_supply(), 0);
+    }
+
+    #[test]
+    fn tranche_history_drops_the_oldest_when_full() {
+        let zeroed = vec![0u8; VaultState::SPACE];
+        let mut vault_state = VaultState::deserialize(&mut &zeroed[..]).unwrap();
+        let destination = Pubkey::new_unique();
+        for amount in 1..=(MAX_TRANCHES as u64 + 2) {
+            vault_state.record_tranche(Tranche {
+                amount,
+                ts: amount as i64,
+                destination,
+            });
+        }
+        assert_eq!(vault_state.tranches.len(), MAX_TRANCHES);
+        assert_eq!(vault_state.tranches[0].amount, 3);
+        assert_eq!(
+            vault_state.tranches.last().unwrap().amount,
+            MAX_TRANCHES as u64 + 2
+        );
+    }
+
+    #[test]
+    fn refund_claims_close_only_after_the_window() {
+        let mut event = event_with(vec![]);
+        event.refund_claim_window = REFUND_CLAIM_WINDOW;
+        assert!(!event.refund_claim_closed(i64::MAX));
+        event.cancelled_at = Some(1_000);
+        assert!(!event.refund_claim_closed(1_000 + REFUND_CLAIM_WINDOW - 1));
+        assert!(event.refund_claim_closed(1_000 + REFUND_CLAIM_WINDOW));
+    }
 }
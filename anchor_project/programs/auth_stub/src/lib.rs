@@ -0,0 +1,102 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke,
+    },
+};
+
+declare_id!("HZwjeKS86i7McAyXN5Kky86LCsMy4bVL2JpTpSw3GFBM");
+
+pub const APPROVAL_SEED: &[u8] = b"approval";
+
+#[program]
+pub mod auth_stub {
+    use super::*;
+
+    pub fn approve_verifier(ctx: Context<ApproveVerifier>, verifier: Pubkey) -> Result<()> {
+        let approval = &mut ctx.accounts.approval;
+        approval.bump = ctx.bumps.approval;
+        approval.verifier = verifier;
+        Ok(())
+    }
+
+    pub fn is_authorized(ctx: Context<IsAuthorized>) -> Result<bool> {
+        let approval = &ctx.accounts.approval;
+        Ok(approval.owner == &crate::ID && !approval.data_is_empty())
+    }
+
+    /// Forwards `data` to `target_program` with the remaining accounts, so
+    /// tests can exercise how EventFlux behaves when reached through CPI.
+    pub fn relay<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Relay<'info>>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|info| AccountMeta {
+                pubkey: info.key(),
+                is_signer: info.is_signer,
+                is_writable: info.is_writable,
+            })
+            .collect();
+        let mut infos = ctx.remaining_accounts.to_vec();
+        infos.push(ctx.accounts.target_program.to_account_info());
+        invoke(
+            &Instruction {
+                program_id: ctx.accounts.target_program.key(),
+                accounts,
+                data,
+            },
+            &infos,
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(verifier: Pubkey)]
+pub struct ApproveVerifier<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Approval::SPACE,
+        seeds = [APPROVAL_SEED, verifier.as_ref()],
+        bump,
+    )]
+    pub approval: Account<'info, Approval>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IsAuthorized<'info> {
+    pub verifier: Signer<'info>,
+    /// CHECK: pass being scanned, supplied by the calling program
+    pub event_pass: UncheckedAccount<'info>,
+    #[account(
+        seeds = [APPROVAL_SEED, verifier.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: approval PDA, which may not exist for denied verifiers
+    pub approval: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Relay<'info> {
+    /// CHECK: any executable program; the stub only forwards to it
+    #[account(executable)]
+    pub target_program: UncheckedAccount<'info>,
+}
+
+#[account]
+pub struct Approval {
+    pub bump: u8,
+    pub verifier: Pubkey,
+}
+
+impl Approval {
+    pub const SPACE: usize = 8 + 1 + 32;
+}
@@ -5,6 +5,8 @@ use anchor_lang::{
 
 declare_id!("9zDeQgUTkwW1X2xW9ZZcACToGt9Lzoz1nAm88PtMu912");
 
+/// Each reserve is seeded by the key that opened it, so a vault's reserve
+/// can only be created by the vault itself.
 pub const ADAPTER_RESERVE_SEED: &[u8] = b"adapter-reserve";
 
 #[program]
@@ -12,7 +14,44 @@ pub mod vault_stub {
     use super::*;
 
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        ctx.accounts.adapter.bump = ctx.bumps.adapter;
+        let adapter = &mut ctx.accounts.adapter;
+        adapter.bump = ctx.bumps.adapter;
+        adapter.vault = ctx.accounts.authority.key();
+        adapter.authority = ctx.accounts.authority.key();
+        adapter.pending_authority = None;
+        Ok(())
+    }
+
+    /// First half of an authority rotation; the new key takes over only once
+    /// it signs `accept_reserve_authority`, so a typo can't lock the reserve.
+    pub fn transfer_reserve_authority(
+        ctx: Context<TransferReserveAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let adapter = &mut ctx.accounts.adapter;
+        adapter.pending_authority = Some(new_authority);
+
+        emit!(ReserveAuthorityProposed {
+            adapter: adapter.key(),
+            authority: adapter.authority,
+            pending_authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_reserve_authority(ctx: Context<AcceptReserveAuthority>) -> Result<()> {
+        let adapter = &mut ctx.accounts.adapter;
+        let previous = adapter.authority;
+        adapter.authority = ctx.accounts.new_authority.key();
+        adapter.pending_authority = None;
+
+        emit!(ReserveAuthorityTransferred {
+            adapter: adapter.key(),
+            previous_authority: previous,
+            authority: adapter.authority,
+        });
+
         Ok(())
     }
 
@@ -38,46 +77,105 @@ pub mod vault_stub {
     }
 
     pub fn harvest(ctx: Context<Harvest>, amount: u64) -> Result<()> {
-        require!(amount > 0, VaultStubError::InvalidAmount);
+        pay_out(
+            &ctx.accounts.adapter.to_account_info(),
+            &ctx.accounts.destination,
+            amount,
+        )
+    }
+
+    /// Returns deployed principal to the vault. The stub keeps principal and
+    /// yield in the same balance, so this moves lamports exactly like
+    /// `harvest`; the caller decides which ledger entry it settles.
+    pub fn withdraw(ctx: Context<Harvest>, amount: u64) -> Result<()> {
+        pay_out(
+            &ctx.accounts.adapter.to_account_info(),
+            &ctx.accounts.destination,
+            amount,
+        )
+    }
 
-        let adapter_info = ctx.accounts.adapter.to_account_info();
-        let destination_info = ctx.accounts.destination.to_account_info();
+    pub fn simulate_loss(ctx: Context<SimulateLoss>, amount: u64) -> Result<()> {
+        pay_out(
+            &ctx.accounts.adapter.to_account_info(),
+            &ctx.accounts.sink,
+            amount,
+        )
+    }
+}
 
-        require!(
-            adapter_info.lamports() >= amount,
-            VaultStubError::InsufficientReserve
-        );
+/// Moves `amount` out of the reserve, never dipping into its rent reserve.
+fn pay_out<'info>(
+    adapter: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, VaultStubError::InvalidAmount);
 
-        **adapter_info.try_borrow_mut_lamports()? -= amount;
-        **destination_info.try_borrow_mut_lamports()? += amount;
+    let available = adapter
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(adapter.data_len()));
+    require!(amount <= available, VaultStubError::InsufficientReserve);
 
-        Ok(())
-    }
+    **adapter.try_borrow_mut_lamports()? -= amount;
+    **destination.try_borrow_mut_lamports()? += amount;
+
+    Ok(())
 }
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
         init,
-        payer = authority,
+        payer = payer,
         space = AdapterReserve::SPACE,
-        seeds = [ADAPTER_RESERVE_SEED],
+        seeds = [ADAPTER_RESERVE_SEED, authority.key().as_ref()],
         bump,
     )]
     pub adapter: Account<'info, AdapterReserve>,
     #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Vault the reserve belongs to; a program-owned vault signs with its
+    /// PDA seeds.
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct TransferReserveAuthority<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ADAPTER_RESERVE_SEED, adapter.vault.as_ref()],
+        bump = adapter.bump,
+        has_one = authority @ VaultStubError::Unauthorized,
+    )]
+    pub adapter: Account<'info, AdapterReserve>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptReserveAuthority<'info> {
+    pub new_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ADAPTER_RESERVE_SEED, adapter.vault.as_ref()],
+        bump = adapter.bump,
+        constraint = adapter.pending_authority == Some(new_authority.key())
+            @ VaultStubError::Unauthorized,
+    )]
+    pub adapter: Account<'info, AdapterReserve>,
+}
+
 #[derive(Accounts)]
 pub struct FundReserve<'info> {
+    pub authority: Signer<'info>,
     #[account(mut)]
     pub funder: Signer<'info>,
     #[account(
         mut,
-        seeds = [ADAPTER_RESERVE_SEED],
+        seeds = [ADAPTER_RESERVE_SEED, adapter.vault.as_ref()],
         bump = adapter.bump,
+        has_one = authority @ VaultStubError::Unauthorized,
     )]
     pub adapter: Account<'info, AdapterReserve>,
     pub system_program: Program<'info, System>,
@@ -85,10 +183,12 @@ pub struct FundReserve<'info> {
 
 #[derive(Accounts)]
 pub struct Harvest<'info> {
+    pub authority: Signer<'info>,
     #[account(
         mut,
-        seeds = [ADAPTER_RESERVE_SEED],
+        seeds = [ADAPTER_RESERVE_SEED, adapter.vault.as_ref()],
         bump = adapter.bump,
+        has_one = authority @ VaultStubError::Unauthorized,
     )]
     pub adapter: Account<'info, AdapterReserve>,
     /// CHECK: destination is validated by the calling program
@@ -96,13 +196,48 @@ pub struct Harvest<'info> {
     pub destination: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SimulateLoss<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ADAPTER_RESERVE_SEED, adapter.vault.as_ref()],
+        bump = adapter.bump,
+        has_one = authority @ VaultStubError::Unauthorized,
+    )]
+    pub adapter: Account<'info, AdapterReserve>,
+    /// CHECK: receives the lamports written off from the reserve
+    #[account(mut)]
+    pub sink: AccountInfo<'info>,
+}
+
 #[account]
 pub struct AdapterReserve {
     pub bump: u8,
+    /// Key the reserve was opened for, part of its address.
+    pub vault: Pubkey,
+    /// Only key allowed to move lamports in or out of the reserve.
+    pub authority: Pubkey,
+    /// Proposed by `transfer_reserve_authority`, waiting to be accepted.
+    pub pending_authority: Option<Pubkey>,
 }
 
 impl AdapterReserve {
-    pub const SPACE: usize = 8 + 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 32 + 1 + 32;
+}
+
+#[event]
+pub struct ReserveAuthorityProposed {
+    pub adapter: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct ReserveAuthorityTransferred {
+    pub adapter: Pubkey,
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
 }
 
 #[error_code]
@@ -113,4 +248,6 @@ pub enum VaultStubError {
     InvalidAmount,
     #[msg("Not enough funds in the adapter reserve")]
     InsufficientReserve,
+    #[msg("Signer is not allowed to manage the adapter reserve")]
+    Unauthorized,
 }
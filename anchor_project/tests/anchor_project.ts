@@ -2,20 +2,42 @@ import * as anchor from "@coral-xyz/anchor";
 import BN from "bn.js";
 import { assert } from "chai";
 import {
+  Ed25519Program,
   Keypair,
   LAMPORTS_PER_SOL,
   PublicKey,
   SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   SYSVAR_RENT_PUBKEY,
+  SYSVAR_SLOT_HASHES_PUBKEY,
+  Transaction,
 } from "@solana/web3.js";
-import { getAccount, getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+  mintTo,
+} from "@solana/spl-token";
+import { createHash } from "crypto";
 
 const EVENT_SEED = Buffer.from("event");
 const VAULT_STATE_SEED = Buffer.from("vault-state");
 const VAULT_TREASURY_SEED = Buffer.from("vault-treasury");
 const PASS_SEED = Buffer.from("event-pass");
 const LOYALTY_MINT_SEED = Buffer.from("loyalty-mint");
+const LOYALTY_AUTHORITY_SEED = Buffer.from("loyalty-authority");
 const ADAPTER_RESERVE_SEED = Buffer.from("adapter-reserve");
+const ORGANIZER_STATS_SEED = Buffer.from("organizer-stats");
+const APPROVAL_SEED = Buffer.from("approval");
+const MANDATE_SEED = Buffer.from("renew-mandate");
+const CREDIT_SEED = Buffer.from("credit");
+const GUEST_LIST_SEED = Buffer.from("guest-list");
+const LUCKY_MINT_SEED = Buffer.from("lucky-mint");
+const ATTENDEE_RECORD_SEED = Buffer.from("attendee-record");
+const TOKEN_VAULT_SEED = Buffer.from("token-vault");
+const PROTOCOL_CONFIG_SEED = Buffer.from("protocol-config");
 
 const expectAnchorError = (err: unknown, code: string) => {
   let anchorErr: anchor.AnchorError | null = null;
@@ -46,6 +68,7 @@ describe("eventflux anchor program", () => {
 
   const program = anchor.workspace.anchorProject as anchor.Program;
   const vaultStub = anchor.workspace.vaultStub as anchor.Program;
+  const authStub = anchor.workspace.authStub as anchor.Program;
 
   const randomEventId = () => new BN(Date.now() + Math.floor(Math.random() * 1_000));
   const organizer = provider.wallet as anchor.Wallet;
@@ -64,22 +87,42 @@ describe("eventflux anchor program", () => {
       [VAULT_TREASURY_SEED, eventPda.toBuffer()],
       program.programId
     );
+    const [guestListPda] = PublicKey.findProgramAddressSync(
+      [GUEST_LIST_SEED, eventPda.toBuffer()],
+      program.programId
+    );
 
-    return { eventPda, vaultStatePda, vaultTreasuryPda };
+    return { eventPda, vaultStatePda, vaultTreasuryPda, guestListPda };
   };
 
   const findEventPassPda = (
     eventPda: PublicKey,
     attendee: PublicKey,
-    tierId: number
+    passIndex: BN
   ) => {
     const [eventPassPda] = PublicKey.findProgramAddressSync(
-      [PASS_SEED, eventPda.toBuffer(), attendee.toBuffer(), Buffer.from([tierId])],
+      [
+        PASS_SEED,
+        eventPda.toBuffer(),
+        attendee.toBuffer(),
+        passIndex.toArrayLike(Buffer, "le", 8),
+      ],
       program.programId
     );
     return eventPassPda;
   };
 
+  const findAttendeeRecordPda = (eventPda: PublicKey, attendee: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [ATTENDEE_RECORD_SEED, eventPda.toBuffer(), attendee.toBuffer()],
+      program.programId
+    )[0];
+
+  const nextPassIndex = async (eventPda: PublicKey): Promise<BN> => {
+    const eventAccount: any = await program.account.event.fetch(eventPda);
+    return eventAccount.nextPassIndex;
+  };
+
   const findLoyaltyMintPda = (eventPassPda: PublicKey) => {
     const [mintPda] = PublicKey.findProgramAddressSync(
       [LOYALTY_MINT_SEED, eventPassPda.toBuffer()],
@@ -88,48 +131,96 @@ describe("eventflux anchor program", () => {
     return mintPda;
   };
 
+  const findLoyaltyAuthorityPda = (eventPda: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [LOYALTY_AUTHORITY_SEED, eventPda.toBuffer()],
+      program.programId
+    )[0];
+
   const fundWallet = async (pubkey: PublicKey, amount = 2 * LAMPORTS_PER_SOL) => {
     const sig = await provider.connection.requestAirdrop(pubkey, amount);
     await provider.connection.confirmTransaction(sig);
   };
 
-  const [adapterReservePda] = PublicKey.findProgramAddressSync(
-    [ADAPTER_RESERVE_SEED],
-    vaultStub.programId
+  const [organizerStatsPda] = PublicKey.findProgramAddressSync(
+    [ORGANIZER_STATS_SEED, organizer.publicKey.toBuffer()],
+    program.programId
+  );
+  const [protocolConfigPda] = PublicKey.findProgramAddressSync(
+    [PROTOCOL_CONFIG_SEED],
+    program.programId
   );
 
-  const ensureAdapterInitialized = async () => {
-    try {
-      await vaultStub.methods
-        .initialize()
+  // The config is a singleton, so whichever test needs it first creates it
+  // with the organizer as admin.
+  const ensureProtocolConfig = async () => {
+    if (await provider.connection.getAccountInfo(protocolConfigPda)) {
+      return;
+    }
+    const [programDataPda] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    await program.methods
+      .initializeProtocolConfig(organizer.publicKey)
+      .accounts({
+        authority: organizer.publicKey,
+        protocolConfig: protocolConfigPda,
+        program: program.programId,
+        programData: programDataPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  };
+
+  // Every event's vault opens its own reserve in the adapter.
+  const adapterReserveFor = (vaultStatePda: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [ADAPTER_RESERVE_SEED, vaultStatePda.toBuffer()],
+      vaultStub.programId
+    )[0];
+
+  const initializeAdapterReserve = async (fixture: {
+    eventPda: PublicKey;
+    vaultStatePda: PublicKey;
+  }) => {
+    const adapterReserve = adapterReserveFor(fixture.vaultStatePda);
+    if (!(await provider.connection.getAccountInfo(adapterReserve))) {
+      await program.methods
+        .initializeAdapterReserve()
         .accounts({
-          adapter: adapterReservePda,
-          authority: organizer.publicKey,
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          adapterReserve,
+          vaultAdapterProgram: vaultStub.programId,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
-    } catch (err: any) {
-      const anchorErr = anchor.AnchorError.parse(err);
-      if (anchorErr?.error?.errorCode?.code !== "AccountAlreadyInitialized") {
-        throw err;
-      }
     }
+    return adapterReserve;
   };
 
-  const topUpAdapterReserve = async (amountLamports: number) => {
-    await vaultStub.methods
-      .fundReserve(new BN(amountLamports))
-      .accounts({
-        funder: organizer.publicKey,
-        adapter: adapterReservePda,
-        systemProgram: SystemProgram.programId,
-      })
-      .rpc();
+  // Yield lands in the reserve as a plain transfer, the way interest accrues
+  // to a position in a lending market.
+  const topUpAdapterReserve = async (
+    fixture: { eventPda: PublicKey; vaultStatePda: PublicKey },
+    amountLamports: number
+  ) => {
+    const adapterReserve = await initializeAdapterReserve(fixture);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: organizer.publicKey,
+          toPubkey: adapterReserve,
+          lamports: amountLamports,
+        })
+      )
+    );
   };
 
   before(async () => {
     await fundWallet(organizer.publicKey, 4 * LAMPORTS_PER_SOL);
-    await ensureAdapterInitialized();
   });
 
   const buildEventArgs = (overrides: Record<string, any> = {}) => {
@@ -151,14 +242,50 @@ describe("eventflux anchor program", () => {
       yieldStrategy: overrides.yieldStrategy ?? { none: {} },
       authorizedVerifiers: overrides.authorizedVerifiers ?? [],
       tiers: overrides.tiers ?? [defaultTier],
+      strictTierOrder: overrides.strictTierOrder ?? false,
+      authProgram: overrides.authProgram ?? null,
+      loyaltyRequiresCheckin: overrides.loyaltyRequiresCheckin ?? true,
+      currency: overrides.currency ?? { sol: {} },
+      tiersFinalized: overrides.tiersFinalized ?? true,
+      metadataHash: overrides.metadataHash ?? Array(32).fill(0),
+      loyaltyDelaySecs: overrides.loyaltyDelaySecs ?? new BN(0),
+      maxReentries: overrides.maxReentries ?? 0,
+      maxVerifiers: overrides.maxVerifiers ?? 5,
+      resaleRoyaltyBps: overrides.resaleRoyaltyBps ?? 0,
+      maxResaleLamports: overrides.maxResaleLamports ?? null,
+      allowCpi: overrides.allowCpi ?? false,
+      imageUri: overrides.imageUri ?? null,
+      thumbnailUri: overrides.thumbnailUri ?? null,
+      allowlistRoot: overrides.allowlistRoot ?? null,
+      maxPassesPerWallet: overrides.maxPassesPerWallet ?? 0,
+      referralBps: overrides.referralBps ?? 0,
+      saleStartTs: overrides.saleStartTs ?? new BN(0),
+      attendeeShareBps: overrides.attendeeShareBps ?? 0,
     } as any;
   };
 
+  const initializeTreasury = async (fixture: {
+    eventPda: PublicKey;
+    vaultStatePda: PublicKey;
+    vaultTreasuryPda: PublicKey;
+  }) => {
+    await program.methods
+      .initializeEventTreasury()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  };
+
   const createEventFixture = async (
     overrides: Record<string, any> = {}
   ) => {
     const args = buildEventArgs(overrides);
-    const { eventPda, vaultStatePda, vaultTreasuryPda } = findEventPdas(
+    const { eventPda, vaultStatePda, vaultTreasuryPda, guestListPda } = findEventPdas(
       organizer.publicKey,
       args.eventId
     );
@@ -168,14 +295,18 @@ describe("eventflux anchor program", () => {
       .accounts({
         organizer: organizer.publicKey,
         event: eventPda,
+        organizerStats: organizerStatsPda,
         vaultState: vaultStatePda,
-        vaultTreasury: vaultTreasuryPda,
+        guestList: guestListPda,
         systemProgram: SystemProgram.programId,
-        rent: SYSVAR_RENT_PUBKEY,
       })
       .rpc();
 
-    return { args, eventPda, vaultStatePda, vaultTreasuryPda };
+    const fixture = { args, eventPda, vaultStatePda, vaultTreasuryPda, guestListPda };
+    if (!overrides.skipTreasury) {
+      await initializeTreasury(fixture);
+    }
+    return fixture;
   };
 
   const mintPass = async (
@@ -187,17 +318,23 @@ describe("eventflux anchor program", () => {
     const eventPassPda = findEventPassPda(
       fixture.eventPda,
       attendee.publicKey,
-      tierId
+      await nextPassIndex(fixture.eventPda)
     );
 
     await program.methods
-      .mintPass(tierId)
+      .mintPass(tierId, new BN(0), null)
       .accounts({
         attendee: attendee.publicKey,
         event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
         vaultState: fixture.vaultStatePda,
         vaultTreasury: fixture.vaultTreasuryPda,
+        guestList: fixture.guestListPda,
         eventPass: eventPassPda,
+        attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+        protocolConfig: protocolConfigPda,
+        feeCollector: null,
+        referrer: null,
         systemProgram: SystemProgram.programId,
       })
       .signers([attendee])
@@ -244,6 +381,7 @@ describe("eventflux anchor program", () => {
         .accounts({
           verifier: randomVerifier.publicKey,
           event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
           eventPass: eventPassPda,
         })
         .signers([randomVerifier])
@@ -258,6 +396,7 @@ describe("eventflux anchor program", () => {
       .accounts({
         verifier: verifier.publicKey,
         event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
         eventPass: eventPassPda,
       })
       .signers([verifier])
@@ -272,6 +411,7 @@ describe("eventflux anchor program", () => {
         .accounts({
           verifier: attendee.publicKey,
           event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
           eventPass: eventPassPda,
         })
         .signers([attendee])
@@ -280,6 +420,8 @@ describe("eventflux anchor program", () => {
     } catch (err: any) {
       expectAnchorError(err, "AlreadyCheckedIn");
     }
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.checkedInCount.toNumber(), 1);
   });
 
   it("withdraws treasury to the organizer after the event", async () => {
@@ -301,6 +443,7 @@ describe("eventflux anchor program", () => {
       .accounts({
         organizer: organizer.publicKey,
         event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
         vaultState: fixture.vaultStatePda,
         destination: organizer.publicKey,
         vaultTreasury: fixture.vaultTreasuryPda,
@@ -314,6 +457,9 @@ describe("eventflux anchor program", () => {
     assert.isAbove(destinationAfter - destinationBefore, 0);
     const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
     assert.isTrue(eventAccount.settled);
+    // Settlement closes the treasury, rent reserve included, instead of
+    // leaving it below rent-exemption.
+    assert.isNull(await provider.connection.getAccountInfo(fixture.vaultTreasuryPda));
   });
 
   it("harvests yield via the vault adapter stub", async () => {
@@ -322,7 +468,7 @@ describe("eventflux anchor program", () => {
     });
     await mintPass(fixture, 1);
     const harvestAmount = LAMPORTS_PER_SOL / 20;
-    await topUpAdapterReserve(harvestAmount);
+    await topUpAdapterReserve(fixture, harvestAmount);
 
     const vaultBefore = await provider.connection.getBalance(
       fixture.vaultTreasuryPda
@@ -335,7 +481,7 @@ describe("eventflux anchor program", () => {
         event: fixture.eventPda,
         vaultState: fixture.vaultStatePda,
         vaultTreasury: fixture.vaultTreasuryPda,
-        adapterReserve: adapterReservePda,
+        adapterReserve: adapterReserveFor(fixture.vaultStatePda),
         vaultAdapterProgram: vaultStub.programId,
       })
       .rpc();
@@ -362,6 +508,7 @@ describe("eventflux anchor program", () => {
       .accounts({
         verifier: organizer.publicKey,
         event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
         eventPass: eventPassPda,
       })
       .rpc();
@@ -379,6 +526,7 @@ describe("eventflux anchor program", () => {
         event: fixture.eventPda,
         eventPass: eventPassPda,
         passOwner: attendee.publicKey,
+        loyaltyAuthority: findLoyaltyAuthorityPda(fixture.eventPda),
         loyaltyMint: loyaltyMintPda,
         loyaltyTokenAccount: ata,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
@@ -402,6 +550,7 @@ describe("eventflux anchor program", () => {
           event: fixture.eventPda,
           eventPass: eventPassPda,
           passOwner: attendee.publicKey,
+          loyaltyAuthority: findLoyaltyAuthorityPda(fixture.eventPda),
           loyaltyMint: loyaltyMintPda,
           loyaltyTokenAccount: ata,
           tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
@@ -412,8 +561,21 @@ describe("eventflux anchor program", () => {
         .rpc();
       assert.fail("Expected loyalty already issued error");
     } catch (err: any) {
-      expectAnchorError(err, "LoyaltyAlreadyIssued");
+      // The mint's authority is revoked after the first issue, so the
+      // account checks reject a repeat before the handler runs.
+      expectAnchorError(err, "ConstraintMintMintAuthority");
+    }
+
+    const loyaltyMint = await getMint(provider.connection, loyaltyMintPda);
+    assert.isNull(loyaltyMint.mintAuthority);
+    try {
+      await mintTo(provider.connection, organizer.payer, loyaltyMintPda, ata, organizer.payer, 1);
+      assert.fail("Expected minting against a locked loyalty mint to fail");
+    } catch (err: any) {
+      // SPL Token's FixedSupply: the mint has no authority left.
+      assert.match(String(err), /custom program error: 0x5\b/);
     }
+    assert.equal(Number((await getAccount(provider.connection, ata)).amount), 1);
   });
 
   it("fails to mint once supply is exhausted", async () => {
@@ -434,18 +596,24 @@ describe("eventflux anchor program", () => {
     const eventPassPda = findEventPassPda(
       fixture.eventPda,
       lateAttendee.publicKey,
-      1
+      await nextPassIndex(fixture.eventPda)
     );
 
     try {
       await program.methods
-        .mintPass(1)
+        .mintPass(1, new BN(0), null)
         .accounts({
           attendee: lateAttendee.publicKey,
           event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
           vaultState: fixture.vaultStatePda,
           vaultTreasury: fixture.vaultTreasuryPda,
+          guestList: fixture.guestListPda,
           eventPass: eventPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, lateAttendee.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollector: null,
+          referrer: null,
           systemProgram: SystemProgram.programId,
         })
         .signers([lateAttendee])
@@ -455,4 +623,5517 @@ describe("eventflux anchor program", () => {
       expectAnchorError(err, "TierSoldOut");
     }
   });
+
+  it("accepts arbitrary tier layouts unless strict ordering is requested", async () => {
+    const tier = (tierId: number, price: number) => ({
+      tierId,
+      label: `Tier ${tierId}`,
+      priceLamports: new BN(price),
+      maxSupply: 5,
+    });
+    const unordered = [tier(3, LAMPORTS_PER_SOL / 5), tier(1, LAMPORTS_PER_SOL / 10)];
+
+    const flexible = await createEventFixture({ tiers: unordered });
+    const flexibleEvent: any = await program.account.event.fetch(flexible.eventPda);
+    assert.isFalse(flexibleEvent.strictTierOrder);
+    assert.deepEqual(
+      flexibleEvent.tiers.map((t: any) => t.tierId),
+      [3, 1]
+    );
+
+    const strict = await createEventFixture({
+      strictTierOrder: true,
+      tiers: [tier(0, LAMPORTS_PER_SOL / 10), tier(1, LAMPORTS_PER_SOL / 5)],
+    });
+    const strictEvent: any = await program.account.event.fetch(strict.eventPda);
+    assert.isTrue(strictEvent.strictTierOrder);
+    try {
+      await program.methods
+        .updateTier(0, new BN(LAMPORTS_PER_SOL / 2), null)
+        .accounts({ organizer: organizer.publicKey, event: strict.eventPda })
+        .rpc();
+      assert.fail("Expected a repricing to respect strict tier order");
+    } catch (err: any) {
+      expectAnchorError(err, "TiersNotOrdered");
+    }
+    try {
+      await program.methods
+        .addTier(tier(2, LAMPORTS_PER_SOL / 20) as any)
+        .accounts({
+          organizer: organizer.publicKey,
+          event: strict.eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Expected a released tier to respect strict tier order");
+    } catch (err: any) {
+      expectAnchorError(err, "TiersNotOrdered");
+    }
+
+    for (const tiers of [
+      unordered,
+      [tier(1, LAMPORTS_PER_SOL / 10), tier(2, LAMPORTS_PER_SOL / 5)],
+      [tier(0, LAMPORTS_PER_SOL / 5), tier(1, LAMPORTS_PER_SOL / 10)],
+    ]) {
+      try {
+        await createEventFixture({ strictTierOrder: true, tiers });
+        assert.fail("Expected strict tier ordering to reject the layout");
+      } catch (err: any) {
+        expectAnchorError(err, "TiersNotOrdered");
+      }
+    }
+  });
+
+  it("absorbs unexpected treasury deposits as surplus", async () => {
+    const fixture = await createEventFixture();
+    await mintPass(fixture, 1);
+
+    const donation = LAMPORTS_PER_SOL / 25;
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: organizer.publicKey,
+          toPubkey: fixture.vaultTreasuryPda,
+          lamports: donation,
+        })
+      )
+    );
+
+    const reconcile = () =>
+      program.methods
+        .reconcileTreasury()
+        .accounts({
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+        })
+        .rpc();
+
+    await reconcile();
+    let vaultState: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+    assert.equal(vaultState.surplusLamports.toNumber(), donation);
+    assert.equal(vaultState.totalDeposited.toNumber(), LAMPORTS_PER_SOL / 10);
+
+    await reconcile();
+    vaultState = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.surplusLamports.toNumber(), donation);
+  });
+
+  it("delegates check-in authorization to an external program", async () => {
+    const approved = Keypair.generate();
+    const denied = Keypair.generate();
+    await fundWallet(approved.publicKey);
+    await fundWallet(denied.publicKey);
+
+    const findApprovalPda = (verifier: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [APPROVAL_SEED, verifier.toBuffer()],
+        authStub.programId
+      )[0];
+
+    await authStub.methods
+      .approveVerifier(approved.publicKey)
+      .accounts({
+        approval: findApprovalPda(approved.publicKey),
+        authority: organizer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const fixture = await createEventFixture({
+      authProgram: authStub.programId,
+      authorizedVerifiers: [denied.publicKey],
+    });
+    const { eventPassPda } = await mintPass(fixture, 1);
+
+    const checkInWith = (verifier: Keypair) =>
+      program.methods
+        .checkIn()
+        .accounts({
+          verifier: verifier.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: eventPassPda,
+        })
+        .remainingAccounts([
+          { pubkey: authStub.programId, isSigner: false, isWritable: false },
+          {
+            pubkey: findApprovalPda(verifier.publicKey),
+            isSigner: false,
+            isWritable: false,
+          },
+        ])
+        .signers([verifier])
+        .rpc();
+
+    try {
+      await checkInWith(denied);
+      assert.fail("Expected the auth program to deny the verifier");
+    } catch (err: any) {
+      expectAnchorError(err, "UnauthorizedVerifier");
+    }
+
+    await checkInWith(approved);
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isTrue(passAccount.checkedIn);
+  });
+
+  it("enforces per-tier check-in windows", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 600),
+      endTs: new BN(now + 7200),
+      tiers: [
+        {
+          tierId: 1,
+          label: "Day 1",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 20),
+          maxSupply: 5,
+          checkinStart: new BN(now - 600),
+          checkinEnd: new BN(now + 1800),
+        },
+        {
+          tierId: 2,
+          label: "Day 2",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 20),
+          maxSupply: 5,
+          checkinStart: new BN(now + 3600),
+          checkinEnd: null,
+        },
+      ],
+    });
+    const dayOne = await mintPass(fixture, 1);
+    const dayTwo = await mintPass(fixture, 2);
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: dayOne.eventPassPda,
+      })
+      .rpc();
+    const dayOnePass: any = await program.account.eventPass.fetch(
+      dayOne.eventPassPda
+    );
+    assert.isTrue(dayOnePass.checkedIn);
+
+    try {
+      await program.methods
+        .checkIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: dayTwo.eventPassPda,
+        })
+        .rpc();
+      assert.fail("Expected the day 2 pass to be outside its window");
+    } catch (err: any) {
+      expectAnchorError(err, "OutsideTierWindow");
+    }
+  });
+
+  it("mints several passes to one wallet with sequential indices", async () => {
+    const fixture = await createEventFixture({
+      tiers: [
+        {
+          tierId: 1,
+          label: "GA",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 50),
+          maxSupply: 5,
+        },
+        {
+          tierId: 2,
+          label: "VIP",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 20),
+          maxSupply: 5,
+        },
+      ],
+    });
+    const attendee = Keypair.generate();
+
+    const minted = [];
+    for (const tierId of [1, 1, 2]) {
+      minted.push(await mintPass(fixture, tierId, attendee));
+    }
+
+    for (const [index, { eventPassPda }] of minted.entries()) {
+      assert.equal(
+        eventPassPda.toBase58(),
+        findEventPassPda(fixture.eventPda, attendee.publicKey, new BN(index)).toBase58()
+      );
+      const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+      assert.equal(passAccount.passIndex.toNumber(), index);
+      assert.equal(passAccount.owner.toBase58(), attendee.publicKey.toBase58());
+    }
+
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.nextPassIndex.toNumber(), 3);
+    assert.equal(eventAccount.totalPasses.toNumber(), 3);
+  });
+
+  it("closes an empty tier and shrinks the event account", async () => {
+    const fixture = await createEventFixture({
+      tiers: [
+        {
+          tierId: 1,
+          label: "GA",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 20),
+          maxSupply: 5,
+        },
+        {
+          tierId: 2,
+          label: "Late Release",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 10),
+          maxSupply: 5,
+        },
+      ],
+    });
+    await mintPass(fixture, 1);
+
+    const closeTier = (tierId: number) =>
+      program.methods
+        .closeEmptyTier(tierId)
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    try {
+      await closeTier(1);
+      assert.fail("Expected a tier with sold passes to stay open");
+    } catch (err: any) {
+      expectAnchorError(err, "TierNotEmpty");
+    }
+
+    const before = await provider.connection.getAccountInfo(fixture.eventPda);
+    await closeTier(2);
+    const after = await provider.connection.getAccountInfo(fixture.eventPda);
+
+    assert.isBelow(after!.data.length, before!.data.length);
+    assert.isBelow(after!.lamports, before!.lamports);
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.deepEqual(
+      eventAccount.tiers.map((t: any) => t.tierId),
+      [1]
+    );
+  });
+
+  it("derives an effective APY from harvested yield", async () => {
+    const fixture = await createEventFixture({
+      yieldStrategy: { kamino: {} },
+    });
+    await mintPass(fixture, 1);
+    const harvestAmount = LAMPORTS_PER_SOL / 100;
+    await topUpAdapterReserve(fixture, harvestAmount);
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+
+    await program.methods
+      .harvestYield(new BN(harvestAmount))
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        adapterReserve: adapterReserveFor(fixture.vaultStatePda),
+        vaultAdapterProgram: vaultStub.programId,
+      })
+      .rpc();
+
+    const vaultState: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+    const elapsed = vaultState.lastHarvestTs.sub(vaultState.firstDepositTs);
+    assert.isAbove(elapsed.toNumber(), 0);
+
+    const expectedApy = new BN(harvestAmount)
+      .mul(new BN(10_000))
+      .mul(new BN(365 * 24 * 60 * 60))
+      .div(vaultState.totalDeposited.mul(elapsed));
+    assert.equal(vaultState.effectiveApyBps.toString(), expectedApy.toString());
+
+    const metrics: any = await program.methods
+      .getYieldMetrics()
+      .accounts({ vaultState: fixture.vaultStatePda })
+      .view();
+    assert.equal(metrics.effectiveApyBps.toString(), expectedApy.toString());
+    assert.equal(metrics.totalYieldHarvested.toNumber(), harvestAmount);
+
+    const idle = await createEventFixture({ yieldStrategy: { kamino: {} } });
+    const idleMetrics: any = await program.methods
+      .getYieldMetrics()
+      .accounts({ vaultState: idle.vaultStatePda })
+      .view();
+    assert.equal(idleMetrics.effectiveApyBps.toNumber(), 0);
+  });
+
+  it("requires the treasury to be initialized before minting", async () => {
+    const fixture = await createEventFixture({ skipTreasury: true });
+    const vaultState: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+    assert.isFalse(vaultState.treasuryInitialized);
+    assert.isNull(
+      await provider.connection.getAccountInfo(fixture.vaultTreasuryPda)
+    );
+
+    try {
+      await mintPass(fixture, 1);
+      assert.fail("Expected minting to require an initialized treasury");
+    } catch (err: any) {
+      expectAnchorError(err, "TreasuryNotInitialized");
+    }
+
+    await initializeTreasury(fixture);
+    const treasury = await provider.connection.getAccountInfo(
+      fixture.vaultTreasuryPda
+    );
+    assert.equal(treasury!.owner.toBase58(), program.programId.toBase58());
+
+    try {
+      await initializeTreasury(fixture);
+      assert.fail("Expected a second treasury initialization to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "TreasuryAlreadyInitialized");
+    }
+
+    await mintPass(fixture, 1);
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.totalPasses.toNumber(), 1);
+  });
+
+  it("renews memberships within an attendee-approved mandate", async () => {
+    const price = LAMPORTS_PER_SOL / 20;
+    const renewalPeriod = 3600;
+    const fixture = await createEventFixture({
+      tiers: [
+        {
+          tierId: 1,
+          label: "Season",
+          priceLamports: new BN(price),
+          maxSupply: 5,
+          renewalPeriod: new BN(renewalPeriod),
+        },
+      ],
+    });
+
+    const findMandatePda = (eventPassPda: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [MANDATE_SEED, eventPassPda.toBuffer()],
+        program.programId
+      )[0];
+
+    const authorize = async (
+      attendee: Keypair,
+      eventPassPda: PublicKey,
+      maxPerPeriod: number
+    ) => {
+      await program.methods
+        .authorizeRenewal(new BN(maxPerPeriod), new BN(2), new BN(2 * price))
+        .accounts({
+          owner: attendee.publicKey,
+          eventPass: eventPassPda,
+          mandate: findMandatePda(eventPassPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attendee])
+        .rpc();
+    };
+
+    const renew = (eventPassPda: PublicKey) =>
+      program.methods
+        .renewMembership()
+        .accounts({
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          eventPass: eventPassPda,
+          mandate: findMandatePda(eventPassPda),
+        })
+        .rpc();
+
+    const member = await mintPass(fixture, 1);
+    const minted: any = await program.account.eventPass.fetch(member.eventPassPda);
+    await authorize(member.attendee, member.eventPassPda, price);
+    await new Promise((resolve) => setTimeout(resolve, 2500));
+
+    await renew(member.eventPassPda);
+    const renewed: any = await program.account.eventPass.fetch(member.eventPassPda);
+    assert.equal(
+      renewed.validUntil.toNumber(),
+      minted.validUntil.toNumber() + renewalPeriod
+    );
+    const vaultState: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+    assert.equal(vaultState.totalDeposited.toNumber(), 2 * price);
+
+    try {
+      await renew(member.eventPassPda);
+      assert.fail("Expected a second renewal within the period to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "MandatePeriodNotElapsed");
+    }
+
+    const frugal = await mintPass(fixture, 1);
+    await authorize(frugal.attendee, frugal.eventPassPda, price / 2);
+    await new Promise((resolve) => setTimeout(resolve, 2500));
+    try {
+      await renew(frugal.eventPassPda);
+      assert.fail("Expected the renewal to exceed the mandate limit");
+    } catch (err: any) {
+      expectAnchorError(err, "MandateLimitExceeded");
+    }
+
+    const balanceBefore = await provider.connection.getBalance(
+      frugal.attendee.publicKey
+    );
+    await program.methods
+      .revokeMandate()
+      .accounts({
+        owner: frugal.attendee.publicKey,
+        mandate: findMandatePda(frugal.eventPassPda),
+      })
+      .signers([frugal.attendee])
+      .rpc();
+    const balanceAfter = await provider.connection.getBalance(
+      frugal.attendee.publicKey
+    );
+    assert.isAbove(balanceAfter - balanceBefore, 2 * price - 10_000);
+
+    try {
+      await renew(frugal.eventPassPda);
+      assert.fail("Expected renewal against a revoked mandate to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "AccountNotInitialized");
+    }
+  });
+
+  it("simulates the mint price a purchase will be charged", async () => {
+    const fixture = await createEventFixture();
+    const quoted: BN = await program.methods
+      .simulateMintPrice(1)
+      .accounts({ event: fixture.eventPda })
+      .view();
+    assert.equal(quoted.toNumber(), fixture.args.tiers[0].priceLamports.toNumber());
+
+    const { eventPassPda } = await mintPass(fixture, 1);
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(passAccount.pricePaid.toNumber(), quoted.toNumber());
+
+    try {
+      await program.methods
+        .simulateMintPrice(9)
+        .accounts({ event: fixture.eventPda })
+        .rpc();
+      assert.fail("Expected an unknown tier to have no price");
+    } catch (err: any) {
+      expectAnchorError(err, "TierNotFound");
+    }
+  });
+
+  it("rejects passes presented against a different event", async () => {
+    const eventA = await createEventFixture();
+    const eventB = await createEventFixture();
+    const { attendee, eventPassPda } = await mintPass(eventA, 1);
+
+    try {
+      await program.methods
+        .checkIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: eventB.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: eventPassPda,
+        })
+        .rpc();
+      assert.fail("Expected check-in against the wrong event to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "PassEventMismatch");
+    }
+
+    const loyaltyMintPda = findLoyaltyMintPda(eventPassPda);
+    try {
+      await program.methods
+        .issueLoyaltyNft()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: eventB.eventPda,
+          eventPass: eventPassPda,
+          passOwner: attendee.publicKey,
+          loyaltyAuthority: findLoyaltyAuthorityPda(eventB.eventPda),
+          loyaltyMint: loyaltyMintPda,
+          loyaltyTokenAccount: getAssociatedTokenAddressSync(
+            loyaltyMintPda,
+            attendee.publicKey
+          ),
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+      assert.fail("Expected loyalty issuance against the wrong event to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "PassEventMismatch");
+    }
+  });
+
+  it("deposits, spends and withdraws prepaid credit", async () => {
+    const fixture = await createEventFixture();
+    const attendee = Keypair.generate();
+    await fundWallet(attendee.publicKey);
+    const price = LAMPORTS_PER_SOL / 10;
+    const [creditPda] = PublicKey.findProgramAddressSync(
+      [CREDIT_SEED, fixture.eventPda.toBuffer(), attendee.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .depositCredit(new BN(3 * price))
+      .accounts({
+        attendee: attendee.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        creditAccount: creditPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([attendee])
+      .rpc();
+
+    let credit: any = await program.account.creditAccount.fetch(creditPda);
+    assert.equal(credit.balance.toNumber(), 3 * price);
+    let vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.totalDeposited.toNumber(), 3 * price);
+
+    const eventPassPda = findEventPassPda(
+      fixture.eventPda,
+      attendee.publicKey,
+      await nextPassIndex(fixture.eventPda)
+    );
+    const walletBefore = await provider.connection.getBalance(attendee.publicKey);
+    await program.methods
+      .mintPassWithCredit(1, new BN(0))
+      .accounts({
+        attendee: attendee.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        organizerStats: organizerStatsPda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        creditAccount: creditPda,
+        guestList: fixture.guestListPda,
+        eventPass: eventPassPda,
+        attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+        protocolConfig: protocolConfigPda,
+        feeCollector: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([attendee])
+      .rpc();
+
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(passAccount.pricePaid.toNumber(), price);
+    credit = await program.account.creditAccount.fetch(creditPda);
+    assert.equal(credit.balance.toNumber(), 2 * price);
+    vaultState = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.totalDeposited.toNumber(), 3 * price);
+    const passRent = await provider.connection.getMinimumBalanceForRentExemption(
+      program.account.eventPass.size
+    );
+    const walletAfter = await provider.connection.getBalance(attendee.publicKey);
+    assert.isAbove(walletAfter, walletBefore - passRent - price);
+
+    await program.methods
+      .withdrawCredit(new BN(2 * price))
+      .accounts({
+        attendee: attendee.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        creditAccount: creditPda,
+      })
+      .signers([attendee])
+      .rpc();
+
+    credit = await program.account.creditAccount.fetch(creditPda);
+    assert.equal(credit.balance.toNumber(), 0);
+    vaultState = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.totalDeposited.toNumber(), price);
+
+    const nextPassPda = findEventPassPda(
+      fixture.eventPda,
+      attendee.publicKey,
+      await nextPassIndex(fixture.eventPda)
+    );
+    try {
+      await program.methods
+        .mintPassWithCredit(1, new BN(0))
+        .accounts({
+          attendee: attendee.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          organizerStats: organizerStatsPda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          creditAccount: creditPda,
+          guestList: fixture.guestListPda,
+          eventPass: nextPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollector: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attendee])
+        .rpc();
+      assert.fail("Expected mint with empty credit to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "InsufficientCredit");
+    }
+  });
+
+  it("blocks banned buyers until they are unbanned", async () => {
+    const fixture = await createEventFixture();
+    const attendee = Keypair.generate();
+
+    await program.methods
+      .banBuyer(attendee.publicKey)
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.deepEqual(
+      eventAccount.banned.map((key: PublicKey) => key.toBase58()),
+      [attendee.publicKey.toBase58()]
+    );
+
+    try {
+      await mintPass(fixture, 1, attendee);
+      assert.fail("Expected mint by a banned buyer to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "BuyerBanned");
+    }
+
+    await program.methods
+      .unbanBuyer(attendee.publicKey)
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const { eventPassPda } = await mintPass(fixture, 1, attendee);
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(passAccount.owner.toBase58(), attendee.publicKey.toBase58());
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.banned.length, 0);
+  });
+
+  it("reissues an unused pass to a new wallet without charging again", async () => {
+    const fixture = await createEventFixture();
+    const { attendee, eventPassPda } = await mintPass(fixture, 1);
+    const original: any = await program.account.eventPass.fetch(eventPassPda);
+    const newOwner = Keypair.generate();
+    const newPassPda = findEventPassPda(
+      fixture.eventPda,
+      newOwner.publicKey,
+      original.passIndex
+    );
+    const vaultBefore: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+
+    let reissued: any = null;
+    const listener = program.addEventListener("passReissued", (event: any) => {
+      reissued = event;
+    });
+    await program.methods
+      .reissuePass(newOwner.publicKey)
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        oldPass: eventPassPda,
+        guestList: fixture.guestListPda,
+        newPass: newPassPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+
+    assert.isNull(await program.account.eventPass.fetchNullable(eventPassPda));
+    const newPass: any = await program.account.eventPass.fetch(newPassPda);
+    assert.equal(newPass.owner.toBase58(), newOwner.publicKey.toBase58());
+    assert.equal(newPass.passIndex.toString(), original.passIndex.toString());
+    assert.equal(newPass.tierId, original.tierId);
+    assert.equal(newPass.pricePaid.toNumber(), original.pricePaid.toNumber());
+    assert.isFalse(newPass.checkedIn);
+
+    const vaultAfter: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+    assert.equal(
+      vaultAfter.totalDeposited.toNumber(),
+      vaultBefore.totalDeposited.toNumber()
+    );
+
+    assert.isNotNull(reissued);
+    assert.equal(reissued.oldPass.toBase58(), eventPassPda.toBase58());
+    assert.equal(reissued.newPass.toBase58(), newPassPda.toBase58());
+    assert.equal(reissued.oldOwner.toBase58(), attendee.publicKey.toBase58());
+  });
+
+  it("refuses to reissue a pass that already checked in", async () => {
+    const fixture = await createEventFixture();
+    const { eventPassPda } = await mintPass(fixture, 1);
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: eventPassPda,
+      })
+      .rpc();
+
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    const newOwner = Keypair.generate();
+    try {
+      await program.methods
+        .reissuePass(newOwner.publicKey)
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          oldPass: eventPassPda,
+          guestList: fixture.guestListPda,
+          newPass: findEventPassPda(
+            fixture.eventPda,
+            newOwner.publicKey,
+            passAccount.passIndex
+          ),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Expected reissue of a checked-in pass to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "AlreadyCheckedIn");
+    }
+  });
+
+  it("records when a tier sells out and clears it when supply grows", async () => {
+    const fixture = await createEventFixture({
+      tiers: [
+        {
+          tierId: 1,
+          label: "Limited",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 100),
+          maxSupply: 1,
+        },
+      ],
+    });
+
+    await mintPass(fixture, 1);
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.isNotNull(eventAccount.tiers[0].soldOutAt);
+    assert.closeTo(
+      eventAccount.tiers[0].soldOutAt.toNumber(),
+      Math.floor(Date.now() / 1000),
+      60
+    );
+
+    await program.methods
+      .increaseTierSupply(1, 2)
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+      })
+      .rpc();
+
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.isNull(eventAccount.tiers[0].soldOutAt);
+    assert.equal(eventAccount.tiers[0].maxSupply, 3);
+
+    await mintPass(fixture, 1);
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.tiers[0].sold, 2);
+    assert.isNull(eventAccount.tiers[0].soldOutAt);
+  });
+
+  it("previews the exact settlement payout before withdrawing", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 3),
+    });
+    await mintPass(fixture, 1);
+    await mintPass(fixture, 1);
+
+    await new Promise((resolve) => setTimeout(resolve, 3500));
+
+    const preview: any = await program.methods
+      .previewSettlement()
+      .accounts({
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .view();
+
+    const treasuryRent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    assert.equal(preview.destination.toBase58(), organizer.publicKey.toBase58());
+    assert.equal(preview.principal.toNumber(), 2 * (LAMPORTS_PER_SOL / 10));
+    assert.equal(preview.yieldHarvested.toNumber(), 0);
+    assert.equal(preview.other.toNumber(), treasuryRent);
+
+    const vaultBefore: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+    assert.equal(vaultBefore.totalWithdrawn.toNumber(), 0);
+
+    await program.methods
+      .withdrawTreasury()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        vaultState: fixture.vaultStatePda,
+        destination: organizer.publicKey,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .rpc();
+
+    const vaultAfter: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+    assert.equal(
+      vaultAfter.totalWithdrawn.toNumber(),
+      preview.totalPayout.toNumber()
+    );
+    assert.equal(await provider.connection.getBalance(fixture.vaultTreasuryPda), 0);
+  });
+
+  const issueLoyalty = (
+    fixture: Awaited<ReturnType<typeof createEventFixture>>,
+    eventPassPda: PublicKey,
+    owner: PublicKey
+  ) => {
+    const loyaltyMintPda = findLoyaltyMintPda(eventPassPda);
+    return program.methods
+      .issueLoyaltyNft()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        eventPass: eventPassPda,
+        passOwner: owner,
+        loyaltyAuthority: findLoyaltyAuthorityPda(fixture.eventPda),
+        loyaltyMint: loyaltyMintPda,
+        loyaltyTokenAccount: getAssociatedTokenAddressSync(loyaltyMintPda, owner),
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+  };
+
+  it("requires check-in for loyalty by default", async () => {
+    const fixture = await createEventFixture();
+    const { attendee, eventPassPda } = await mintPass(fixture, 1);
+
+    try {
+      await issueLoyalty(fixture, eventPassPda, attendee.publicKey);
+      assert.fail("Expected loyalty before check-in to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "PassNotCheckedIn");
+    }
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: eventPassPda,
+      })
+      .rpc();
+    await issueLoyalty(fixture, eventPassPda, attendee.publicKey);
+
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isNotNull(passAccount.loyaltyMint);
+  });
+
+  it("issues loyalty at purchase when check-in is not required", async () => {
+    const fixture = await createEventFixture({ loyaltyRequiresCheckin: false });
+    const { attendee, eventPassPda } = await mintPass(fixture, 1);
+
+    await issueLoyalty(fixture, eventPassPda, attendee.publicKey);
+
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isFalse(passAccount.checkedIn);
+    const tokenAccount = await getAccount(
+      provider.connection,
+      getAssociatedTokenAddressSync(
+        findLoyaltyMintPda(eventPassPda),
+        attendee.publicKey
+      )
+    );
+    assert.equal(Number(tokenAccount.amount), 1);
+
+    try {
+      await issueLoyalty(fixture, eventPassPda, attendee.publicKey);
+      assert.fail("Expected second loyalty issuance to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "ConstraintMintMintAuthority");
+    }
+  });
+
+  it("tags the treasury currency and gates lamport payments on it", async () => {
+    const solEvent = await createEventFixture();
+    const solAccount: any = await program.account.event.fetch(solEvent.eventPda);
+    assert.deepEqual(solAccount.currency, { sol: {} });
+    await mintPass(solEvent, 1);
+
+    const tokenMint = Keypair.generate().publicKey;
+    const splEvent = await createEventFixture({
+      currency: { spl: { 0: tokenMint } },
+    });
+    const splAccount: any = await program.account.event.fetch(splEvent.eventPda);
+    assert.equal(splAccount.currency.spl[0].toBase58(), tokenMint.toBase58());
+    const splVault: any = await program.account.vaultState.fetch(splEvent.vaultStatePda);
+    assert.equal(splVault.currency.spl[0].toBase58(), tokenMint.toBase58());
+
+    try {
+      await mintPass(splEvent, 1);
+      assert.fail("Expected a lamport mint on an SPL event to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "CurrencyMismatch");
+    }
+  });
+
+  const checkInDryRun = (
+    fixture: Awaited<ReturnType<typeof createEventFixture>>,
+    eventPassPda: PublicKey,
+    verifier?: Keypair
+  ) => {
+    const builder = program.methods.checkInDryRun().accounts({
+      verifier: verifier?.publicKey ?? organizer.publicKey,
+      event: fixture.eventPda,
+      eventPass: eventPassPda,
+    });
+    return (verifier ? builder.signers([verifier]) : builder).view() as Promise<any>;
+  };
+
+  it("reports a denial code for each check-in rule without mutating the pass", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+    const open = await createEventFixture();
+    const { eventPassPda } = await mintPass(open, 1);
+    let status = await checkInDryRun(open, eventPassPda);
+    assert.isTrue(status.eligible);
+    assert.isNull(status.denial);
+
+    status = await checkInDryRun(open, eventPassPda, Keypair.generate());
+    assert.isFalse(status.eligible);
+    assert.deepEqual(status.denial, { unauthorizedVerifier: {} });
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: open.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: eventPassPda,
+      })
+      .rpc();
+    status = await checkInDryRun(open, eventPassPda);
+    assert.deepEqual(status.denial, { alreadyCheckedIn: {} });
+
+    const upcoming = await createEventFixture({
+      startTs: new BN(now + 600),
+      endTs: new BN(now + 3600),
+    });
+    const upcomingPass = await mintPass(upcoming, 1);
+    status = await checkInDryRun(upcoming, upcomingPass.eventPassPda);
+    assert.deepEqual(status.denial, { eventNotStarted: {} });
+
+    const windowed = await createEventFixture({
+      tiers: [
+        {
+          tierId: 1,
+          label: "Late",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 100),
+          maxSupply: 5,
+          checkinStart: new BN(now + 1800),
+        },
+      ],
+    });
+    const windowedPass = await mintPass(windowed, 1);
+    status = await checkInDryRun(windowed, windowedPass.eventPassPda);
+    assert.deepEqual(status.denial, { outsideTierWindow: {} });
+
+    const membership = await createEventFixture({
+      tiers: [
+        {
+          tierId: 1,
+          label: "Monthly",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 100),
+          maxSupply: 5,
+          renewalPeriod: new BN(1),
+        },
+      ],
+    });
+    const membershipPass = await mintPass(membership, 1);
+    await sleep(2500);
+    status = await checkInDryRun(membership, membershipPass.eventPassPda);
+    assert.deepEqual(status.denial, { passExpired: {} });
+
+    const ending = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(Math.floor(Date.now() / 1000) + 3),
+    });
+    const endingPass = await mintPass(ending, 1);
+    await sleep(3500);
+    status = await checkInDryRun(ending, endingPass.eventPassPda);
+    assert.deepEqual(status.denial, { eventEnded: {} });
+
+    const untouched: any = await program.account.eventPass.fetch(
+      endingPass.eventPassPda
+    );
+    assert.isFalse(untouched.checkedIn);
+  });
+
+  it("extends a live event but never shortens it", async () => {
+    const fixture = await createEventFixture();
+    const before: any = await program.account.event.fetch(fixture.eventPda);
+    const newEnd = before.endTs.add(new BN(86_400));
+
+    await program.methods
+      .extendEvent(newEnd)
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+      })
+      .rpc();
+
+    const after: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(after.endTs.toString(), newEnd.toString());
+
+    try {
+      await program.methods
+        .extendEvent(before.endTs)
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+        })
+        .rpc();
+      assert.fail("Expected shortening the event to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidSchedule");
+    }
+  });
+
+  it("builds tiers incrementally, sells once finalized and releases tiers after launch", async () => {
+    const fixture = await createEventFixture({ tiers: [], tiersFinalized: false });
+
+    for (const tierId of [1, 2]) {
+      await program.methods
+        .addTier({
+          tierId,
+          label: `Tier ${tierId}`,
+          priceLamports: new BN((LAMPORTS_PER_SOL / 100) * tierId),
+          maxSupply: 10,
+        } as any)
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.tiers.length, 2);
+    assert.isFalse(eventAccount.tiersFinalized);
+
+    try {
+      await mintPass(fixture, 1);
+      assert.fail("Expected mint before finalization to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "TiersNotFinalized");
+    }
+
+    await program.methods
+      .finalizeTiers()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+      })
+      .rpc();
+
+    const { eventPassPda } = await mintPass(fixture, 2);
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(passAccount.tierId, 2);
+
+    // A tier released after launch sells straight away.
+    await program.methods
+      .addTier({
+        tierId: 3,
+        label: "Last-minute GA",
+        priceLamports: new BN(LAMPORTS_PER_SOL / 100),
+        maxSupply: 10,
+      } as any)
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.tiers.length, 3);
+    assert.isTrue(eventAccount.tiersFinalized);
+    const lateMint = await mintPass(fixture, 3);
+    const latePass: any = await program.account.eventPass.fetch(lateMint.eventPassPda);
+    assert.equal(latePass.tierId, 3);
+
+    try {
+      await program.methods
+        .finalizeTiers()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+        })
+        .rpc();
+      assert.fail("Expected finalizing twice to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "TiersFinalized");
+    }
+  });
+
+  it("rejects mints priced above the buyer's slippage bound", async () => {
+    const fixture = await createEventFixture();
+    const price = LAMPORTS_PER_SOL / 10;
+    const mintWithLimit = async (maxLamports: number) => {
+      const attendee = Keypair.generate();
+      await fundWallet(attendee.publicKey);
+      const eventPassPda = findEventPassPda(
+        fixture.eventPda,
+        attendee.publicKey,
+        await nextPassIndex(fixture.eventPda)
+      );
+      await program.methods
+        .mintPass(1, new BN(maxLamports), null)
+        .accounts({
+          attendee: attendee.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          guestList: fixture.guestListPda,
+          eventPass: eventPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollector: null,
+          referrer: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attendee])
+        .rpc();
+      return eventPassPda;
+    };
+
+    const withinBound = await mintWithLimit(price);
+    const passAccount: any = await program.account.eventPass.fetch(withinBound);
+    assert.equal(passAccount.pricePaid.toNumber(), price);
+
+    try {
+      await mintWithLimit(price - 1);
+      assert.fail("Expected mint above the slippage bound to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "PriceSlippageExceeded");
+    }
+
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.tiers[0].sold, 1);
+  });
+
+  it("stamps the first deposit time once and keeps it on later mints", async () => {
+    const fixture = await createEventFixture();
+    const fetchMetrics = () =>
+      program.methods
+        .getYieldMetrics()
+        .accounts({ vaultState: fixture.vaultStatePda })
+        .view() as Promise<any>;
+
+    const empty = await fetchMetrics();
+    assert.equal(empty.firstDepositTs.toNumber(), 0);
+
+    await mintPass(fixture, 1);
+    const first = await fetchMetrics();
+    assert.closeTo(first.firstDepositTs.toNumber(), Math.floor(Date.now() / 1000), 60);
+
+    await new Promise((resolve) => setTimeout(resolve, 1500));
+    await mintPass(fixture, 1);
+    const second = await fetchMetrics();
+    assert.equal(second.firstDepositTs.toString(), first.firstDepositTs.toString());
+
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.firstDepositTs.toString(), first.firstDepositTs.toString());
+  });
+
+  it("commits to off-chain metadata and rotates the hash on update", async () => {
+    const fakeHash = (seed: number) =>
+      Array.from({ length: 32 }, (_, i) => (seed + i * 7) % 256);
+    const originalHash = fakeHash(1);
+    const fixture = await createEventFixture({ metadataHash: originalHash });
+
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.deepEqual(Array.from(eventAccount.metadataHash), originalHash);
+
+    const updatedHash = fakeHash(2);
+    await program.methods
+      .updateEventMetadata("EventFlux Summit 2", "Metropolis Arena North", updatedHash)
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.deepEqual(Array.from(eventAccount.metadataHash), updatedHash);
+    assert.notDeepEqual(updatedHash, originalHash);
+    assert.equal(eventAccount.name, "EventFlux Summit 2");
+    assert.equal(eventAccount.venue, "Metropolis Arena North");
+  });
+
+  it("lets a checked-in attendee claim their own loyalty NFT", async () => {
+    const fixture = await createEventFixture();
+    const { attendee, eventPassPda } = await mintPass(fixture, 1);
+    const loyaltyMintPda = findLoyaltyMintPda(eventPassPda);
+    const [loyaltyAuthorityPda] = PublicKey.findProgramAddressSync(
+      [LOYALTY_AUTHORITY_SEED, fixture.eventPda.toBuffer()],
+      program.programId
+    );
+    const ata = getAssociatedTokenAddressSync(loyaltyMintPda, attendee.publicKey);
+    const claim = () =>
+      program.methods
+        .claimLoyalty()
+        .accounts({
+          owner: attendee.publicKey,
+          event: fixture.eventPda,
+          eventPass: eventPassPda,
+          loyaltyAuthority: loyaltyAuthorityPda,
+          loyaltyMint: loyaltyMintPda,
+          loyaltyTokenAccount: ata,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([attendee])
+        .rpc();
+
+    try {
+      await claim();
+      assert.fail("Expected claim before check-in to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "PassNotCheckedIn");
+    }
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: attendee.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: eventPassPda,
+      })
+      .signers([attendee])
+      .rpc();
+    await claim();
+
+    const tokenAccount = await getAccount(provider.connection, ata);
+    assert.equal(Number(tokenAccount.amount), 1);
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(passAccount.loyaltyMint.toBase58(), loyaltyMintPda.toBase58());
+
+    try {
+      await claim();
+      assert.fail("Expected a second claim to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "ConstraintMintMintAuthority");
+    }
+  });
+
+  it("summarizes an event's headline fields for listings", async () => {
+    const fixture = await createEventFixture();
+    await mintPass(fixture, 1);
+    await mintPass(fixture, 1);
+
+    const summary: any = await program.methods
+      .getEventSummary()
+      .accounts({
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .view();
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+
+    assert.equal(summary.eventId.toString(), eventAccount.eventId.toString());
+    assert.equal(summary.organizer.toBase58(), eventAccount.organizer.toBase58());
+    assert.equal(summary.name, eventAccount.name);
+    assert.equal(summary.venue, eventAccount.venue);
+    assert.equal(summary.startTs.toString(), eventAccount.startTs.toString());
+    assert.equal(summary.endTs.toString(), eventAccount.endTs.toString());
+    assert.equal(summary.totalPasses.toNumber(), 2);
+    assert.equal(summary.tierCount, eventAccount.tiers.length);
+    assert.deepEqual(summary.status, { live: {} });
+
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    const treasuryRent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const treasuryBalance = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    assert.equal(summary.checkedInCount.toNumber(), 0);
+    assert.equal(summary.remainingSupply.toNumber(), 3);
+    assert.equal(summary.totalDeposited.toNumber(), vaultState.totalDeposited.toNumber());
+    assert.equal(summary.netTreasuryBalance.toNumber(), treasuryBalance - treasuryRent);
+  });
+
+  it("throttles loyalty issuance until the post-check-in delay passes", async () => {
+    const fixture = await createEventFixture({ loyaltyDelaySecs: new BN(3) });
+    const { attendee, eventPassPda } = await mintPass(fixture, 1);
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: eventPassPda,
+      })
+      .rpc();
+
+    try {
+      await issueLoyalty(fixture, eventPassPda, attendee.publicKey);
+      assert.fail("Expected loyalty right after check-in to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "LoyaltyTooSoon");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    await issueLoyalty(fixture, eventPassPda, attendee.publicKey);
+
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isNotNull(passAccount.loyaltyMint);
+  });
+
+  it("pauses and resumes yield harvesting without touching sales", async () => {
+    const fixture = await createEventFixture({ yieldStrategy: { kamino: {} } });
+    const harvestAmount = LAMPORTS_PER_SOL / 100;
+    await topUpAdapterReserve(fixture, 2 * harvestAmount);
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .setHarvestPaused(paused)
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+        })
+        .rpc();
+    const harvest = () =>
+      program.methods
+        .harvestYield(new BN(harvestAmount))
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          adapterReserve: adapterReserveFor(fixture.vaultStatePda),
+          vaultAdapterProgram: vaultStub.programId,
+        })
+        .rpc();
+
+    await setPaused(true);
+    let vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.isTrue(vaultState.harvestPaused);
+
+    try {
+      await harvest();
+      assert.fail("Expected harvest to fail while paused");
+    } catch (err: any) {
+      expectAnchorError(err, "HarvestPaused");
+    }
+    await mintPass(fixture, 1);
+    const deposit = () =>
+      program.methods
+        .depositToAdapter(new BN(harvestAmount))
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          adapterReserve: adapterReserveFor(fixture.vaultStatePda),
+        })
+        .rpc();
+    try {
+      await deposit();
+      assert.fail("Expected adapter deposits to fail while paused");
+    } catch (err: any) {
+      expectAnchorError(err, "HarvestPaused");
+    }
+
+    await setPaused(false);
+    await deposit();
+    await harvest();
+    vaultState = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.isFalse(vaultState.harvestPaused);
+    assert.equal(vaultState.totalYieldHarvested.toNumber(), harvestAmount);
+  });
+
+  it("allows a bounded number of re-entries per pass", async () => {
+    const gateA = Keypair.generate();
+    const gateB = Keypair.generate();
+    const fixture = await createEventFixture({
+      maxReentries: 2,
+      authorizedVerifiers: [gateA.publicKey, gateB.publicKey],
+    });
+    const { attendee, eventPassPda } = await mintPass(fixture, 1);
+    // Distinct verifiers keep back-to-back scans from producing identical
+    // transactions.
+    const scan = (verifier?: Keypair) => {
+      const builder = program.methods.checkIn().accounts({
+        verifier: verifier?.publicKey ?? organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: eventPassPda,
+      });
+      return (verifier ? builder.signers([verifier]) : builder).rpc();
+    };
+
+    await scan();
+    let passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isTrue(passAccount.checkedIn);
+    assert.equal(passAccount.reentryCount, 0);
+    const firstEntry = passAccount.checkedInAt.toString();
+
+    await scan(attendee);
+    await scan(gateA);
+    passAccount = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(passAccount.reentryCount, 2);
+    assert.equal(passAccount.checkedInAt.toString(), firstEntry);
+    assert.isNotNull(passAccount.lastEntryAt);
+
+    try {
+      await scan(gateB);
+      assert.fail("Expected re-entry beyond the limit to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "ReentryLimitReached");
+    }
+
+    const singleEntry = await createEventFixture();
+    const single = await mintPass(singleEntry, 1);
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: singleEntry.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: single.eventPassPda,
+      })
+      .rpc();
+    try {
+      await program.methods
+        .checkIn()
+        .accounts({
+          verifier: single.attendee.publicKey,
+          event: singleEntry.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: single.eventPassPda,
+        })
+        .signers([single.attendee])
+        .rpc();
+      assert.fail("Expected a second entry on a single-entry event to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "AlreadyCheckedIn");
+    }
+  });
+
+  it("rolls sales, check-ins and revenue up into the organizer dashboard", async () => {
+    const dashboard = () =>
+      program.methods
+        .getOrganizerDashboard()
+        .accounts({ organizerStats: organizerStatsPda })
+        .view() as Promise<any>;
+    const before = await dashboard();
+    assert.equal(before.organizer.toBase58(), organizer.publicKey.toBase58());
+
+    const now = Math.floor(Date.now() / 1000);
+    const first = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 4),
+    });
+    const second = await createEventFixture();
+    const firstPass = await mintPass(first, 1);
+    await mintPass(first, 1);
+    const secondPass = await mintPass(second, 1);
+    const refunded = await mintPass(second, 1);
+
+    for (const [fixture, pass] of [
+      [first, firstPass],
+      [second, secondPass],
+    ] as const) {
+      await program.methods
+        .checkIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: pass.eventPassPda,
+        })
+        .rpc();
+    }
+    // A refunded pass no longer counts as sold.
+    await program.methods
+      .cancelEvent()
+      .accounts({ organizer: organizer.publicKey, event: second.eventPda })
+      .rpc();
+    await refundPass(second, refunded);
+
+    await new Promise((resolve) => setTimeout(resolve, 4500));
+    const payout = await provider.connection.getBalance(first.vaultTreasuryPda);
+    await program.methods
+      .withdrawTreasury()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: first.eventPda,
+        organizerStats: organizerStatsPda,
+        vaultState: first.vaultStatePda,
+        destination: organizer.publicKey,
+        vaultTreasury: first.vaultTreasuryPda,
+      })
+      .rpc();
+
+    const after = await dashboard();
+    assert.equal(after.lifetimePasses.sub(before.lifetimePasses).toNumber(), 3);
+    assert.equal(after.lifetimeCheckins.sub(before.lifetimeCheckins).toNumber(), 2);
+    assert.equal(after.lifetimeRevenue.sub(before.lifetimeRevenue).toNumber(), payout);
+  });
+
+  it("draws a lucky winner among checked-in passes and lets them claim", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 4),
+    });
+    const passes = [];
+    for (let i = 0; i < 3; i += 1) {
+      const pass = await mintPass(fixture, 1);
+      await program.methods
+        .checkIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: pass.eventPassPda,
+        })
+        .rpc();
+      passes.push(pass);
+    }
+    const drop = (candidates: PublicKey[]) =>
+      program.methods
+        .luckyDrop()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .remainingAccounts(
+          candidates.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+        )
+        .rpc();
+
+    try {
+      await drop(passes.map((pass) => pass.eventPassPda));
+      assert.fail("Expected a drop before the event ends to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "EventNotEnded");
+    }
+    await new Promise((resolve) => setTimeout(resolve, 4500));
+
+    let won: any = null;
+    const listener = program.addEventListener("luckyDropWon", (event: any) => {
+      won = event;
+    });
+    await drop(passes.map((pass) => pass.eventPassPda));
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+
+    // The slot hash can't be pinned on a live validator, so check the pick
+    // against the seed the program reported.
+    assert.isNotNull(won);
+    assert.equal(won.candidateCount, 3);
+    const expectedIndex = new BN(Buffer.from(won.seed).subarray(0, 8), "le").modn(3);
+    const winner = passes[expectedIndex];
+    assert.equal(won.eventPass.toBase58(), winner.eventPassPda.toBase58());
+    assert.equal(won.owner.toBase58(), winner.attendee.publicKey.toBase58());
+    const winningPass: any = await program.account.eventPass.fetch(winner.eventPassPda);
+    assert.isTrue(winningPass.luckyWinner);
+
+    try {
+      await drop([winner.eventPassPda]);
+      assert.fail("Expected a drop with only past winners to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "NoLuckyDropCandidates");
+    }
+
+    const [loyaltyAuthorityPda] = PublicKey.findProgramAddressSync(
+      [LOYALTY_AUTHORITY_SEED, fixture.eventPda.toBuffer()],
+      program.programId
+    );
+    const claim = (pass: (typeof passes)[number]) => {
+      const [luckyMintPda] = PublicKey.findProgramAddressSync(
+        [LUCKY_MINT_SEED, pass.eventPassPda.toBuffer()],
+        program.programId
+      );
+      const ata = getAssociatedTokenAddressSync(luckyMintPda, pass.attendee.publicKey);
+      return program.methods
+        .claimLuckyReward()
+        .accounts({
+          owner: pass.attendee.publicKey,
+          event: fixture.eventPda,
+          eventPass: pass.eventPassPda,
+          loyaltyAuthority: loyaltyAuthorityPda,
+          luckyMint: luckyMintPda,
+          luckyTokenAccount: ata,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([pass.attendee])
+        .rpc()
+        .then(() => ata);
+    };
+
+    const loser = passes[(expectedIndex + 1) % 3];
+    try {
+      await claim(loser);
+      assert.fail("Expected a non-winner claim to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "NotLuckyWinner");
+    }
+    const ata = await claim(winner);
+    const tokenAccount = await getAccount(provider.connection, ata);
+    assert.equal(Number(tokenAccount.amount), 1);
+    const luckyMint = await getMint(provider.connection, tokenAccount.mint);
+    assert.isNull(luckyMint.mintAuthority);
+    assert.equal(Number(luckyMint.supply), 1);
+  });
+
+  it("sizes the verifier list per event and enforces its cap", async () => {
+    const addVerifier = (eventPda: PublicKey, verifier: PublicKey) =>
+      program.methods
+        .addVerifier(verifier)
+        .accounts({ organizer: organizer.publicKey, event: eventPda })
+        .rpc();
+
+    const small = await createEventFixture({ maxVerifiers: 2 });
+    const large = await createEventFixture({ maxVerifiers: 32 });
+    const smallInfo = await provider.connection.getAccountInfo(small.eventPda);
+    const largeInfo = await provider.connection.getAccountInfo(large.eventPda);
+    assert.equal(largeInfo!.data.length - smallInfo!.data.length, 30 * 32);
+
+    const gate = Keypair.generate().publicKey;
+    await addVerifier(small.eventPda, gate);
+    try {
+      await addVerifier(small.eventPda, gate);
+      assert.fail("Expected a duplicate verifier to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "VerifierAlreadyAuthorized");
+    }
+    await addVerifier(small.eventPda, Keypair.generate().publicKey);
+    try {
+      await addVerifier(small.eventPda, Keypair.generate().publicKey);
+      assert.fail("Expected adding past the per-event cap to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "TooManyVerifiers");
+    }
+    const eventAccount: any = await program.account.event.fetch(small.eventPda);
+    assert.equal(eventAccount.authorizedVerifiers.length, 2);
+    assert.equal(eventAccount.maxVerifiers, 2);
+
+    try {
+      await createEventFixture({
+        maxVerifiers: 1,
+        authorizedVerifiers: [gate, Keypair.generate().publicKey],
+        skipTreasury: true,
+      });
+      assert.fail("Expected more initial verifiers than slots to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "TooManyVerifiers");
+    }
+    try {
+      await createEventFixture({ maxVerifiers: 33, skipTreasury: true });
+      assert.fail("Expected a cap above the absolute maximum to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "TooManyVerifiers");
+    }
+  });
+
+  it("sweeps post-settlement dust and closes the treasury", async () => {
+    const settlement = Keypair.generate();
+    await fundWallet(settlement.publicKey);
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 4),
+      settlementTreasury: settlement.publicKey,
+    });
+    await mintPass(fixture, 1);
+    const sweep = () =>
+      program.methods
+        .withdrawDust()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          destination: settlement.publicKey,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    const sendToTreasury = async (lamports: number) => {
+      const tx = new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: organizer.publicKey,
+          toPubkey: fixture.vaultTreasuryPda,
+          lamports,
+        })
+      );
+      await provider.sendAndConfirm(tx);
+    };
+
+    try {
+      await sweep();
+      assert.fail("Expected a sweep before settlement to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "EventNotSettled");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 4500));
+    await program.methods
+      .withdrawTreasury()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        vaultState: fixture.vaultStatePda,
+        destination: settlement.publicKey,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .rpc();
+
+    const rentFloor = await provider.connection.getMinimumBalanceForRentExemption(0);
+    await sendToTreasury(rentFloor);
+    const vaultBefore: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+    const settlementBefore = await provider.connection.getBalance(settlement.publicKey);
+    await sweep();
+
+    assert.isNull(await provider.connection.getAccountInfo(fixture.vaultTreasuryPda));
+    const settlementAfter = await provider.connection.getBalance(settlement.publicKey);
+    assert.equal(settlementAfter - settlementBefore, rentFloor);
+    const vaultAfter: any = await program.account.vaultState.fetch(
+      fixture.vaultStatePda
+    );
+    assert.equal(
+      vaultAfter.totalWithdrawn.sub(vaultBefore.totalWithdrawn).toNumber(),
+      rentFloor
+    );
+
+    await sendToTreasury(rentFloor + 1_000);
+    try {
+      await sweep();
+      assert.fail("Expected a balance above the dust threshold to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "DustAboveThreshold");
+    }
+  });
+
+  it("refunds tier deposits on check-in and forfeits no-show deposits", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const deposit = LAMPORTS_PER_SOL / 20;
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 6),
+      tiers: [
+        {
+          tierId: 1,
+          label: "Cup deposit",
+          priceLamports: new BN(price),
+          maxSupply: 5,
+          depositLamports: new BN(deposit),
+        },
+      ],
+    });
+    const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    const attendee = await mintPass(fixture, 1);
+    const noShow = await mintPass(fixture, 1);
+    const treasuryAfterMints = await provider.connection.getBalance(
+      fixture.vaultTreasuryPda
+    );
+    assert.equal(treasuryAfterMints - treasuryBefore, 2 * (price + deposit));
+
+    let vault: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vault.totalDeposited.toNumber(), 2 * price);
+    assert.equal(vault.depositsHeld.toNumber(), 2 * deposit);
+
+    const reclaim = (pass: Awaited<ReturnType<typeof mintPass>>) =>
+      program.methods
+        .reclaimDeposit()
+        .accounts({
+          owner: pass.attendee.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          eventPass: pass.eventPassPda,
+        })
+        .signers([pass.attendee])
+        .rpc();
+
+    try {
+      await reclaim(attendee);
+      assert.fail("Expected reclaim before check-in to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "PassNotCheckedIn");
+    }
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: attendee.eventPassPda,
+      })
+      .rpc();
+    const walletBefore = await provider.connection.getBalance(attendee.attendee.publicKey);
+    await reclaim(attendee);
+    const walletAfter = await provider.connection.getBalance(attendee.attendee.publicKey);
+    // The attendee also paid the transaction fee.
+    assert.isAbove(walletAfter - walletBefore, deposit - 10_000);
+    const passAccount: any = await program.account.eventPass.fetch(attendee.eventPassPda);
+    assert.equal(passAccount.depositLamports.toNumber(), 0);
+
+    try {
+      await reclaim(attendee);
+      assert.fail("Expected a second reclaim to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "NoDepositToReclaim");
+    }
+
+    vault = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vault.depositsHeld.toNumber(), deposit);
+
+    await new Promise((resolve) => setTimeout(resolve, 6500));
+    const breakdown: any = await program.methods
+      .previewSettlement()
+      .accounts({
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .view();
+    assert.equal(breakdown.principal.toNumber(), 2 * price);
+    assert.equal(breakdown.forfeitedDeposits.toNumber(), deposit);
+
+    await program.methods
+      .withdrawTreasury()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        vaultState: fixture.vaultStatePda,
+        destination: organizer.publicKey,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .rpc();
+    try {
+      await reclaim(noShow);
+      assert.fail("Expected a no-show reclaim after settlement to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "AlreadySettled");
+    }
+  });
+
+  it("sells a pass peer-to-peer with the organizer royalty applied", async () => {
+    const price = LAMPORTS_PER_SOL / 5;
+    const fixture = await createEventFixture({
+      resaleRoyaltyBps: 1_000,
+      maxResaleLamports: new BN(price),
+    });
+    const seller = await mintPass(fixture, 1);
+    const original: any = await program.account.eventPass.fetch(seller.eventPassPda);
+    const buyer = Keypair.generate();
+    await fundWallet(buyer.publicKey);
+    const newPassPda = findEventPassPda(
+      fixture.eventPda,
+      buyer.publicKey,
+      original.passIndex
+    );
+    const sell = (amount: number, oldPass = seller.eventPassPda) =>
+      program.methods
+        .transferPassWithPayment(new BN(amount))
+        .accounts({
+          seller: seller.attendee.publicKey,
+          buyer: buyer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          oldPass,
+          guestList: fixture.guestListPda,
+          newPass: newPassPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller.attendee, buyer])
+        .rpc();
+
+    try {
+      await sell(price + 1);
+      assert.fail("Expected a resale above the cap to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "ResalePriceTooHigh");
+    }
+
+    const manageBan = (method: "banBuyer" | "unbanBuyer") =>
+      program.methods[method](buyer.publicKey)
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    await manageBan("banBuyer");
+    try {
+      await sell(price);
+      assert.fail("Expected a resale to a banned buyer to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "BuyerBanned");
+    }
+    await manageBan("unbanBuyer");
+
+    const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    const sellerBefore = await provider.connection.getBalance(seller.attendee.publicKey);
+    const passRent = await provider.connection.getBalance(seller.eventPassPda);
+    await sell(price);
+
+    const royalty = price / 10;
+    const treasuryAfter = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    assert.equal(treasuryAfter - treasuryBefore, royalty);
+    const sellerAfter = await provider.connection.getBalance(seller.attendee.publicKey);
+    // The seller also gets the old pass rent back and pays a share of the fee.
+    assert.isAbove(sellerAfter - sellerBefore, price - royalty + passRent - 20_000);
+
+    assert.isNull(await program.account.eventPass.fetchNullable(seller.eventPassPda));
+    const newPass: any = await program.account.eventPass.fetch(newPassPda);
+    assert.equal(newPass.owner.toBase58(), buyer.publicKey.toBase58());
+    assert.equal(newPass.passIndex.toString(), original.passIndex.toString());
+    assert.equal(newPass.pricePaid.toNumber(), original.pricePaid.toNumber());
+  });
+
+  it("sells a pass without royalty and refuses once it has been used", async () => {
+    const fixture = await createEventFixture();
+    const seller = await mintPass(fixture, 1);
+    const buyer = Keypair.generate();
+    await fundWallet(buyer.publicKey);
+    const sold: any = await program.account.eventPass.fetch(seller.eventPassPda);
+    const newPassPda = findEventPassPda(fixture.eventPda, buyer.publicKey, sold.passIndex);
+    const sell = () =>
+      program.methods
+        .transferPassWithPayment(new BN(LAMPORTS_PER_SOL / 4))
+        .accounts({
+          seller: seller.attendee.publicKey,
+          buyer: buyer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          oldPass: seller.eventPassPda,
+          guestList: fixture.guestListPda,
+          newPass: newPassPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller.attendee, buyer])
+        .rpc();
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: seller.eventPassPda,
+      })
+      .rpc();
+    try {
+      await sell();
+      assert.fail("Expected resale of a checked-in pass to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "AlreadyCheckedIn");
+    }
+
+    const unused = await mintPass(fixture, 1);
+    const unusedPass: any = await program.account.eventPass.fetch(unused.eventPassPda);
+    const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    const unusedBuyerPass = findEventPassPda(
+      fixture.eventPda,
+      buyer.publicKey,
+      unusedPass.passIndex
+    );
+    await program.methods
+      .transferPassWithPayment(new BN(LAMPORTS_PER_SOL / 4))
+      .accounts({
+        seller: unused.attendee.publicKey,
+        buyer: buyer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        oldPass: unused.eventPassPda,
+        guestList: fixture.guestListPda,
+        newPass: unusedBuyerPass,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([unused.attendee, buyer])
+      .rpc();
+    assert.equal(
+      await provider.connection.getBalance(fixture.vaultTreasuryPda),
+      treasuryBefore
+    );
+    const bought: any = await program.account.eventPass.fetch(unusedBuyerPass);
+    assert.equal(bought.owner.toBase58(), buyer.publicKey.toBase58());
+  });
+
+  it("enumerates every pass for an event from the guest list", async () => {
+    const fixture = await createEventFixture();
+    const minted = [];
+    for (let i = 0; i < 4; i += 1) {
+      minted.push(await mintPass(fixture, 1));
+    }
+
+    const newOwner = Keypair.generate();
+    const reissuedPda = findEventPassPda(fixture.eventPda, newOwner.publicKey, new BN(1));
+    await program.methods
+      .reissuePass(newOwner.publicKey)
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        oldPass: minted[1].eventPassPda,
+        guestList: fixture.guestListPda,
+        newPass: reissuedPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // Only the event address is needed: derive the guest list, then read
+    // entries in pass_index order.
+    const [guestListPda] = PublicKey.findProgramAddressSync(
+      [GUEST_LIST_SEED, fixture.eventPda.toBuffer()],
+      program.programId
+    );
+    const guestList: any = await program.account.guestList.fetch(guestListPda);
+    const expected = [
+      minted[0].eventPassPda,
+      reissuedPda,
+      minted[2].eventPassPda,
+      minted[3].eventPassPda,
+    ];
+    assert.deepEqual(
+      guestList.passes.map((pass: PublicKey) => pass.toBase58()),
+      expected.map((pass) => pass.toBase58())
+    );
+    const passes: any[] = await program.account.eventPass.fetchMultiple(guestList.passes);
+    passes.forEach((pass, index) => assert.equal(pass.passIndex.toNumber(), index));
+
+    // Page through large lists with a data slice instead of the whole account.
+    const entriesOffset = 8 + 1 + 32 + 4;
+    const page = await provider.connection.getAccountInfo(guestListPda, {
+      dataSlice: { offset: entriesOffset + 2 * 32, length: 2 * 32 },
+    });
+    assert.equal(
+      new PublicKey(page!.data.subarray(0, 32)).toBase58(),
+      minted[2].eventPassPda.toBase58()
+    );
+    assert.equal(
+      new PublicKey(page!.data.subarray(32, 64)).toBase58(),
+      minted[3].eventPassPda.toBase58()
+    );
+  });
+
+  it("refuses treasury withdrawals routed through another program", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const schedule = { startTs: new BN(now - 10), endTs: new BN(now + 4) };
+    const guarded = await createEventFixture(schedule);
+    const composable = await createEventFixture({ ...schedule, allowCpi: true });
+    await mintPass(guarded, 1);
+    await mintPass(composable, 1);
+    await new Promise((resolve) => setTimeout(resolve, 4500));
+
+    const withdrawal = (fixture: Awaited<ReturnType<typeof createEventFixture>>) =>
+      program.methods.withdrawTreasury().accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        vaultState: fixture.vaultStatePda,
+        destination: organizer.publicKey,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      });
+    const relay = async (fixture: Awaited<ReturnType<typeof createEventFixture>>) => {
+      const ix = await withdrawal(fixture).instruction();
+      return authStub.methods
+        .relay(ix.data)
+        .accounts({ targetProgram: program.programId })
+        .remainingAccounts(ix.keys)
+        .rpc();
+    };
+
+    try {
+      await relay(guarded);
+      assert.fail("Expected a CPI withdrawal to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "CpiNotAllowed");
+    }
+    await withdrawal(guarded).rpc();
+    const guardedEvent: any = await program.account.event.fetch(guarded.eventPda);
+    assert.isTrue(guardedEvent.settled);
+
+    await relay(composable);
+    const composableEvent: any = await program.account.event.fetch(composable.eventPda);
+    assert.isTrue(composableEvent.settled);
+  });
+
+  it("only harvests through the adapter recorded on the vault", async () => {
+    const fixture = await createEventFixture({ yieldStrategy: { kamino: {} } });
+    await mintPass(fixture, 1);
+    await topUpAdapterReserve(fixture, LAMPORTS_PER_SOL / 10);
+    const vault: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vault.adapterProgram.toBase58(), vaultStub.programId.toBase58());
+
+    const harvest = (adapterReserve: PublicKey, vaultAdapterProgram: PublicKey) =>
+      program.methods
+        .harvestYield(new BN(1_000))
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          adapterReserve,
+          vaultAdapterProgram,
+        })
+        .rpc();
+
+    const adapterReserve = adapterReserveFor(fixture.vaultStatePda);
+    try {
+      await harvest(adapterReserve, authStub.programId);
+      assert.fail("Expected a different adapter program to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "AdapterMismatch");
+    }
+
+    // Another event's reserve can't be harvested into this vault.
+    const other = await createEventFixture({ yieldStrategy: { kamino: {} } });
+    await topUpAdapterReserve(other, LAMPORTS_PER_SOL / 10);
+    try {
+      await harvest(adapterReserveFor(other.vaultStatePda), vaultStub.programId);
+      assert.fail("Expected another event's reserve to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "ConstraintSeeds");
+    }
+
+    await harvest(adapterReserve, vaultStub.programId);
+    const harvested: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(harvested.totalYieldHarvested.toNumber(), 1_000);
+  });
+
+  it("lets a custodian hold and present a pass until the owner claims it", async () => {
+    const fixture = await createEventFixture();
+    const custodian = Keypair.generate();
+    await fundWallet(custodian.publicKey);
+    const mintInCustody = async (beneficiary: PublicKey) => {
+      const eventPassPda = findEventPassPda(
+        fixture.eventPda,
+        beneficiary,
+        await nextPassIndex(fixture.eventPda)
+      );
+      await program.methods
+        .mintPassInCustody(1, new BN(0), beneficiary)
+        .accounts({
+          custodian: custodian.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          organizerStats: organizerStatsPda,
+          guestList: fixture.guestListPda,
+          eventPass: eventPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, beneficiary),
+          protocolConfig: protocolConfigPda,
+          feeCollector: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([custodian])
+        .rpc();
+      return eventPassPda;
+    };
+    const claimCustody = (owner: Keypair, eventPass: PublicKey) =>
+      program.methods
+        .claimCustody()
+        .accounts({ owner: owner.publicKey, eventPass })
+        .signers([owner])
+        .rpc();
+
+    const scanned = Keypair.generate();
+    const scannedPass = await mintInCustody(scanned.publicKey);
+    const passAccount: any = await program.account.eventPass.fetch(scannedPass);
+    assert.equal(passAccount.owner.toBase58(), scanned.publicKey.toBase58());
+    assert.equal(passAccount.custodian.toBase58(), custodian.publicKey.toBase58());
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: custodian.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: scannedPass,
+      })
+      .signers([custodian])
+      .rpc();
+    assert.isTrue((await program.account.eventPass.fetch(scannedPass) as any).checkedIn);
+
+    const claimant = Keypair.generate();
+    await fundWallet(claimant.publicKey);
+    const claimedPass = await mintInCustody(claimant.publicKey);
+
+    // The custodian can't sell the pass out from under its owner.
+    const buyer = Keypair.generate();
+    await fundWallet(buyer.publicKey);
+    try {
+      await program.methods
+        .transferPassWithPayment(new BN(0))
+        .accounts({
+          seller: custodian.publicKey,
+          buyer: buyer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          oldPass: claimedPass,
+          guestList: fixture.guestListPda,
+          newPass: findEventPassPda(fixture.eventPda, buyer.publicKey, new BN(1)),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([custodian, buyer])
+        .rpc();
+      assert.fail("Expected a custodian transfer to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "ConstraintRaw");
+    }
+
+    await claimCustody(claimant, claimedPass);
+    const claimed: any = await program.account.eventPass.fetch(claimedPass);
+    assert.isNull(claimed.custodian);
+    const status = await checkInDryRun(fixture, claimedPass, custodian);
+    assert.deepEqual(status.denial, { unauthorizedVerifier: {} });
+
+    try {
+      await claimCustody(claimant, claimedPass);
+      assert.fail("Expected a second claim to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "NotInCustody");
+    }
+  });
+
+  it("copies tier benefit flags onto minted passes", async () => {
+    const BENEFIT_BACKSTAGE = 1 << 0;
+    const BENEFIT_MERCH = 1 << 1;
+    const BENEFIT_PARKING = 1 << 2;
+    const tier = (tierId: number, benefits?: number) => ({
+      tierId,
+      label: `Tier ${tierId}`,
+      priceLamports: new BN(LAMPORTS_PER_SOL / 10),
+      maxSupply: 5,
+      benefits: benefits ?? null,
+    });
+    const fixture = await createEventFixture({
+      tiers: [
+        tier(1, BENEFIT_BACKSTAGE | BENEFIT_MERCH),
+        tier(2, BENEFIT_PARKING),
+        tier(3),
+      ],
+    });
+
+    const expected: Record<number, number> = {
+      1: BENEFIT_BACKSTAGE | BENEFIT_MERCH,
+      2: BENEFIT_PARKING,
+      3: 0,
+    };
+    for (const [tierId, benefits] of Object.entries(expected)) {
+      const { eventPassPda } = await mintPass(fixture, Number(tierId));
+      const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+      assert.equal(passAccount.benefits, benefits);
+    }
+  });
+
+  it("rejects every config change once the event is frozen", async () => {
+    const staged = await createEventFixture({ tiers: [], tiersFinalized: false });
+    try {
+      await program.methods
+        .freezeEventConfig()
+        .accounts({ organizer: organizer.publicKey, event: staged.eventPda })
+        .rpc();
+      assert.fail("Expected freezing an unfinished tier list to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "TiersNotFinalized");
+    }
+
+    const fixture = await createEventFixture({
+      tiers: [
+        {
+          tierId: 1,
+          label: "GA",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 10),
+          maxSupply: 5,
+        },
+        {
+          tierId: 2,
+          label: "Unsold",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 5),
+          maxSupply: 5,
+        },
+      ],
+    });
+    const accounts = { organizer: organizer.publicKey, event: fixture.eventPda };
+    const withSystem = { ...accounts, systemProgram: SystemProgram.programId };
+    await program.methods.freezeEventConfig().accounts(accounts).rpc();
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.isTrue(eventAccount.configFrozen);
+
+    const mutators: [string, () => Promise<string>][] = [
+      [
+        "updateEventMetadata",
+        () =>
+          program.methods
+            .updateEventMetadata("Renamed", "Elsewhere", Array(32).fill(1))
+            .accounts(withSystem)
+            .rpc(),
+      ],
+      [
+        "extendEvent",
+        () =>
+          program.methods
+            .extendEvent(eventAccount.endTs.add(new BN(3600)))
+            .accounts(accounts)
+            .rpc(),
+      ],
+      [
+        "increaseTierSupply",
+        () => program.methods.increaseTierSupply(1, 5).accounts(accounts).rpc(),
+      ],
+      ["closeEmptyTier", () => program.methods.closeEmptyTier(2).accounts(withSystem).rpc()],
+      [
+        "addTier",
+        () =>
+          program.methods
+            .addTier({
+              tierId: 3,
+              label: "Late",
+              priceLamports: new BN(LAMPORTS_PER_SOL / 10),
+              maxSupply: 5,
+            } as any)
+            .accounts(withSystem)
+            .rpc(),
+      ],
+      ["freezeEventConfig", () => program.methods.freezeEventConfig().accounts(accounts).rpc()],
+    ];
+    for (const [name, call] of mutators) {
+      try {
+        await call();
+        assert.fail(`Expected ${name} to fail on a frozen event`);
+      } catch (err: any) {
+        expectAnchorError(err, "ConfigFrozen");
+      }
+    }
+
+    // Sales and check-in are unaffected.
+    const { eventPassPda } = await mintPass(fixture, 1);
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: eventPassPda,
+      })
+      .rpc();
+  });
+
+  it("rejects oversized create args before allocating the event", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const longTier = (tierId: number) => ({
+      tierId,
+      label: "L".repeat(40),
+      priceLamports: new BN(LAMPORTS_PER_SOL / 10),
+      maxSupply: 5,
+    });
+    // Each set is also mis-scheduled; the bounds error must win.
+    const cases: [Record<string, any>, string][] = [
+      [{ name: "N".repeat(200) }, "MetadataTooLong"],
+      [{ tiers: [0, 1, 2, 3, 4].map(longTier) }, "TooManyTiers"],
+      [{ tiers: [longTier(0)] }, "TierLabelTooLong"],
+      [{ maxVerifiers: 33 }, "TooManyVerifiers"],
+    ];
+
+    for (const [overrides, code] of cases) {
+      const eventId = randomEventId();
+      try {
+        await createEventFixture({
+          ...overrides,
+          eventId,
+          startTs: new BN(now + 3600),
+          endTs: new BN(now),
+        });
+        assert.fail(`Expected ${code}`);
+      } catch (err: any) {
+        expectAnchorError(err, code);
+      }
+      const { eventPda } = findEventPdas(organizer.publicKey, eventId);
+      assert.isNull(await provider.connection.getAccountInfo(eventPda));
+    }
+  });
+
+  it("sets and resizes the listing image URIs", async () => {
+    const fixture = await createEventFixture({
+      imageUri: "https://cdn.example/summit.png",
+    });
+    const accounts = {
+      organizer: organizer.publicKey,
+      event: fixture.eventPda,
+      systemProgram: SystemProgram.programId,
+    };
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.imageUri, "https://cdn.example/summit.png");
+    assert.isNull(eventAccount.thumbnailUri);
+    const initialSize = (await provider.connection.getAccountInfo(fixture.eventPda))!.data
+      .length;
+
+    const image = "https://cdn.example/summit-hero-" + "x".repeat(80) + ".png";
+    const thumbnail = "https://cdn.example/summit-thumb.png";
+    await program.methods.setEventImages(image, thumbnail).accounts(accounts).rpc();
+
+    const summary: any = await program.methods
+      .getEventSummary()
+      .accounts({
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .view();
+    assert.equal(summary.imageUri, image);
+    assert.equal(summary.thumbnailUri, thumbnail);
+    const grownSize = (await provider.connection.getAccountInfo(fixture.eventPda))!.data.length;
+    assert.equal(
+      grownSize - initialSize,
+      image.length - "https://cdn.example/summit.png".length + 4 + thumbnail.length
+    );
+
+    await program.methods.setEventImages(null, null).accounts(accounts).rpc();
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.isNull(eventAccount.imageUri);
+    assert.isNull(eventAccount.thumbnailUri);
+    const clearedSize = (await provider.connection.getAccountInfo(fixture.eventPda))!.data
+      .length;
+    assert.equal(initialSize - clearedSize, 4 + "https://cdn.example/summit.png".length);
+
+    try {
+      await program.methods
+        .setEventImages(null, "t".repeat(129))
+        .accounts(accounts)
+        .rpc();
+      assert.fail("Expected an oversized thumbnail to be rejected");
+    } catch (err: any) {
+      expectAnchorError(err, "MetadataTooLong");
+    }
+
+    await program.methods
+      .freezeEventConfig()
+      .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+      .rpc();
+    try {
+      await program.methods.setEventImages(image, null).accounts(accounts).rpc();
+      assert.fail("Expected images to be locked after freezing");
+    } catch (err: any) {
+      expectAnchorError(err, "ConfigFrozen");
+    }
+  });
+
+  it("sells and admits a walk-up at the door only while the event is live", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const operator = Keypair.generate();
+    await fundWallet(operator.publicKey);
+    const doorSale = async (
+      fixture: Awaited<ReturnType<typeof createEventFixture>>,
+      seller: Keypair = operator
+    ) => {
+      const attendee = Keypair.generate();
+      await fundWallet(attendee.publicKey);
+      const eventPassPda = findEventPassPda(
+        fixture.eventPda,
+        attendee.publicKey,
+        await nextPassIndex(fixture.eventPda)
+      );
+      await program.methods
+        .payAndCheckin(1)
+        .accounts({
+          operator: seller.publicKey,
+          payer: attendee.publicKey,
+          attendee: attendee.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          organizerStats: organizerStatsPda,
+          guestList: fixture.guestListPda,
+          eventPass: eventPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollector: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller, attendee])
+        .rpc();
+      return eventPassPda;
+    };
+
+    const live = await createEventFixture({ authorizedVerifiers: [operator.publicKey] });
+    const treasuryBefore = await provider.connection.getBalance(live.vaultTreasuryPda);
+    const eventPassPda = await doorSale(live);
+    const eventPass: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isTrue(eventPass.checkedIn);
+    assert.isNotNull(eventPass.checkedInAt);
+    assert.equal(
+      (await provider.connection.getBalance(live.vaultTreasuryPda)) - treasuryBefore,
+      LAMPORTS_PER_SOL / 10
+    );
+    const guestList: any = await program.account.guestList.fetch(live.guestListPda);
+    assert.equal(guestList.passes[0].toBase58(), eventPassPda.toBase58());
+
+    try {
+      await doorSale(live, Keypair.generate());
+      assert.fail("Expected an unlisted operator to be refused");
+    } catch (err: any) {
+      expectAnchorError(err, "UnauthorizedVerifier");
+    }
+
+    // Events can't be created already over, so the ended case waits out a
+    // short one.
+    for (const [schedule, code, waitMs] of [
+      [{ startTs: new BN(now + 3600), endTs: new BN(now + 7200) }, "EventNotStarted", 0],
+      [{ startTs: new BN(now - 7200), endTs: new BN(now + 2) }, "EventEnded", 3000],
+    ] as [Record<string, any>, string, number][]) {
+      const fixture = await createEventFixture({
+        ...schedule,
+        authorizedVerifiers: [operator.publicKey],
+      });
+      await new Promise((resolve) => setTimeout(resolve, waitMs));
+      try {
+        await doorSale(fixture);
+        assert.fail(`Expected ${code}`);
+      } catch (err: any) {
+        expectAnchorError(err, code);
+      }
+    }
+  });
+
+  it("revokes every verifier at once", async () => {
+    const verifiers = [Keypair.generate(), Keypair.generate()];
+    const fixture = await createEventFixture({
+      authorizedVerifiers: verifiers.map((v) => v.publicKey),
+    });
+    const extra = Keypair.generate();
+    await program.methods
+      .addVerifier(extra.publicKey)
+      .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+      .rpc();
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.authorizedVerifiers.length, 3);
+    const sizeBefore = (await provider.connection.getAccountInfo(fixture.eventPda))!.data.length;
+
+    await program.methods
+      .revokeVerifierAll()
+      .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+      .rpc();
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.lengthOf(eventAccount.authorizedVerifiers, 0);
+    assert.equal(
+      (await provider.connection.getAccountInfo(fixture.eventPda))!.data.length,
+      sizeBefore
+    );
+
+    const { eventPassPda } = await mintPass(fixture, 1);
+    const former = verifiers[0];
+    await fundWallet(former.publicKey);
+    try {
+      await program.methods
+        .checkIn()
+        .accounts({
+          verifier: former.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: eventPassPda,
+        })
+        .signers([former])
+        .rpc();
+      assert.fail("Expected a revoked verifier to be refused");
+    } catch (err: any) {
+      expectAnchorError(err, "UnauthorizedVerifier");
+    }
+  });
+
+  it("estimates the exact rent create_event and mint_pass allocate", async () => {
+    const args = buildEventArgs({
+      maxVerifiers: 7,
+      imageUri: "https://cdn.example/estimate.png",
+    });
+    const estimate: any = await program.methods.estimateRent(args).view();
+    const balance = (pubkey: PublicKey) => provider.connection.getBalance(pubkey);
+
+    const fixture = await createEventFixture(args);
+    assert.equal(await balance(fixture.eventPda), estimate.event.toNumber());
+    assert.equal(await balance(fixture.vaultStatePda), estimate.vaultState.toNumber());
+    assert.equal(await balance(fixture.guestListPda), estimate.guestList.toNumber());
+    assert.equal(await balance(fixture.vaultTreasuryPda), estimate.vaultTreasury.toNumber());
+    assert.equal(await balance(organizerStatsPda), estimate.organizerStats.toNumber());
+
+    const { eventPassPda } = await mintPass(fixture, 1);
+    assert.equal(await balance(eventPassPda), estimate.eventPass.toNumber());
+    assert.equal(
+      await balance(fixture.guestListPda),
+      estimate.guestList.add(estimate.guestListEntry).toNumber()
+    );
+  });
+
+  it("holds settlement while a dispute is open until the admin resolves it", async () => {
+    await ensureProtocolConfig();
+
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 2),
+    });
+    const { attendee, eventPassPda } = await mintPass(fixture, 1);
+    const raise = () =>
+      program.methods
+        .raiseDispute()
+        .accounts({
+          holder: attendee.publicKey,
+          event: fixture.eventPda,
+          protocolConfig: protocolConfigPda,
+          eventPass: eventPassPda,
+        })
+        .signers([attendee])
+        .rpc();
+    await raise();
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.isTrue(eventAccount.disputed);
+
+    await new Promise((resolve) => setTimeout(resolve, 2500));
+    const withdraw = () =>
+      program.methods
+        .withdrawTreasury()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          vaultState: fixture.vaultStatePda,
+          destination: organizer.publicKey,
+          vaultTreasury: fixture.vaultTreasuryPda,
+        })
+        .rpc();
+    try {
+      await withdraw();
+      assert.fail("Expected settlement to be held during the dispute");
+    } catch (err: any) {
+      expectAnchorError(err, "EventDisputed");
+    }
+
+    const outsider = Keypair.generate();
+    await fundWallet(outsider.publicKey);
+    const resolve = (admin: Keypair, refund = false) =>
+      program.methods
+        .resolveDispute(refund)
+        .accounts({
+          admin: admin.publicKey,
+          protocolConfig: protocolConfigPda,
+          event: fixture.eventPda,
+        })
+        .signers([admin])
+        .rpc();
+    try {
+      await resolve(outsider);
+      assert.fail("Expected only the protocol admin to resolve disputes");
+    } catch (err: any) {
+      expectAnchorError(err, "NotProtocolAdmin");
+    }
+    await resolve(organizer.payer);
+
+    try {
+      await raise();
+      assert.fail("Expected a pass to raise only one dispute");
+    } catch (err: any) {
+      expectAnchorError(err, "DisputeAlreadyRaised");
+    }
+
+    await withdraw();
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.isFalse(eventAccount.disputed);
+    assert.isTrue(eventAccount.settled);
+  });
+
+  it("cancels a disputed event when the admin rules for the buyers", async () => {
+    await ensureProtocolConfig();
+    const price = LAMPORTS_PER_SOL / 10;
+    const fixture = await createEventFixture();
+    const pass = await mintPass(fixture, 1);
+    await program.methods
+      .raiseDispute()
+      .accounts({
+        holder: pass.attendee.publicKey,
+        event: fixture.eventPda,
+        protocolConfig: protocolConfigPda,
+        eventPass: pass.eventPassPda,
+      })
+      .signers([pass.attendee])
+      .rpc();
+
+    await program.methods
+      .resolveDispute(true)
+      .accounts({
+        admin: organizer.publicKey,
+        protocolConfig: protocolConfigPda,
+        event: fixture.eventPda,
+      })
+      .rpc();
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.isFalse(eventAccount.disputed);
+    assert.isTrue(eventAccount.cancelled);
+
+    const passAccount: any = await program.account.eventPass.fetch(pass.eventPassPda);
+    const walletBefore = await provider.connection.getBalance(pass.attendee.publicKey);
+    await refundPass(fixture, pass);
+    assert.isAtLeast(
+      (await provider.connection.getBalance(pass.attendee.publicKey)) - walletBefore,
+      price - passAccount.feePaid.toNumber()
+    );
+  });
+
+  it("uploads offline scans with the time they actually happened", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const verifier = Keypair.generate();
+    await fundWallet(verifier.publicKey);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 600),
+      authorizedVerifiers: [verifier.publicKey],
+    });
+    const { eventPassPda } = await mintPass(fixture, 1);
+    const upload = (ts: number) =>
+      program.methods
+        .checkInWithTime(new BN(ts))
+        .accounts({
+          verifier: verifier.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: eventPassPda,
+        })
+        .signers([verifier])
+        .rpc();
+
+    for (const [ts, code] of [
+      [now + 600, "CheckinInFuture"],
+      [now - 900, "EventNotStarted"],
+    ] as [number, string][]) {
+      try {
+        await upload(ts);
+        assert.fail(`Expected ${code}`);
+      } catch (err: any) {
+        expectAnchorError(err, code);
+      }
+    }
+
+    await upload(now - 300);
+    const eventPass: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isTrue(eventPass.checkedIn);
+    assert.equal(eventPass.checkedInAt.toNumber(), now - 300);
+    assert.equal(eventPass.lastEntryAt.toNumber(), now - 300);
+  });
+
+  it("rotates the adapter reserve authority in two steps", async () => {
+    const operator = Keypair.generate();
+    const [adapterReservePda] = PublicKey.findProgramAddressSync(
+      [ADAPTER_RESERVE_SEED, operator.publicKey.toBuffer()],
+      vaultStub.programId
+    );
+    await vaultStub.methods
+      .initialize()
+      .accounts({
+        adapter: adapterReservePda,
+        payer: organizer.publicKey,
+        authority: operator.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([operator])
+      .rpc();
+    const transfer = (authority: Keypair, newAuthority: PublicKey) =>
+      vaultStub.methods
+        .transferReserveAuthority(newAuthority)
+        .accounts({ authority: authority.publicKey, adapter: adapterReservePda })
+        .signers([authority])
+        .rpc();
+    const accept = (newAuthority: Keypair) =>
+      vaultStub.methods
+        .acceptReserveAuthority()
+        .accounts({ newAuthority: newAuthority.publicKey, adapter: adapterReservePda })
+        .signers([newAuthority])
+        .rpc();
+
+    const intruder = Keypair.generate();
+    await fundWallet(intruder.publicKey);
+    try {
+      await transfer(intruder, intruder.publicKey);
+      assert.fail("Expected a non-authority rotation to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "Unauthorized");
+    }
+    try {
+      await vaultStub.methods
+        .harvest(new BN(1))
+        .accounts({
+          authority: intruder.publicKey,
+          adapter: adapterReservePda,
+          destination: intruder.publicKey,
+        })
+        .signers([intruder])
+        .rpc();
+      assert.fail("Expected a non-authority harvest to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "Unauthorized");
+    }
+
+    const successor = Keypair.generate();
+    await fundWallet(successor.publicKey);
+    await transfer(operator, successor.publicKey);
+    let reserve: any = await vaultStub.account.adapterReserve.fetch(adapterReservePda);
+    assert.equal(reserve.authority.toBase58(), operator.publicKey.toBase58());
+    assert.equal(reserve.pendingAuthority.toBase58(), successor.publicKey.toBase58());
+
+    try {
+      await accept(intruder);
+      assert.fail("Expected only the proposed authority to accept");
+    } catch (err: any) {
+      expectAnchorError(err, "Unauthorized");
+    }
+    await accept(successor);
+    reserve = await vaultStub.account.adapterReserve.fetch(adapterReservePda);
+    assert.equal(reserve.authority.toBase58(), successor.publicKey.toBase58());
+    assert.isNull(reserve.pendingAuthority);
+    assert.equal(reserve.vault.toBase58(), operator.publicKey.toBase58());
+
+    // Funding and write-offs follow the authority, not the original opener.
+    const fund = (authority: Keypair) =>
+      vaultStub.methods
+        .fundReserve(new BN(1_000))
+        .accounts({
+          authority: authority.publicKey,
+          funder: organizer.publicKey,
+          adapter: adapterReservePda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    try {
+      await fund(operator);
+      assert.fail("Expected the previous authority to be refused");
+    } catch (err: any) {
+      expectAnchorError(err, "Unauthorized");
+    }
+    await fund(successor);
+    try {
+      await vaultStub.methods
+        .simulateLoss(new BN(1_000))
+        .accounts({
+          authority: intruder.publicKey,
+          adapter: adapterReservePda,
+          sink: intruder.publicKey,
+        })
+        .signers([intruder])
+        .rpc();
+      assert.fail("Expected a non-authority write-off to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "Unauthorized");
+    }
+  });
+
+  it("sweeps no-show deposits to the forfeiture split after the event", async () => {
+    const deposit = LAMPORTS_PER_SOL / 20;
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 4),
+      tiers: [
+        {
+          tierId: 1,
+          label: "Cup deposit",
+          priceLamports: new BN(LAMPORTS_PER_SOL / 10),
+          maxSupply: 5,
+          depositLamports: new BN(deposit),
+        },
+      ],
+    });
+    const charity = Keypair.generate().publicKey;
+    const retained = Keypair.generate().publicKey;
+    const setSplit = (split: { recipient: PublicKey; bps: number }[]) =>
+      program.methods
+        .setForfeitureSplit(split)
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    try {
+      await setSplit([
+        { recipient: charity, bps: 6_000 },
+        { recipient: retained, bps: 3_000 },
+      ]);
+      assert.fail("Expected a split that doesn't total 10_000 bps to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidForfeitureSplit");
+    }
+    await setSplit([
+      { recipient: charity, bps: 7_000 },
+      { recipient: retained, bps: 3_000 },
+    ]);
+
+    const attendee = await mintPass(fixture, 1);
+    const noShow = await mintPass(fixture, 1);
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: attendee.eventPassPda,
+      })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 4500));
+
+    const sweep = (passes: PublicKey[]) =>
+      program.methods
+        .sweepForfeitures()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          organizerStats: organizerStatsPda,
+          guestList: fixture.guestListPda,
+        })
+        .remainingAccounts([
+          { pubkey: charity, isSigner: false, isWritable: true },
+          { pubkey: retained, isSigner: false, isWritable: true },
+          ...passes.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })),
+        ])
+        .rpc();
+
+    try {
+      await sweep([attendee.eventPassPda]);
+      assert.fail("Expected a checked-in pass's deposit to stay reclaimable");
+    } catch (err: any) {
+      expectAnchorError(err, "AlreadyCheckedIn");
+    }
+
+    await sweep([noShow.eventPassPda]);
+    assert.equal(await provider.connection.getBalance(charity), (deposit * 7) / 10);
+    assert.equal(await provider.connection.getBalance(retained), (deposit * 3) / 10);
+    const noShowPass: any = await program.account.eventPass.fetch(noShow.eventPassPda);
+    assert.equal(noShowPass.depositLamports.toNumber(), 0);
+    const vault: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vault.depositsHeld.toNumber(), deposit);
+  });
+
+  const refundPass = (
+    fixture: Awaited<ReturnType<typeof createEventFixture>>,
+    pass: Awaited<ReturnType<typeof mintPass>>
+  ) =>
+    program.methods
+      .refundPass()
+      .accounts({
+        owner: pass.attendee.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        organizerStats: organizerStatsPda,
+        guestList: fixture.guestListPda,
+        eventPass: pass.eventPassPda,
+      })
+      .signers([pass.attendee])
+      .rpc();
+
+  it("only refunds passes for cancelled events", async () => {
+    const fixture = await createEventFixture();
+    const pass = await mintPass(fixture, 1);
+    try {
+      await refundPass(fixture, pass);
+      assert.fail("Expected refunds to require a cancelled event");
+    } catch (err: any) {
+      expectAnchorError(err, "EventNotCancelled");
+    }
+    assert.isNotNull(await provider.connection.getAccountInfo(pass.eventPassPda));
+  });
+
+  it("sweeps refunds left unclaimed past a cancelled event's claim window", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const deposit = LAMPORTS_PER_SOL / 20;
+    const fixture = await createEventFixture({
+      tiers: [
+        {
+          tierId: 1,
+          label: "Cup deposit",
+          priceLamports: new BN(price),
+          maxSupply: 5,
+          depositLamports: new BN(deposit),
+        },
+      ],
+    });
+    const charity = Keypair.generate().publicKey;
+    await program.methods
+      .setForfeitureSplit([{ recipient: charity, bps: 10_000 }])
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .setRefundClaimWindow(new BN(3))
+      .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+      .rpc();
+    const claimed = await mintPass(fixture, 1);
+    const stale = await mintPass(fixture, 1);
+    await program.methods
+      .cancelEvent()
+      .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+      .rpc();
+
+    const sweep = () =>
+      program.methods
+        .sweepForfeitures()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          organizerStats: organizerStatsPda,
+          guestList: fixture.guestListPda,
+        })
+        .remainingAccounts([
+          { pubkey: charity, isSigner: false, isWritable: true },
+          { pubkey: stale.eventPassPda, isSigner: false, isWritable: true },
+          { pubkey: stale.attendee.publicKey, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+    try {
+      await sweep();
+      assert.fail("Expected refunds to stay claimable during the window");
+    } catch (err: any) {
+      expectAnchorError(err, "RefundWindowOpen");
+    }
+    await refundPass(fixture, claimed);
+
+    await new Promise((resolve) => setTimeout(resolve, 3500));
+    try {
+      await refundPass(fixture, stale);
+      assert.fail("Expected the claim window to have closed");
+    } catch (err: any) {
+      expectAnchorError(err, "RefundWindowClosed");
+    }
+
+    const stalePass: any = await program.account.eventPass.fetch(stale.eventPassPda);
+    const owed = price - stalePass.feePaid.toNumber() + deposit;
+    await sweep();
+    assert.equal(await provider.connection.getBalance(charity), owed);
+    assert.isNull(await provider.connection.getAccountInfo(stale.eventPassPda));
+    const vault: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vault.depositsHeld.toNumber(), 0);
+    assert.equal(vault.totalDeposited.toNumber(), 0);
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.totalPasses.toNumber(), 0);
+  });
+
+  it("cancels an event, blocks mints and check-ins, and refunds holders", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const fixture = await createEventFixture();
+    const pass = await mintPass(fixture, 1);
+    const cancel = (signer: Keypair) =>
+      program.methods
+        .cancelEvent()
+        .accounts({ organizer: signer.publicKey, event: fixture.eventPda })
+        .signers([signer])
+        .rpc();
+
+    const stranger = Keypair.generate();
+    await fundWallet(stranger.publicKey);
+    try {
+      await cancel(stranger);
+      assert.fail("Expected only the organizer to cancel");
+    } catch (err: any) {
+      expectAnchorError(err, "ConstraintRaw");
+    }
+    await cancel(organizer.payer);
+
+    const summary: any = await program.methods
+      .getEventSummary()
+      .accounts({
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .view();
+    assert.deepEqual(summary.status, { cancelled: {} });
+
+    try {
+      await mintPass(fixture, 1);
+      assert.fail("Expected mints to be refused after cancellation");
+    } catch (err: any) {
+      expectAnchorError(err, "EventCancelled");
+    }
+    try {
+      await program.methods
+        .checkIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: pass.eventPassPda,
+        })
+        .rpc();
+      assert.fail("Expected check-ins to be refused after cancellation");
+    } catch (err: any) {
+      expectAnchorError(err, "EventCancelled");
+    }
+
+    const walletBefore = await provider.connection.getBalance(pass.attendee.publicKey);
+    await refundPass(fixture, pass);
+    const walletAfter = await provider.connection.getBalance(pass.attendee.publicKey);
+    // Price plus the closed pass's rent, minus the transaction fee.
+    assert.isAbove(walletAfter - walletBefore, price);
+    assert.isNull(await provider.connection.getAccountInfo(pass.eventPassPda));
+
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.totalPasses.toNumber(), 0);
+    assert.equal(eventAccount.tiers[0].sold, 0);
+    const vault: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vault.totalDeposited.toNumber(), 0);
+    const guestList: any = await program.account.guestList.fetch(fixture.guestListPda);
+    assert.equal(guestList.passes[0].toBase58(), PublicKey.default.toBase58());
+
+    try {
+      await refundPass(fixture, pass);
+      assert.fail("Expected a closed pass to be unrefundable");
+    } catch (err: any) {
+      expectAnchorError(err, "AccountNotInitialized");
+    }
+  });
+
+  it("refuses to settle a cancelled event so refunds stay funded", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 2),
+    });
+    const pass = await mintPass(fixture, 1);
+    await program.methods
+      .cancelEvent()
+      .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 2500));
+
+    try {
+      await program.methods
+        .withdrawTreasury()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          vaultState: fixture.vaultStatePda,
+          destination: organizer.publicKey,
+          vaultTreasury: fixture.vaultTreasuryPda,
+        })
+        .rpc();
+      assert.fail("Expected a cancelled event to stay unsettled");
+    } catch (err: any) {
+      expectAnchorError(err, "EventCancelled");
+    }
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.isFalse(eventAccount.settled);
+
+    const passAccount: any = await program.account.eventPass.fetch(pass.eventPassPda);
+    const walletBefore = await provider.connection.getBalance(pass.attendee.publicKey);
+    await refundPass(fixture, pass);
+    assert.isAtLeast(
+      (await provider.connection.getBalance(pass.attendee.publicKey)) - walletBefore,
+      price - passAccount.feePaid.toNumber()
+    );
+  });
+
+  it("refunds only refundable tiers before the deadline but every tier on cancellation", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const price = LAMPORTS_PER_SOL / 10;
+    const fixture = await createEventFixture({
+      startTs: new BN(now + 3600),
+      endTs: new BN(now + 7200),
+      tiers: [
+        { tierId: 1, label: "GA", priceLamports: new BN(price), maxSupply: 5 },
+        {
+          tierId: 2,
+          label: "Flash sale",
+          priceLamports: new BN(price / 2),
+          maxSupply: 5,
+          refundable: false,
+        },
+      ],
+    });
+    const setDeadline = (deadline: BN | null) =>
+      program.methods
+        .setRefundDeadline(deadline)
+        .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+        .rpc();
+
+    try {
+      await setDeadline(new BN(now + 3601));
+      assert.fail("Expected a deadline after the start to be refused");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidSchedule");
+    }
+    await setDeadline(new BN(now + 1800));
+
+    const general = await mintPass(fixture, 1);
+    const flash = await mintPass(fixture, 2);
+    const walletBefore = await provider.connection.getBalance(general.attendee.publicKey);
+    await refundPass(fixture, general);
+    const walletAfter = await provider.connection.getBalance(general.attendee.publicKey);
+    assert.isAbove(walletAfter - walletBefore, price);
+    assert.isNull(await provider.connection.getAccountInfo(general.eventPassPda));
+
+    try {
+      await refundPass(fixture, flash);
+      assert.fail("Expected the flash-sale tier to be non-refundable");
+    } catch (err: any) {
+      expectAnchorError(err, "TierNonRefundable");
+    }
+
+    await program.methods
+      .cancelEvent()
+      .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+      .rpc();
+    await refundPass(fixture, flash);
+    assert.isNull(await provider.connection.getAccountInfo(flash.eventPassPda));
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.totalPasses.toNumber(), 0);
+  });
+
+  it("cranks refunds for a batch of passes on a cancelled event", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const fixture = await createEventFixture();
+    const first = await mintPass(fixture, 1);
+    const second = await mintPass(fixture, 1);
+    const attended = await mintPass(fixture, 1);
+    const early = await mintPass(fixture, 1);
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: attended.eventPassPda,
+      })
+      .rpc();
+    await program.methods
+      .cancelEvent()
+      .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+      .rpc();
+    await refundPass(fixture, early);
+
+    const crank = (passes: Awaited<ReturnType<typeof mintPass>>[], owners?: PublicKey[]) =>
+      program.methods
+        .refundBatch()
+        .accounts({
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          organizerStats: organizerStatsPda,
+          guestList: fixture.guestListPda,
+        })
+        .remainingAccounts(
+          passes.flatMap((pass, index) => [
+            { pubkey: pass.eventPassPda, isWritable: true, isSigner: false },
+            {
+              pubkey: owners?.[index] ?? pass.attendee.publicKey,
+              isWritable: true,
+              isSigner: false,
+            },
+          ])
+        )
+        .rpc();
+
+    try {
+      await crank([first], [second.attendee.publicKey]);
+      assert.fail("Expected a refund to the wrong wallet to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "PassOwnerMismatch");
+    }
+
+    const balances = () =>
+      Promise.all(
+        [first, second].map((pass) => provider.connection.getBalance(pass.attendee.publicKey))
+      );
+    const before = await balances();
+    // The early pass is already closed and the attended one is skipped; the
+    // duplicate of the first pass finds it closed.
+    await crank([first, second, attended, early, first]);
+    const after = await balances();
+    for (const [index, pass] of [first, second].entries()) {
+      // Price plus the closed pass's rent; the crank paid the fee.
+      assert.isAbove(after[index] - before[index], price);
+      assert.isNull(await provider.connection.getAccountInfo(pass.eventPassPda));
+    }
+    assert.isNotNull(await provider.connection.getAccountInfo(attended.eventPassPda));
+
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.tiers[0].sold, 1);
+    assert.equal(eventAccount.totalPasses.toNumber(), 1);
+    const vault: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vault.totalRefunded.toNumber(), 3 * price);
+    assert.equal(vault.totalDeposited.toNumber(), price);
+
+    try {
+      await crank(new Array(11).fill(attended));
+      assert.fail("Expected an oversized batch to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidBatchSize");
+    }
+  });
+
+  it("lets an owner give an unused pass to another wallet before the event ends", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 8),
+    });
+    const transfer = async (
+      pass: Awaited<ReturnType<typeof mintPass>>,
+      newOwner: PublicKey
+    ) => {
+      const { passIndex }: any = await program.account.eventPass.fetch(pass.eventPassPda);
+      const newPassPda = findEventPassPda(fixture.eventPda, newOwner, passIndex);
+      await program.methods
+        .transferPass(newOwner)
+        .accounts({
+          owner: pass.attendee.publicKey,
+          event: fixture.eventPda,
+          oldPass: pass.eventPassPda,
+          guestList: fixture.guestListPda,
+          newPass: newPassPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([pass.attendee])
+        .rpc();
+      return newPassPda;
+    };
+
+    const gift = await mintPass(fixture, 1);
+    const friend = Keypair.generate().publicKey;
+    const oldPass: any = await program.account.eventPass.fetch(gift.eventPassPda);
+    const newPassPda = await transfer(gift, friend);
+    assert.isNull(await provider.connection.getAccountInfo(gift.eventPassPda));
+    const newPass: any = await program.account.eventPass.fetch(newPassPda);
+    assert.equal(newPass.owner.toBase58(), friend.toBase58());
+    assert.equal(newPass.tierId, oldPass.tierId);
+    assert.equal(newPass.pricePaid.toString(), oldPass.pricePaid.toString());
+    assert.equal(newPass.mintedAt.toString(), oldPass.mintedAt.toString());
+    assert.isFalse(newPass.checkedIn);
+    const guestList: any = await program.account.guestList.fetch(fixture.guestListPda);
+    assert.equal(
+      guestList.passes[oldPass.passIndex.toNumber()].toBase58(),
+      newPassPda.toBase58()
+    );
+
+    const used = await mintPass(fixture, 1);
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: used.eventPassPda,
+      })
+      .rpc();
+    try {
+      await transfer(used, friend);
+      assert.fail("Expected a checked-in pass to stay with its owner");
+    } catch (err: any) {
+      expectAnchorError(err, "AlreadyCheckedIn");
+    }
+
+    const late = await mintPass(fixture, 1);
+    await new Promise((resolve) => setTimeout(resolve, 8500));
+    try {
+      await transfer(late, friend);
+      assert.fail("Expected transfers to close when the event ends");
+    } catch (err: any) {
+      expectAnchorError(err, "EventEnded");
+    }
+  });
+
+  it("caps resales at the tier's anti-scalping markup", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const fixture = await createEventFixture({
+      tiers: [
+        {
+          tierId: 1,
+          label: "Capped",
+          priceLamports: new BN(price),
+          maxSupply: 5,
+          maxResaleBps: 2_000,
+        },
+      ],
+    });
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.tiers[0].maxResaleBps, 2_000);
+
+    const seller = await mintPass(fixture, 1);
+    const buyer = Keypair.generate();
+    await fundWallet(buyer.publicKey);
+    const { passIndex }: any = await program.account.eventPass.fetch(seller.eventPassPda);
+    const newPassPda = findEventPassPda(fixture.eventPda, buyer.publicKey, passIndex);
+    const sell = (amount: number) =>
+      program.methods
+        .transferPassWithPayment(new BN(amount))
+        .accounts({
+          seller: seller.attendee.publicKey,
+          buyer: buyer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          oldPass: seller.eventPassPda,
+          guestList: fixture.guestListPda,
+          newPass: newPassPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller.attendee, buyer])
+        .rpc();
+
+    const ceiling = (price * 12) / 10;
+    try {
+      await sell(ceiling + 1);
+      assert.fail("Expected a resale above the tier markup to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "ResalePriceTooHigh");
+    }
+    await sell(ceiling);
+    const newPass: any = await program.account.eventPass.fetch(newPassPda);
+    assert.equal(newPass.owner.toBase58(), buyer.publicKey.toBase58());
+  });
+
+  it("burns a pass after the event to return its rent", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const price = LAMPORTS_PER_SOL / 10;
+    const deposit = LAMPORTS_PER_SOL / 50;
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 3),
+      tiers: [
+        {
+          tierId: 1,
+          label: "Cup deposit",
+          priceLamports: new BN(price),
+          maxSupply: 5,
+          depositLamports: new BN(deposit),
+        },
+      ],
+    });
+    const pass = await mintPass(fixture, 1);
+    const { passIndex }: any = await program.account.eventPass.fetch(pass.eventPassPda);
+    const burn = () =>
+      program.methods
+        .burnPass()
+        .accounts({
+          owner: pass.attendee.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          organizerStats: organizerStatsPda,
+          guestList: fixture.guestListPda,
+          eventPass: pass.eventPassPda,
+        })
+        .signers([pass.attendee])
+        .rpc();
+
+    try {
+      await burn();
+      assert.fail("Expected burning to wait for the event to end");
+    } catch (err: any) {
+      expectAnchorError(err, "EventNotEnded");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 3500));
+    const rent = await provider.connection.getBalance(pass.eventPassPda);
+    const walletBefore = await provider.connection.getBalance(pass.attendee.publicKey);
+    const statsBefore: any = await program.account.organizerStats.fetch(organizerStatsPda);
+    await burn();
+    const walletAfter = await provider.connection.getBalance(pass.attendee.publicKey);
+    assert.isAbove(walletAfter - walletBefore, rent - 10_000);
+    const statsAfter: any = await program.account.organizerStats.fetch(organizerStatsPda);
+    assert.equal(statsBefore.lifetimePasses.sub(statsAfter.lifetimePasses).toNumber(), 1);
+
+    const rederived = findEventPassPda(fixture.eventPda, pass.attendee.publicKey, passIndex);
+    assert.equal(rederived.toBase58(), pass.eventPassPda.toBase58());
+    assert.isNull(await provider.connection.getAccountInfo(rederived));
+    const guestList: any = await program.account.guestList.fetch(fixture.guestListPda);
+    assert.equal(guestList.passes[0].toBase58(), PublicKey.default.toBase58());
+
+    // The unreclaimed deposit becomes revenue instead of staying held.
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.depositsHeld.toNumber(), 0);
+    assert.equal(vaultState.totalDeposited.toNumber(), price + deposit);
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.totalPasses.toNumber(), 0);
+  });
+
+  it("draws proceeds in stages before settling", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 3),
+    });
+    await mintPass(fixture, 1);
+    await mintPass(fixture, 1);
+    const accounts = {
+      organizer: organizer.publicKey,
+      event: fixture.eventPda,
+      organizerStats: organizerStatsPda,
+      vaultState: fixture.vaultStatePda,
+      destination: organizer.publicKey,
+      vaultTreasury: fixture.vaultTreasuryPda,
+    };
+    const withdraw = (amount: number) =>
+      program.methods.withdrawTreasuryPartial(new BN(amount)).accounts(accounts).rpc();
+
+    try {
+      await withdraw(price);
+      assert.fail("Expected partial withdrawals to wait for the event to end");
+    } catch (err: any) {
+      expectAnchorError(err, "EventNotEnded");
+    }
+    await new Promise((resolve) => setTimeout(resolve, 3500));
+
+    try {
+      await withdraw(0);
+      assert.fail("Expected a zero withdrawal to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "NothingToWithdraw");
+    }
+    try {
+      await withdraw(2 * price + 1);
+      assert.fail("Expected withdrawing into the rent reserve to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "WithdrawalExceedsAvailable");
+    }
+
+    const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    await withdraw(price / 2);
+    await withdraw(price);
+    assert.equal(
+      treasuryBefore - (await provider.connection.getBalance(fixture.vaultTreasuryPda)),
+      (3 * price) / 2
+    );
+    let vault: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vault.totalWithdrawn.toNumber(), (3 * price) / 2);
+    assert.deepEqual(
+      vault.tranches.map((t: any) => t.amount.toNumber()),
+      [price / 2, price]
+    );
+    vault.tranches.forEach((t: any) => {
+      assert.equal(t.destination.toBase58(), organizer.publicKey.toBase58());
+      assert.isAtLeast(t.ts.toNumber(), now);
+    });
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.isFalse(eventAccount.settled);
+
+    await program.methods.withdrawTreasury().accounts(accounts).rpc();
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.isTrue(eventAccount.settled);
+    assert.equal(await provider.connection.getBalance(fixture.vaultTreasuryPda), 0);
+  });
+
+  it("removes a single verifier and frees its slot", async () => {
+    const fixture = await createEventFixture({ maxVerifiers: 2 });
+    const manage = (method: "addVerifier" | "removeVerifier", verifier: PublicKey) =>
+      program.methods[method](verifier)
+        .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+        .rpc();
+    const [first, second, third] = [0, 1, 2].map(() => Keypair.generate().publicKey);
+
+    await manage("addVerifier", first);
+    await manage("addVerifier", second);
+    await manage("removeVerifier", first);
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.deepEqual(
+      eventAccount.authorizedVerifiers.map((v: PublicKey) => v.toBase58()),
+      [second.toBase58()]
+    );
+
+    await manage("addVerifier", third);
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.authorizedVerifiers.length, 2);
+
+    try {
+      await manage("removeVerifier", first);
+      assert.fail("Expected removing an absent verifier to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "VerifierNotFound");
+    }
+  });
+
+  it("corrects a tier's price and supply until it starts selling", async () => {
+    const fixture = await createEventFixture();
+    const newPrice = LAMPORTS_PER_SOL / 5;
+    const update = (price: BN | null, maxSupply: number | null) =>
+      program.methods
+        .updateTier(1, price, maxSupply)
+        .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+        .rpc();
+
+    try {
+      await update(null, 0);
+      assert.fail("Expected zero supply to be refused");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidTierSet");
+    }
+
+    await update(new BN(newPrice), 3);
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.tiers[0].priceLamports.toNumber(), newPrice);
+    assert.equal(eventAccount.tiers[0].maxSupply, 3);
+    assert.equal(eventAccount.tiers[0].label, "VIP");
+
+    const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    const { eventPassPda } = await mintPass(fixture, 1);
+    assert.equal(
+      (await provider.connection.getBalance(fixture.vaultTreasuryPda)) - treasuryBefore,
+      newPrice
+    );
+    const eventPass: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(eventPass.pricePaid.toNumber(), newPrice);
+
+    for (const [price, maxSupply, code] of [
+      [null, 0, "SupplyBelowSold"],
+      [new BN(LAMPORTS_PER_SOL / 10), null, "TierNotEmpty"],
+    ] as [BN | null, number | null, string][]) {
+      try {
+        await update(price, maxSupply);
+        assert.fail(`Expected ${code}`);
+      } catch (err: any) {
+        expectAnchorError(err, code);
+      }
+    }
+  });
+
+  it("pauses and resumes sales without blocking check-in or withdrawal", async () => {
+    const fixture = await createEventFixture();
+    const pass = await mintPass(fixture, 1);
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .setSalesPaused(paused)
+        .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+        .rpc();
+
+    await setPaused(true);
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.isTrue(eventAccount.salesPaused);
+
+    try {
+      await mintPass(fixture, 1);
+      assert.fail("Expected mints to be refused while sales are paused");
+    } catch (err: any) {
+      expectAnchorError(err, "SalesPaused");
+    }
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: pass.eventPassPda,
+      })
+      .rpc();
+    const passAccount: any = await program.account.eventPass.fetch(pass.eventPassPda);
+    assert.isTrue(passAccount.checkedIn);
+
+    await setPaused(false);
+    await mintPass(fixture, 1);
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.isFalse(eventAccount.salesPaused);
+    assert.equal(eventAccount.tiers[0].sold, 2);
+  });
+
+  it("restricts invite-only events to wallets proven against the allowlist", async () => {
+    const sha256 = (...parts: Buffer[]) =>
+      createHash("sha256").update(Buffer.concat(parts)).digest();
+    const invited = Keypair.generate();
+    const alsoInvited = Keypair.generate();
+    const outsider = Keypair.generate();
+    const leaf = sha256(invited.publicKey.toBuffer());
+    const sibling = sha256(alsoInvited.publicKey.toBuffer());
+    const root =
+      Buffer.compare(leaf, sibling) <= 0 ? sha256(leaf, sibling) : sha256(sibling, leaf);
+    const fixture = await createEventFixture({ allowlistRoot: Array.from(root) });
+
+    const mintAllowlisted = async (attendee: Keypair, proof: Buffer[]) => {
+      await fundWallet(attendee.publicKey);
+      const eventPassPda = findEventPassPda(
+        fixture.eventPda,
+        attendee.publicKey,
+        await nextPassIndex(fixture.eventPda)
+      );
+      await program.methods
+        .mintPassAllowlisted(1, new BN(0), proof.map((node) => Array.from(node)))
+        .accounts({
+          attendee: attendee.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          guestList: fixture.guestListPda,
+          eventPass: eventPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollector: null,
+          referrer: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attendee])
+        .rpc();
+      return eventPassPda;
+    };
+
+    try {
+      await mintPass(fixture, 1, invited);
+      assert.fail("Expected the open mint path to be closed");
+    } catch (err: any) {
+      expectAnchorError(err, "NotOnAllowlist");
+    }
+    try {
+      await mintAllowlisted(outsider, [sibling]);
+      assert.fail("Expected a wallet outside the allowlist to be refused");
+    } catch (err: any) {
+      expectAnchorError(err, "NotOnAllowlist");
+    }
+
+    const eventPassPda = await mintAllowlisted(invited, [sibling]);
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(passAccount.owner.toBase58(), invited.publicKey.toBase58());
+  });
+
+  it("caps how many passes one wallet can mint across tiers", async () => {
+    const fixture = await createEventFixture({
+      maxPassesPerWallet: 2,
+      tiers: [
+        { tierId: 1, label: "GA", priceLamports: new BN(LAMPORTS_PER_SOL / 100), maxSupply: 5 },
+        { tierId: 2, label: "VIP", priceLamports: new BN(LAMPORTS_PER_SOL / 50), maxSupply: 5 },
+      ],
+    });
+    const buyer = Keypair.generate();
+
+    await mintPass(fixture, 1, buyer);
+    await mintPass(fixture, 2, buyer);
+    const record: any = await program.account.attendeeRecord.fetch(
+      findAttendeeRecordPda(fixture.eventPda, buyer.publicKey)
+    );
+    assert.equal(record.minted, 2);
+
+    try {
+      await mintPass(fixture, 1, buyer);
+      assert.fail("Expected a third pass to exceed the wallet limit");
+    } catch (err: any) {
+      expectAnchorError(err, "WalletLimitReached");
+    }
+
+    await mintPass(fixture, 1);
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.totalPasses.toNumber(), 3);
+  });
+
+  it("counts custody, credit, door and SPL mints against the wallet cap", async () => {
+    const price = LAMPORTS_PER_SOL / 100;
+    const operator = Keypair.generate();
+    await fundWallet(operator.publicKey);
+    const paymentMint = await createMint(
+      provider.connection,
+      organizer.payer,
+      organizer.publicKey,
+      null,
+      6
+    );
+
+    const paths: [
+      string,
+      Record<string, any>,
+      (
+        fixture: Awaited<ReturnType<typeof createEventFixture>>,
+        buyer: Keypair,
+        eventPass: PublicKey
+      ) => Promise<string>
+    ][] = [
+      [
+        "custody",
+        {},
+        (fixture, buyer, eventPass) =>
+          program.methods
+            .mintPassInCustody(1, new BN(0), buyer.publicKey)
+            .accounts({
+              custodian: operator.publicKey,
+              event: fixture.eventPda,
+              vaultState: fixture.vaultStatePda,
+              vaultTreasury: fixture.vaultTreasuryPda,
+              organizerStats: organizerStatsPda,
+              guestList: fixture.guestListPda,
+              eventPass,
+              attendeeRecord: findAttendeeRecordPda(fixture.eventPda, buyer.publicKey),
+              protocolConfig: protocolConfigPda,
+              feeCollector: null,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([operator])
+            .rpc(),
+      ],
+      [
+        "credit",
+        {},
+        async (fixture, buyer, eventPass) => {
+          const [creditPda] = PublicKey.findProgramAddressSync(
+            [CREDIT_SEED, fixture.eventPda.toBuffer(), buyer.publicKey.toBuffer()],
+            program.programId
+          );
+          await program.methods
+            .depositCredit(new BN(price))
+            .accounts({
+              attendee: buyer.publicKey,
+              event: fixture.eventPda,
+              vaultState: fixture.vaultStatePda,
+              vaultTreasury: fixture.vaultTreasuryPda,
+              creditAccount: creditPda,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([buyer])
+            .rpc();
+          return program.methods
+            .mintPassWithCredit(1, new BN(0))
+            .accounts({
+              attendee: buyer.publicKey,
+              event: fixture.eventPda,
+              vaultState: fixture.vaultStatePda,
+              vaultTreasury: fixture.vaultTreasuryPda,
+              organizerStats: organizerStatsPda,
+              creditAccount: creditPda,
+              guestList: fixture.guestListPda,
+              eventPass,
+              attendeeRecord: findAttendeeRecordPda(fixture.eventPda, buyer.publicKey),
+              protocolConfig: protocolConfigPda,
+              feeCollector: null,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([buyer])
+            .rpc();
+        },
+      ],
+      [
+        "door",
+        { authorizedVerifiers: [operator.publicKey] },
+        (fixture, buyer, eventPass) =>
+          program.methods
+            .payAndCheckin(1)
+            .accounts({
+              operator: operator.publicKey,
+              payer: buyer.publicKey,
+              attendee: buyer.publicKey,
+              event: fixture.eventPda,
+              vaultState: fixture.vaultStatePda,
+              vaultTreasury: fixture.vaultTreasuryPda,
+              organizerStats: organizerStatsPda,
+              guestList: fixture.guestListPda,
+              eventPass,
+              attendeeRecord: findAttendeeRecordPda(fixture.eventPda, buyer.publicKey),
+              protocolConfig: protocolConfigPda,
+              feeCollector: null,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([operator, buyer])
+            .rpc(),
+      ],
+      [
+        "spl",
+        { currency: { spl: { 0: paymentMint } } },
+        async (fixture, buyer, eventPass) => {
+          const buyerTokens = getAssociatedTokenAddressSync(paymentMint, buyer.publicKey);
+          if (!(await provider.connection.getAccountInfo(buyerTokens))) {
+            await createAssociatedTokenAccount(
+              provider.connection,
+              organizer.payer,
+              paymentMint,
+              buyer.publicKey
+            );
+          }
+          await mintTo(
+            provider.connection,
+            organizer.payer,
+            paymentMint,
+            buyerTokens,
+            organizer.payer,
+            price
+          );
+          const [tokenVaultPda] = PublicKey.findProgramAddressSync(
+            [TOKEN_VAULT_SEED, fixture.eventPda.toBuffer()],
+            program.programId
+          );
+          return program.methods
+            .mintPassSpl(1, new BN(0))
+            .accounts({
+              attendee: buyer.publicKey,
+              event: fixture.eventPda,
+              vaultState: fixture.vaultStatePda,
+              vaultTreasury: fixture.vaultTreasuryPda,
+              paymentMint,
+              attendeeTokenAccount: buyerTokens,
+              tokenVault: tokenVaultPda,
+              organizerStats: organizerStatsPda,
+              guestList: fixture.guestListPda,
+              eventPass,
+              attendeeRecord: findAttendeeRecordPda(fixture.eventPda, buyer.publicKey),
+              protocolConfig: protocolConfigPda,
+              feeCollectorTokenAccount: null,
+              tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([buyer])
+            .rpc();
+        },
+      ],
+    ];
+
+    for (const [path, overrides, mint] of paths) {
+      const fixture = await createEventFixture({
+        ...overrides,
+        maxPassesPerWallet: 1,
+        tiers: [{ tierId: 1, label: "GA", priceLamports: new BN(price), maxSupply: 5 }],
+      });
+      const buyer = Keypair.generate();
+      await fundWallet(buyer.publicKey);
+      const nextPass = async () =>
+        findEventPassPda(fixture.eventPda, buyer.publicKey, await nextPassIndex(fixture.eventPda));
+
+      await mint(fixture, buyer, await nextPass());
+      const record: any = await program.account.attendeeRecord.fetch(
+        findAttendeeRecordPda(fixture.eventPda, buyer.publicKey)
+      );
+      assert.equal(record.minted, 1, path);
+      try {
+        await mint(fixture, buyer, await nextPass());
+        assert.fail(`Expected a second ${path} mint to exceed the wallet limit`);
+      } catch (err: any) {
+        expectAnchorError(err, "WalletLimitReached");
+      }
+    }
+  });
+
+  it("checks in same-tier passes held by one wallet independently", async () => {
+    const fixture = await createEventFixture();
+    const attendee = Keypair.generate();
+    const first = await mintPass(fixture, 1, attendee);
+    const second = await mintPass(fixture, 1, attendee);
+    const checkIn = (eventPassPda: PublicKey) =>
+      program.methods
+        .checkIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: eventPassPda,
+        })
+        .rpc();
+
+    await checkIn(first.eventPassPda);
+    let secondPass: any = await program.account.eventPass.fetch(second.eventPassPda);
+    assert.isFalse(secondPass.checkedIn);
+
+    await checkIn(second.eventPassPda);
+    secondPass = await program.account.eventPass.fetch(second.eventPassPda);
+    const firstPass: any = await program.account.eventPass.fetch(first.eventPassPda);
+    assert.isTrue(firstPass.checkedIn);
+    assert.isTrue(secondPass.checkedIn);
+  });
+
+  it("mints passes for SPL-priced events into the event token vault", async () => {
+    const price = 25_000_000;
+    const paymentMint = await createMint(
+      provider.connection,
+      organizer.payer,
+      organizer.publicKey,
+      null,
+      6
+    );
+    const fixture = await createEventFixture({
+      currency: { spl: { 0: paymentMint } },
+      tiers: [{ tierId: 1, label: "GA", priceLamports: new BN(price), maxSupply: 5 }],
+    });
+    const [tokenVaultPda] = PublicKey.findProgramAddressSync(
+      [TOKEN_VAULT_SEED, fixture.eventPda.toBuffer()],
+      program.programId
+    );
+    const attendee = Keypair.generate();
+    await fundWallet(attendee.publicKey);
+
+    const mintWith = async (mint: PublicKey) => {
+      const attendeeTokenAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        organizer.payer,
+        mint,
+        attendee.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        organizer.payer,
+        mint,
+        attendeeTokenAccount,
+        organizer.payer,
+        2 * price
+      );
+      const eventPassPda = findEventPassPda(
+        fixture.eventPda,
+        attendee.publicKey,
+        await nextPassIndex(fixture.eventPda)
+      );
+      await program.methods
+        .mintPassSpl(1, new BN(0))
+        .accounts({
+          attendee: attendee.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          paymentMint: mint,
+          attendeeTokenAccount,
+          tokenVault: tokenVaultPda,
+          organizerStats: organizerStatsPda,
+          guestList: fixture.guestListPda,
+          eventPass: eventPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollectorTokenAccount: null,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attendee])
+        .rpc();
+      return attendeeTokenAccount;
+    };
+
+    const otherMint = await createMint(
+      provider.connection,
+      organizer.payer,
+      organizer.publicKey,
+      null,
+      6
+    );
+    try {
+      await mintWith(otherMint);
+      assert.fail("Expected a payment in the wrong mint to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "CurrencyMismatch");
+    }
+
+    const attendeeTokenAccount = await mintWith(paymentMint);
+    const vault = await getAccount(provider.connection, tokenVaultPda);
+    assert.equal(Number(vault.amount), price);
+    assert.equal(vault.owner.toBase58(), fixture.vaultTreasuryPda.toBase58());
+    const attendeeTokens = await getAccount(provider.connection, attendeeTokenAccount);
+    assert.equal(Number(attendeeTokens.amount), price);
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.totalDeposited.toNumber(), price);
+  });
+
+  it("splits the platform fee to the fee collector on mint", async () => {
+    await ensureProtocolConfig();
+    const price = LAMPORTS_PER_SOL / 10;
+    const feeBps = 500;
+    const fee = (price * feeBps) / 10_000;
+    const collector = Keypair.generate();
+    await fundWallet(collector.publicKey);
+    const setFee = (bps: number, feeCollector: PublicKey) =>
+      program.methods
+        .setProtocolFee(bps, feeCollector)
+        .accounts({ admin: organizer.publicKey, protocolConfig: protocolConfigPda })
+        .rpc();
+
+    await setFee(feeBps, collector.publicKey);
+    try {
+      const fixture = await createEventFixture();
+      const attendee = Keypair.generate();
+      await fundWallet(attendee.publicKey);
+      const mint = async (feeCollector: PublicKey | null) => {
+        const eventPassPda = findEventPassPda(
+          fixture.eventPda,
+          attendee.publicKey,
+          await nextPassIndex(fixture.eventPda)
+        );
+        await program.methods
+          .mintPass(1, new BN(0), null)
+          .accounts({
+            attendee: attendee.publicKey,
+            event: fixture.eventPda,
+            organizerStats: organizerStatsPda,
+            vaultState: fixture.vaultStatePda,
+            vaultTreasury: fixture.vaultTreasuryPda,
+            guestList: fixture.guestListPda,
+            eventPass: eventPassPda,
+            attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+            protocolConfig: protocolConfigPda,
+            feeCollector,
+            referrer: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([attendee])
+          .rpc();
+        return eventPassPda;
+      };
+
+      try {
+        await mint(null);
+        assert.fail("Expected a mint without the fee collector to fail");
+      } catch (err: any) {
+        expectAnchorError(err, "InvalidFeeCollector");
+      }
+
+      const collectorBefore = await provider.connection.getBalance(collector.publicKey);
+      const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+      const eventPassPda = await mint(collector.publicKey);
+      const collectorAfter = await provider.connection.getBalance(collector.publicKey);
+      const treasuryAfter = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+      assert.equal(collectorAfter - collectorBefore, fee);
+      assert.equal(treasuryAfter - treasuryBefore, price - fee);
+
+      const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+      assert.equal(passAccount.pricePaid.toNumber(), price);
+      assert.equal(passAccount.feePaid.toNumber(), fee);
+      const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+      assert.equal(vaultState.totalDeposited.toNumber(), price - fee);
+    } finally {
+      await setFee(0, PublicKey.default);
+    }
+  });
+
+  it("refunds platform fees charged on an event back to its treasury", async () => {
+    await ensureProtocolConfig();
+    const price = LAMPORTS_PER_SOL / 10;
+    const feeBps = 500;
+    const fee = (price * feeBps) / 10_000;
+    const collector = Keypair.generate();
+    await fundWallet(collector.publicKey);
+    const setFee = (bps: number, feeCollector: PublicKey) =>
+      program.methods
+        .setProtocolFee(bps, feeCollector)
+        .accounts({ admin: organizer.publicKey, protocolConfig: protocolConfigPda })
+        .rpc();
+
+    await setFee(feeBps, collector.publicKey);
+    try {
+      const fixture = await createEventFixture();
+      const attendee = Keypair.generate();
+      await fundWallet(attendee.publicKey);
+      const eventPassPda = findEventPassPda(
+        fixture.eventPda,
+        attendee.publicKey,
+        await nextPassIndex(fixture.eventPda)
+      );
+      await program.methods
+        .mintPass(1, new BN(0), null)
+        .accounts({
+          attendee: attendee.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          guestList: fixture.guestListPda,
+          eventPass: eventPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollector: collector.publicKey,
+          referrer: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attendee])
+        .rpc();
+      let vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+      assert.equal(vaultState.feesCollected.toNumber(), fee);
+
+      const refund = (amount: number, admin: Keypair = organizer.payer) =>
+        program.methods
+          .refundFee(new BN(amount))
+          .accounts({
+            admin: admin.publicKey,
+            protocolConfig: protocolConfigPda,
+            feeCollector: collector.publicKey,
+            event: fixture.eventPda,
+            vaultState: fixture.vaultStatePda,
+            vaultTreasury: fixture.vaultTreasuryPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin, collector])
+          .rpc();
+
+      const outsider = Keypair.generate();
+      await fundWallet(outsider.publicKey);
+      try {
+        await refund(fee, outsider);
+        assert.fail("Expected only the protocol admin to refund fees");
+      } catch (err: any) {
+        expectAnchorError(err, "NotProtocolAdmin");
+      }
+      try {
+        await refund(fee + 1);
+        assert.fail("Expected a refund above the collected fees to fail");
+      } catch (err: any) {
+        expectAnchorError(err, "FeeRefundExceedsCollected");
+      }
+
+      const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+      await refund(fee);
+      const treasuryAfter = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+      assert.equal(treasuryAfter - treasuryBefore, fee);
+      vaultState = await program.account.vaultState.fetch(fixture.vaultStatePda);
+      assert.equal(vaultState.feesRefunded.toNumber(), fee);
+      assert.equal(vaultState.totalDeposited.toNumber(), price);
+
+      try {
+        await refund(1);
+        assert.fail("Expected fees to be refundable only once");
+      } catch (err: any) {
+        expectAnchorError(err, "FeeRefundExceedsCollected");
+      }
+    } finally {
+      await setFee(0, PublicKey.default);
+    }
+  });
+
+  it("hands buyers their platform fee back when a cancelled event's fees are refunded", async () => {
+    await ensureProtocolConfig();
+    const price = LAMPORTS_PER_SOL / 10;
+    const feeBps = 500;
+    const fee = (price * feeBps) / 10_000;
+    const collector = Keypair.generate();
+    await fundWallet(collector.publicKey);
+    const setFee = (bps: number, feeCollector: PublicKey) =>
+      program.methods
+        .setProtocolFee(bps, feeCollector)
+        .accounts({ admin: organizer.publicKey, protocolConfig: protocolConfigPda })
+        .rpc();
+
+    await setFee(feeBps, collector.publicKey);
+    try {
+      const fixture = await createEventFixture();
+      const attendee = Keypair.generate();
+      await fundWallet(attendee.publicKey);
+      const eventPassPda = findEventPassPda(
+        fixture.eventPda,
+        attendee.publicKey,
+        await nextPassIndex(fixture.eventPda)
+      );
+      await program.methods
+        .mintPass(1, new BN(0), null)
+        .accounts({
+          attendee: attendee.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          guestList: fixture.guestListPda,
+          eventPass: eventPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollector: collector.publicKey,
+          referrer: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attendee])
+        .rpc();
+      await program.methods
+        .cancelEvent()
+        .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+        .rpc();
+
+      const refundFee = (amount: number) =>
+        program.methods
+          .refundFee(new BN(amount))
+          .accounts({
+            admin: organizer.publicKey,
+            protocolConfig: protocolConfigPda,
+            feeCollector: collector.publicKey,
+            event: fixture.eventPda,
+            vaultState: fixture.vaultStatePda,
+            vaultTreasury: fixture.vaultTreasuryPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([collector])
+          .rpc();
+      try {
+        await refundFee(fee + 1);
+        assert.fail("Expected a clawback above the recorded fees to fail");
+      } catch (err: any) {
+        expectAnchorError(err, "FeeRefundExceedsCollected");
+      }
+      await refundFee(fee);
+      let vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+      assert.equal(vaultState.feeRefundsHeld.toNumber(), fee);
+      assert.equal(vaultState.totalDeposited.toNumber(), price - fee);
+
+      const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+      await refundPass(fixture, { attendee, eventPassPda });
+      const treasuryAfter = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+      assert.equal(treasuryBefore - treasuryAfter, price);
+      vaultState = await program.account.vaultState.fetch(fixture.vaultStatePda);
+      assert.equal(vaultState.totalRefunded.toNumber(), price);
+      assert.equal(vaultState.feeRefundsHeld.toNumber(), 0);
+      assert.equal(vaultState.totalDeposited.toNumber(), 0);
+    } finally {
+      await setFee(0, PublicKey.default);
+    }
+  });
+
+  it("charges the platform fee on custody, door, credit and SPL mints", async () => {
+    await ensureProtocolConfig();
+    const price = LAMPORTS_PER_SOL / 10;
+    const feeBps = 500;
+    const fee = (price * feeBps) / 10_000;
+    const collector = Keypair.generate();
+    await fundWallet(collector.publicKey);
+    const setFee = (bps: number, feeCollector: PublicKey) =>
+      program.methods
+        .setProtocolFee(bps, feeCollector)
+        .accounts({ admin: organizer.publicKey, protocolConfig: protocolConfigPda })
+        .rpc();
+
+    await setFee(feeBps, collector.publicKey);
+    try {
+      const operator = Keypair.generate();
+      await fundWallet(operator.publicKey);
+      const fixture = await createEventFixture({ authorizedVerifiers: [operator.publicKey] });
+      const buyer = Keypair.generate();
+      await fundWallet(buyer.publicKey);
+      const [creditPda] = PublicKey.findProgramAddressSync(
+        [CREDIT_SEED, fixture.eventPda.toBuffer(), buyer.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .depositCredit(new BN(price))
+        .accounts({
+          attendee: buyer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          creditAccount: creditPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const paths: [string, (eventPass: PublicKey) => Promise<string>][] = [
+        [
+          "custody",
+          (eventPass) =>
+            program.methods
+              .mintPassInCustody(1, new BN(0), buyer.publicKey)
+              .accounts({
+                custodian: buyer.publicKey,
+                event: fixture.eventPda,
+                vaultState: fixture.vaultStatePda,
+                vaultTreasury: fixture.vaultTreasuryPda,
+                organizerStats: organizerStatsPda,
+                guestList: fixture.guestListPda,
+                eventPass,
+                attendeeRecord: findAttendeeRecordPda(fixture.eventPda, buyer.publicKey),
+                protocolConfig: protocolConfigPda,
+                feeCollector: collector.publicKey,
+                systemProgram: SystemProgram.programId,
+              })
+              .signers([buyer])
+              .rpc(),
+        ],
+        [
+          "door",
+          (eventPass) =>
+            program.methods
+              .payAndCheckin(1)
+              .accounts({
+                operator: operator.publicKey,
+                payer: buyer.publicKey,
+                attendee: buyer.publicKey,
+                event: fixture.eventPda,
+                vaultState: fixture.vaultStatePda,
+                vaultTreasury: fixture.vaultTreasuryPda,
+                organizerStats: organizerStatsPda,
+                guestList: fixture.guestListPda,
+                eventPass,
+                attendeeRecord: findAttendeeRecordPda(fixture.eventPda, buyer.publicKey),
+                protocolConfig: protocolConfigPda,
+                feeCollector: collector.publicKey,
+                systemProgram: SystemProgram.programId,
+              })
+              .signers([operator, buyer])
+              .rpc(),
+        ],
+        [
+          "credit",
+          (eventPass) =>
+            program.methods
+              .mintPassWithCredit(1, new BN(0))
+              .accounts({
+                attendee: buyer.publicKey,
+                event: fixture.eventPda,
+                vaultState: fixture.vaultStatePda,
+                vaultTreasury: fixture.vaultTreasuryPda,
+                organizerStats: organizerStatsPda,
+                creditAccount: creditPda,
+                guestList: fixture.guestListPda,
+                eventPass,
+                attendeeRecord: findAttendeeRecordPda(fixture.eventPda, buyer.publicKey),
+                protocolConfig: protocolConfigPda,
+                feeCollector: collector.publicKey,
+                systemProgram: SystemProgram.programId,
+              })
+              .signers([buyer])
+              .rpc(),
+        ],
+      ];
+      for (const [path, mint] of paths) {
+        const eventPassPda = findEventPassPda(
+          fixture.eventPda,
+          buyer.publicKey,
+          await nextPassIndex(fixture.eventPda)
+        );
+        const collectorBefore = await provider.connection.getBalance(collector.publicKey);
+        const depositedBefore = (
+          (await program.account.vaultState.fetch(fixture.vaultStatePda)) as any
+        ).totalDeposited.toNumber();
+        await mint(eventPassPda);
+        const collectorAfter = await provider.connection.getBalance(collector.publicKey);
+        assert.equal(collectorAfter - collectorBefore, fee, path);
+        const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+        assert.equal(passAccount.pricePaid.toNumber(), price, path);
+        assert.equal(passAccount.feePaid.toNumber(), fee, path);
+        const depositedAfter = (
+          (await program.account.vaultState.fetch(fixture.vaultStatePda)) as any
+        ).totalDeposited.toNumber();
+        // Credit was booked when deposited, so spending it only takes the
+        // fee back out.
+        const expected = path === "credit" ? -fee : price - fee;
+        assert.equal(depositedAfter - depositedBefore, expected, path);
+      }
+
+      const paymentMint = await createMint(
+        provider.connection,
+        organizer.payer,
+        organizer.publicKey,
+        null,
+        6
+      );
+      const splFixture = await createEventFixture({
+        currency: { spl: { 0: paymentMint } },
+        tiers: [{ tierId: 1, label: "GA", priceLamports: new BN(price), maxSupply: 5 }],
+      });
+      const [tokenVaultPda] = PublicKey.findProgramAddressSync(
+        [TOKEN_VAULT_SEED, splFixture.eventPda.toBuffer()],
+        program.programId
+      );
+      const buyerTokens = await createAssociatedTokenAccount(
+        provider.connection,
+        organizer.payer,
+        paymentMint,
+        buyer.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        organizer.payer,
+        paymentMint,
+        buyerTokens,
+        organizer.payer,
+        price
+      );
+      const collectorTokens = await createAssociatedTokenAccount(
+        provider.connection,
+        organizer.payer,
+        paymentMint,
+        collector.publicKey
+      );
+      const splPassPda = findEventPassPda(
+        splFixture.eventPda,
+        buyer.publicKey,
+        await nextPassIndex(splFixture.eventPda)
+      );
+      await program.methods
+        .mintPassSpl(1, new BN(0))
+        .accounts({
+          attendee: buyer.publicKey,
+          event: splFixture.eventPda,
+          vaultState: splFixture.vaultStatePda,
+          vaultTreasury: splFixture.vaultTreasuryPda,
+          paymentMint,
+          attendeeTokenAccount: buyerTokens,
+          tokenVault: tokenVaultPda,
+          organizerStats: organizerStatsPda,
+          guestList: splFixture.guestListPda,
+          eventPass: splPassPda,
+          attendeeRecord: findAttendeeRecordPda(splFixture.eventPda, buyer.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollectorTokenAccount: collectorTokens,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+      assert.equal(Number((await getAccount(provider.connection, collectorTokens)).amount), fee);
+      assert.equal(
+        Number((await getAccount(provider.connection, tokenVaultPda)).amount),
+        price - fee
+      );
+      const splPass: any = await program.account.eventPass.fetch(splPassPda);
+      assert.equal(splPass.pricePaid.toNumber(), price);
+      assert.equal(splPass.feePaid.toNumber(), fee);
+    } finally {
+      await setFee(0, PublicKey.default);
+    }
+  });
+
+  it("pays the referrer its share of the price and rejects self-referral", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const referralBps = 1_000;
+    const referral = (price * referralBps) / 10_000;
+    const fixture = await createEventFixture({ referralBps });
+    const referrer = Keypair.generate();
+    await fundWallet(referrer.publicKey);
+    const attendee = Keypair.generate();
+    await fundWallet(attendee.publicKey);
+    const mint = async (referrerKey: PublicKey) => {
+      const eventPassPda = findEventPassPda(
+        fixture.eventPda,
+        attendee.publicKey,
+        await nextPassIndex(fixture.eventPda)
+      );
+      await program.methods
+        .mintPass(1, new BN(0), referrerKey)
+        .accounts({
+          attendee: attendee.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          guestList: fixture.guestListPda,
+          eventPass: eventPassPda,
+          attendeeRecord: findAttendeeRecordPda(fixture.eventPda, attendee.publicKey),
+          protocolConfig: protocolConfigPda,
+          feeCollector: null,
+          referrer: referrerKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attendee])
+        .rpc();
+      return eventPassPda;
+    };
+
+    try {
+      await mint(attendee.publicKey);
+      assert.fail("Expected a buyer referring themselves to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "SelfReferral");
+    }
+
+    const referrerBefore = await provider.connection.getBalance(referrer.publicKey);
+    const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    const eventPassPda = await mint(referrer.publicKey);
+    const referrerAfter = await provider.connection.getBalance(referrer.publicKey);
+    const treasuryAfter = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    assert.equal(referrerAfter - referrerBefore, referral);
+    assert.equal(treasuryAfter - treasuryBefore, price - referral);
+    const passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(passAccount.pricePaid.toNumber(), price);
+    assert.equal(passAccount.referralPaid.toNumber(), referral);
+  });
+
+  it("charges the early-bird price until the tier's cutoff", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const price = LAMPORTS_PER_SOL / 10;
+    const earlyPrice = LAMPORTS_PER_SOL / 20;
+    const earlyTier = {
+      tierId: 1,
+      label: "GA",
+      priceLamports: new BN(price),
+      maxSupply: 5,
+      earlyPriceLamports: new BN(earlyPrice),
+      earlyUntilTs: new BN(now + 4),
+    };
+
+    try {
+      await createEventFixture({
+        tiers: [{ ...earlyTier, earlyUntilTs: new BN(now + 7200) }],
+      });
+      assert.fail("Expected an early-bird cutoff after the event to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidTierWindow");
+    }
+
+    const fixture = await createEventFixture({ tiers: [earlyTier] });
+    const early = await mintPass(fixture, 1);
+    let passAccount: any = await program.account.eventPass.fetch(early.eventPassPda);
+    assert.equal(passAccount.pricePaid.toNumber(), earlyPrice);
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+    const late = await mintPass(fixture, 1);
+    passAccount = await program.account.eventPass.fetch(late.eventPassPda);
+    assert.equal(passAccount.pricePaid.toNumber(), price);
+  });
+
+  it("emits events for mint, check-in and settlement", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 10),
+      endTs: new BN(now + 3),
+    });
+    const seen: Record<string, any> = {};
+    const listeners = ["passMinted", "passCheckedIn", "treasurySettled"].map((name) =>
+      program.addEventListener(name as any, (event: any) => {
+        if (event.event.equals(fixture.eventPda)) {
+          seen[name] = event;
+        }
+      })
+    );
+
+    try {
+      const { attendee, eventPassPda } = await mintPass(fixture, 1);
+      await program.methods
+        .checkIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: eventPassPda,
+        })
+        .rpc();
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      await program.methods
+        .withdrawTreasury()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          vaultState: fixture.vaultStatePda,
+          destination: organizer.publicKey,
+          vaultTreasury: fixture.vaultTreasuryPda,
+        })
+        .rpc();
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      assert.equal(seen.passMinted.attendee.toBase58(), attendee.publicKey.toBase58());
+      assert.equal(seen.passMinted.tierId, 1);
+      assert.equal(seen.passMinted.pricePaid.toNumber(), LAMPORTS_PER_SOL / 10);
+      assert.equal(seen.passCheckedIn.pass.toBase58(), eventPassPda.toBase58());
+      assert.equal(seen.passCheckedIn.verifier.toBase58(), organizer.publicKey.toBase58());
+      assert.equal(seen.treasurySettled.destination.toBase58(), organizer.publicKey.toBase58());
+      assert.isAbove(seen.treasurySettled.amount.toNumber(), 0);
+    } finally {
+      for (const listener of listeners) {
+        await program.removeEventListener(listener);
+      }
+    }
+  });
+
+  it("switches an event onto a yield strategy before any harvest", async () => {
+    const fixture = await createEventFixture();
+    await mintPass(fixture, 1);
+    const harvestAmount = LAMPORTS_PER_SOL / 100;
+    await topUpAdapterReserve(fixture, harvestAmount);
+    const switchTo = (strategy: any) =>
+      program.methods
+        .switchYieldStrategy(strategy)
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+        })
+        .rpc();
+
+    try {
+      await switchTo({ none: {} });
+      assert.fail("Expected switching to the current strategy to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "StrategyUnchanged");
+    }
+
+    await switchTo({ kamino: {} });
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.deepEqual(eventAccount.yieldStrategy, { kamino: {} });
+
+    await program.methods
+      .harvestYield(new BN(harvestAmount))
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        adapterReserve: adapterReserveFor(fixture.vaultStatePda),
+        vaultAdapterProgram: vaultStub.programId,
+      })
+      .rpc();
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.deepEqual(vaultState.strategy, { kamino: {} });
+    assert.equal(vaultState.totalYieldHarvested.toNumber(), harvestAmount);
+
+    try {
+      await switchTo({ sanctum: {} });
+      assert.fail("Expected switching after a harvest to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "YieldAlreadyHarvested");
+    }
+  });
+
+  it("rejects a harvest larger than the principal in the vault", async () => {
+    const fixture = await createEventFixture({ yieldStrategy: { kamino: {} } });
+    await mintPass(fixture, 1);
+    const principal = LAMPORTS_PER_SOL / 10;
+    await topUpAdapterReserve(fixture, 2 * principal);
+    const harvest = (amount: number) =>
+      program.methods
+        .harvestYield(new BN(amount))
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          adapterReserve: adapterReserveFor(fixture.vaultStatePda),
+          vaultAdapterProgram: vaultStub.programId,
+        })
+        .rpc();
+
+    try {
+      await harvest(principal + 1);
+      assert.fail("Expected an over-reported harvest to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "HarvestExceedsPrincipal");
+    }
+
+    await harvest(principal);
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.totalYieldHarvested.toNumber(), principal);
+  });
+
+  it("checks passes in on a relayed ed25519 verifier signature", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const verifier = Keypair.generate();
+    const fixture = await createEventFixture({
+      startTs: new BN(now - 600),
+      authorizedVerifiers: [verifier.publicKey],
+    });
+    const { eventPassPda } = await mintPass(fixture, 1);
+    const { passIndex } = (await program.account.eventPass.fetch(eventPassPda)) as any;
+    const submitSigned = (signer: Keypair, ts: number, nonce = 0) =>
+      program.methods
+        .checkInSigned()
+        .accounts({
+          event: fixture.eventPda,
+          eventPass: eventPassPda,
+          organizerStats: organizerStatsPda,
+          instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .preInstructions([
+          Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: signer.secretKey,
+            message: Buffer.concat([
+              eventPassPda.toBuffer(),
+              passIndex.toArrayLike(Buffer, "le", 8),
+              new BN(nonce).toArrayLike(Buffer, "le", 4),
+              new BN(ts).toArrayLike(Buffer, "le", 8),
+            ]),
+          }),
+        ])
+        .rpc();
+
+    try {
+      await submitSigned(Keypair.generate(), now - 300);
+      assert.fail("Expected an unknown signer to be rejected");
+    } catch (err: any) {
+      expectAnchorError(err, "UnauthorizedVerifier");
+    }
+
+    try {
+      await program.methods
+        .checkInSigned()
+        .accounts({
+          event: fixture.eventPda,
+          eventPass: eventPassPda,
+          organizerStats: organizerStatsPda,
+          instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+      assert.fail("Expected a missing signature to be rejected");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidCheckInSignature");
+    }
+
+    // Relayed by the organizer wallet; the verifier never signs the transaction.
+    await submitSigned(verifier, now - 300);
+    const eventPass: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isTrue(eventPass.checkedIn);
+    assert.equal(eventPass.checkedInAt.toNumber(), now - 300);
+
+    try {
+      await submitSigned(verifier, now - 300);
+      assert.fail("Expected the replayed signature to be rejected");
+    } catch (err: any) {
+      expectAnchorError(err, "AlreadyCheckedIn");
+    }
+
+    await program.methods
+      .unsetCheckIn()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        eventPass: eventPassPda,
+        organizerStats: organizerStatsPda,
+      })
+      .rpc();
+    try {
+      await submitSigned(verifier, now - 300);
+      assert.fail("Expected the old signature to be stale after the undo");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidCheckInSignature");
+    }
+    await submitSigned(verifier, now - 200, 1);
+    const rescanned: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.equal(rescanned.checkinNonce, 1);
+    assert.equal(rescanned.checkedInAt.toNumber(), now - 200);
+  });
+
+  it("batch checks in passes and skips ones already admitted", async () => {
+    const fixture = await createEventFixture();
+    const passes: PublicKey[] = [];
+    for (let i = 0; i < 3; i++) {
+      passes.push((await mintPass(fixture, 1)).eventPassPda);
+    }
+    const batch = (pubkeys: PublicKey[]) =>
+      program.methods
+        .batchCheckIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+        })
+        .remainingAccounts(
+          pubkeys.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+        )
+        .rpc();
+
+    try {
+      await batch(Array(21).fill(passes[0]));
+      assert.fail("Expected an oversized batch to be rejected");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidBatchSize");
+    }
+
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: passes[0],
+      })
+      .rpc();
+
+    let summary: any = null;
+    const listener = program.addEventListener("batchCheckedIn", (event: any) => {
+      summary = event;
+    });
+    // The repeated pass is skipped once the batch has admitted it.
+    await batch([...passes, passes[1]]);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+
+    assert.isNotNull(summary);
+    assert.equal(summary.checkedIn, 2);
+    assert.equal(summary.skipped, 2);
+    for (const pass of passes) {
+      const eventPass: any = await program.account.eventPass.fetch(pass);
+      assert.isTrue(eventPass.checkedIn);
+    }
+  });
+
+  it("keeps sales closed until the sale start time", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    try {
+      await createEventFixture({
+        startTs: new BN(now - 600),
+        endTs: new BN(now + 3600),
+        saleStartTs: new BN(now + 7200),
+      });
+      assert.fail("Expected a sale start after the event end to be rejected");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidSchedule");
+    }
+
+    const fixture = await createEventFixture({ saleStartTs: new BN(now + 600) });
+    try {
+      await mintPass(fixture, 1);
+      assert.fail("Expected minting before the sale start to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "SaleNotOpen");
+    }
+
+    const open = await createEventFixture({ saleStartTs: new BN(now - 60) });
+    await mintPass(open, 1);
+    const eventAccount: any = await program.account.event.fetch(open.eventPda);
+    assert.equal(eventAccount.saleStartTs.toNumber(), now - 60);
+    assert.equal(eventAccount.totalPasses.toNumber(), 1);
+  });
+
+  it("deploys idle treasury proceeds into the adapter", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const deposit = (fixture: Awaited<ReturnType<typeof createEventFixture>>, amount: number) =>
+      program.methods
+        .depositToAdapter(new BN(amount))
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          adapterReserve: adapterReserveFor(fixture.vaultStatePda),
+        })
+        .rpc();
+
+    const noStrategy = await createEventFixture();
+    await mintPass(noStrategy, 1);
+    await initializeAdapterReserve(noStrategy);
+    try {
+      await deposit(noStrategy, price / 2);
+      assert.fail("Expected deploying without a strategy to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "NoYieldStrategy");
+    }
+
+    const cancelled = await createEventFixture({ yieldStrategy: { kamino: {} } });
+    await mintPass(cancelled, 1);
+    await initializeAdapterReserve(cancelled);
+    await program.methods
+      .cancelEvent()
+      .accounts({ organizer: organizer.publicKey, event: cancelled.eventPda })
+      .rpc();
+    try {
+      await deposit(cancelled, price / 2);
+      assert.fail("Expected a cancelled event's refund funds to stay in the treasury");
+    } catch (err: any) {
+      expectAnchorError(err, "EventCancelled");
+    }
+
+    const fixture = await createEventFixture({ yieldStrategy: { kamino: {} } });
+    await mintPass(fixture, 1);
+    const adapterReservePda = await initializeAdapterReserve(fixture);
+    // Harvested yield sits in the treasury but isn't idle principal.
+    await topUpAdapterReserve(fixture, 100);
+    await program.methods
+      .harvestYield(new BN(100))
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        adapterReserve: adapterReservePda,
+        vaultAdapterProgram: vaultStub.programId,
+      })
+      .rpc();
+    try {
+      await deposit(fixture, price + 1);
+      assert.fail("Expected deploying more than the idle balance to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidAdapterDeposit");
+    }
+
+    const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    const reserveBefore = await provider.connection.getBalance(adapterReservePda);
+    await deposit(fixture, price);
+
+    assert.equal(
+      await provider.connection.getBalance(fixture.vaultTreasuryPda),
+      treasuryBefore - price
+    );
+    assert.equal(await provider.connection.getBalance(adapterReservePda), reserveBefore + price);
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.deployedPrincipal.toNumber(), price);
+  });
+
+  it("recalls deployed principal before the event can settle", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      yieldStrategy: { kamino: {} },
+      endTs: new BN(now + 4),
+    });
+    await mintPass(fixture, 1);
+    const adapterReserve = await initializeAdapterReserve(fixture);
+    await program.methods
+      .depositToAdapter(new BN(price))
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        adapterReserve,
+      })
+      .rpc();
+
+    const vaultAccounts = {
+      organizer: organizer.publicKey,
+      event: fixture.eventPda,
+      vaultState: fixture.vaultStatePda,
+      vaultTreasury: fixture.vaultTreasuryPda,
+      adapterReserve,
+      vaultAdapterProgram: vaultStub.programId,
+    };
+    try {
+      await program.methods.harvestYield(new BN(price)).accounts(vaultAccounts).rpc();
+      assert.fail("Expected harvesting deployed principal to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "HarvestExceedsYield");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+    const settle = () =>
+      program.methods
+        .withdrawTreasury()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          vaultState: fixture.vaultStatePda,
+          destination: organizer.publicKey,
+          vaultTreasury: fixture.vaultTreasuryPda,
+        })
+        .rpc();
+    try {
+      await settle();
+      assert.fail("Expected settlement to wait for the principal");
+    } catch (err: any) {
+      expectAnchorError(err, "PrincipalStillDeployed");
+    }
+
+    const recall = (amount: number) =>
+      program.methods.recallFromAdapter(new BN(amount)).accounts(vaultAccounts).rpc();
+    try {
+      await recall(price + 1);
+      assert.fail("Expected recalling more than was deployed to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidAdapterRecall");
+    }
+    const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    await recall(price);
+    assert.equal(
+      await provider.connection.getBalance(fixture.vaultTreasuryPda),
+      treasuryBefore + price
+    );
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.deployedPrincipal.toNumber(), 0);
+    assert.equal(vaultState.totalYieldHarvested.toNumber(), 0);
+
+    await settle();
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.isTrue(eventAccount.settled);
+  });
+
+  it("splits principal into idle and deployed balances", async () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    const fixture = await createEventFixture({ yieldStrategy: { kamino: {} } });
+    await mintPass(fixture, 1);
+    await mintPass(fixture, 1);
+    await initializeAdapterReserve(fixture);
+    await program.methods
+      .depositToAdapter(new BN(price))
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        adapterReserve: adapterReserveFor(fixture.vaultStatePda),
+      })
+      .rpc();
+
+    // Deployed principal no longer reads as a treasury deficit.
+    await program.methods
+      .reconcileTreasury()
+      .accounts({
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .rpc();
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.surplusLamports.toNumber(), 0);
+    assert.equal(vaultState.totalDeposited.toNumber(), 2 * price);
+    assert.equal(vaultState.deployedPrincipal.toNumber(), price);
+
+    const breakdown: any = await program.methods
+      .previewSettlement()
+      .accounts({
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .view();
+    assert.equal(breakdown.principal.toNumber(), price);
+  });
+
+  it("withdraws harvested yield without touching principal", async () => {
+    const fixture = await createEventFixture({ yieldStrategy: { kamino: {} } });
+    await mintPass(fixture, 1);
+    await topUpAdapterReserve(fixture, 100);
+    await program.methods
+      .harvestYield(new BN(100))
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        adapterReserve: adapterReserveFor(fixture.vaultStatePda),
+        vaultAdapterProgram: vaultStub.programId,
+      })
+      .rpc();
+
+    const yieldDestination = Keypair.generate();
+    await fundWallet(yieldDestination.publicKey);
+    const withdrawYield = (amount: number) =>
+      program.methods
+        .withdrawYield(new BN(amount))
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+          yieldDestination: yieldDestination.publicKey,
+          organizerStats: organizerStatsPda,
+        })
+        .rpc();
+
+    const destinationBefore = await provider.connection.getBalance(yieldDestination.publicKey);
+    await withdrawYield(60);
+    assert.equal(
+      await provider.connection.getBalance(yieldDestination.publicKey),
+      destinationBefore + 60
+    );
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.yieldWithdrawn.toNumber(), 60);
+    assert.equal(vaultState.totalDeposited.toNumber(), LAMPORTS_PER_SOL / 10);
+
+    try {
+      await withdrawYield(50);
+      assert.fail("Expected withdrawing more than the unclaimed yield to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "InsufficientYield");
+    }
+
+    await program.methods
+      .cancelEvent()
+      .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+      .rpc();
+    try {
+      await withdrawYield(40);
+      assert.fail("Expected a cancelled event's yield to stay behind its refunds");
+    } catch (err: any) {
+      expectAnchorError(err, "EventCancelled");
+    }
+  });
+
+  it("shares harvested yield with checked-in attendees after settlement", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const fixture = await createEventFixture({
+      yieldStrategy: { kamino: {} },
+      endTs: new BN(now + 6),
+      attendeeShareBps: 5_000,
+    });
+    const present = await mintPass(fixture, 1);
+    const absent = await mintPass(fixture, 1);
+    await program.methods
+      .checkIn()
+      .accounts({
+        verifier: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        eventPass: present.eventPassPda,
+      })
+      .rpc();
+    await topUpAdapterReserve(fixture, 1_000);
+    await program.methods
+      .harvestYield(new BN(1_000))
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        adapterReserve: adapterReserveFor(fixture.vaultStatePda),
+        vaultAdapterProgram: vaultStub.programId,
+      })
+      .rpc();
+
+    const claim = (pass: { attendee: Keypair; eventPassPda: PublicKey }) =>
+      program.methods
+        .claimYieldDividend()
+        .accounts({
+          owner: pass.attendee.publicKey,
+          event: fixture.eventPda,
+          vaultState: fixture.vaultStatePda,
+          eventPass: pass.eventPassPda,
+          vaultTreasury: fixture.vaultTreasuryPda,
+        })
+        .signers([pass.attendee])
+        .rpc();
+
+    try {
+      await claim(present);
+      assert.fail("Expected claims to wait for settlement");
+    } catch (err: any) {
+      expectAnchorError(err, "EventNotSettled");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 7000));
+    await program.methods
+      .withdrawTreasury()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        vaultState: fixture.vaultStatePda,
+        destination: organizer.publicKey,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .rpc();
+
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.checkedInCount.toNumber(), 1);
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(vaultState.dividendPerPass.toNumber(), 500);
+
+    try {
+      await claim(absent);
+      assert.fail("Expected a pass that never checked in to be refused");
+    } catch (err: any) {
+      expectAnchorError(err, "PassNotCheckedIn");
+    }
+
+    const treasuryBefore = await provider.connection.getBalance(fixture.vaultTreasuryPda);
+    await claim(present);
+    assert.equal(
+      await provider.connection.getBalance(fixture.vaultTreasuryPda),
+      treasuryBefore - 500
+    );
+    const passAccount: any = await program.account.eventPass.fetch(present.eventPassPda);
+    assert.isTrue(passAccount.dividendClaimed);
+
+    try {
+      await claim(present);
+      assert.fail("Expected a second claim to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "DividendAlreadyClaimed");
+    }
+  });
+
+  it("reissues a pass to its current owner with fresh attendance", async () => {
+    const fixture = await createEventFixture();
+    const { eventPassPda } = await mintPass(fixture, 1);
+    const checkIn = () =>
+      program.methods
+        .checkIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: eventPassPda,
+        })
+        .rpc();
+    await checkIn();
+    const before: any = await program.account.eventPass.fetch(eventPassPda);
+
+    let reissued: any = null;
+    const listener = program.addEventListener("passReissued", (event: any) => {
+      reissued = event;
+    });
+    await program.methods
+      .reissuePassToOwner()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        eventPass: eventPassPda,
+        organizerStats: organizerStatsPda,
+      })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+
+    assert.isNotNull(reissued);
+    assert.equal(reissued.newPass.toBase58(), eventPassPda.toBase58());
+    assert.equal(reissued.newOwner.toBase58(), before.owner.toBase58());
+    const after: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isFalse(after.checkedIn);
+    assert.isNull(after.checkedInAt);
+    assert.equal(after.tierId, before.tierId);
+    assert.equal(after.pricePaid.toNumber(), before.pricePaid.toNumber());
+    // Scans signed before the reset no longer match the pass.
+    assert.equal(after.checkinNonce, before.checkinNonce + 1);
+    let eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.checkedInCount.toNumber(), 0);
+
+    // The reset pass can be scanned again.
+    await checkIn();
+    eventAccount = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.checkedInCount.toNumber(), 1);
+  });
+
+  it("lets the organizer undo a mistaken scan until loyalty is issued", async () => {
+    const fixture = await createEventFixture();
+    const { attendee, eventPassPda } = await mintPass(fixture, 1);
+    const checkIn = () =>
+      program.methods
+        .checkIn()
+        .accounts({
+          verifier: organizer.publicKey,
+          event: fixture.eventPda,
+          organizerStats: organizerStatsPda,
+          eventPass: eventPassPda,
+        })
+        .rpc();
+    const unset = () =>
+      program.methods
+        .unsetCheckIn()
+        .accounts({
+          organizer: organizer.publicKey,
+          event: fixture.eventPda,
+          eventPass: eventPassPda,
+          organizerStats: organizerStatsPda,
+        })
+        .rpc();
+
+    await checkIn();
+    await unset();
+    let passAccount: any = await program.account.eventPass.fetch(eventPassPda);
+    assert.isFalse(passAccount.checkedIn);
+    assert.isNull(passAccount.checkedInAt);
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    assert.equal(eventAccount.checkedInCount.toNumber(), 0);
+
+    await checkIn();
+    const loyaltyMintPda = findLoyaltyMintPda(eventPassPda);
+    await program.methods
+      .issueLoyaltyNft()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        eventPass: eventPassPda,
+        passOwner: attendee.publicKey,
+        loyaltyAuthority: findLoyaltyAuthorityPda(fixture.eventPda),
+        loyaltyMint: loyaltyMintPda,
+        loyaltyTokenAccount: getAssociatedTokenAddressSync(loyaltyMintPda, attendee.publicKey),
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    try {
+      await unset();
+      assert.fail("Expected unsetting a check-in after loyalty issuance to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "LoyaltyAlreadyIssued");
+    }
+    passAccount = await program.account.eventPass.fetch(eventPassPda);
+    assert.isTrue(passAccount.checkedIn);
+  });
+
+  it("rejects events that are already over or run too long", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const year = 365 * 24 * 60 * 60;
+    for (const [schedule, code] of [
+      [{ startTs: new BN(now - 7200), endTs: new BN(now - 3600) }, "EventInPast"],
+      [{ startTs: new BN(now - 60), endTs: new BN(now + year) }, "EventTooLong"],
+    ] as [Record<string, any>, string][]) {
+      try {
+        await createEventFixture(schedule);
+        assert.fail(`Expected ${code}`);
+      } catch (err: any) {
+        expectAnchorError(err, code);
+      }
+    }
+
+    const fixture = await createEventFixture({ startTs: new BN(now - 60) });
+    try {
+      await program.methods
+        .extendEvent(new BN(now + year))
+        .accounts({ organizer: organizer.publicKey, event: fixture.eventPda })
+        .rpc();
+      assert.fail("Expected extending past the maximum duration to fail");
+    } catch (err: any) {
+      expectAnchorError(err, "EventTooLong");
+    }
+  });
+
+  it("rejects a zero or self-referencing settlement treasury", async () => {
+    try {
+      await createEventFixture({ settlementTreasury: PublicKey.default });
+      assert.fail("Expected the zero key to be rejected");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidSettlementTreasury");
+    }
+
+    const eventId = randomEventId();
+    const { vaultTreasuryPda } = findEventPdas(organizer.publicKey, eventId);
+    try {
+      await createEventFixture({ eventId, settlementTreasury: vaultTreasuryPda });
+      assert.fail("Expected the event's own treasury to be rejected");
+    } catch (err: any) {
+      expectAnchorError(err, "InvalidSettlementTreasury");
+    }
+  });
 });
@@ -0,0 +1,328 @@
+import * as anchor from "@coral-xyz/anchor";
+import BN from "bn.js";
+import { assert } from "chai";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+
+const EVENT_SEED = Buffer.from("event");
+const VAULT_STATE_SEED = Buffer.from("vault-state");
+const VAULT_TREASURY_SEED = Buffer.from("vault-treasury");
+const PASS_SEED = Buffer.from("event-pass");
+const ADAPTER_RESERVE_SEED = Buffer.from("adapter-reserve");
+const ORGANIZER_STATS_SEED = Buffer.from("organizer-stats");
+const GUEST_LIST_SEED = Buffer.from("guest-list");
+const ATTENDEE_RECORD_SEED = Buffer.from("attendee-record");
+const PROTOCOL_CONFIG_SEED = Buffer.from("protocol-config");
+
+const TICKET_PRICE = LAMPORTS_PER_SOL / 10;
+
+// Drives the full yield lifecycle (ticket deposits, adapter deposits, harvests
+// and recalls, settlement) and checks the cross-program ledger at every step:
+//   vault_state.total_deposited == adapter principal + treasury principal
+//   vault_state.total_yield_harvested == lamports paid out by the adapter
+describe("vault accounting reconciliation", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.anchorProject as anchor.Program;
+  const vaultStub = anchor.workspace.vaultStub as anchor.Program;
+  const organizer = provider.wallet as anchor.Wallet;
+  const connection = provider.connection;
+
+  const [organizerStatsPda] = PublicKey.findProgramAddressSync(
+    [ORGANIZER_STATS_SEED, organizer.publicKey.toBuffer()],
+    program.programId
+  );
+
+  const fundWallet = async (pubkey: PublicKey, amount = 2 * LAMPORTS_PER_SOL) => {
+    const sig = await connection.requestAirdrop(pubkey, amount);
+    await connection.confirmTransaction(sig);
+  };
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  let treasuryRent: number;
+
+  before(async () => {
+    await fundWallet(organizer.publicKey, 4 * LAMPORTS_PER_SOL);
+    treasuryRent = await connection.getMinimumBalanceForRentExemption(0);
+  });
+
+  const createEvent = async (endOffsetSecs: number) => {
+    const now = Math.floor(Date.now() / 1000);
+    const eventId = new BN(Date.now() + Math.floor(Math.random() * 1_000));
+    const [eventPda] = PublicKey.findProgramAddressSync(
+      [EVENT_SEED, organizer.publicKey.toBuffer(), eventId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [vaultStatePda] = PublicKey.findProgramAddressSync(
+      [VAULT_STATE_SEED, eventPda.toBuffer()],
+      program.programId
+    );
+    const [vaultTreasuryPda] = PublicKey.findProgramAddressSync(
+      [VAULT_TREASURY_SEED, eventPda.toBuffer()],
+      program.programId
+    );
+    const [guestListPda] = PublicKey.findProgramAddressSync(
+      [GUEST_LIST_SEED, eventPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .createEvent({
+        eventId,
+        name: "Yield Ledger",
+        venue: "Reconciliation Hall",
+        startTs: new BN(now - 60),
+        endTs: new BN(now + endOffsetSecs),
+        settlementTreasury: organizer.publicKey,
+        yieldStrategy: { kamino: {} },
+        authorizedVerifiers: [],
+        tiers: [
+          {
+            tierId: 1,
+            label: "GA",
+            priceLamports: new BN(TICKET_PRICE),
+            maxSupply: 10,
+          },
+        ],
+        strictTierOrder: false,
+        authProgram: null,
+        loyaltyRequiresCheckin: true,
+        currency: { sol: {} },
+        tiersFinalized: true,
+        metadataHash: Array(32).fill(0),
+        loyaltyDelaySecs: new BN(0),
+        maxReentries: 0,
+        maxVerifiers: 5,
+        resaleRoyaltyBps: 0,
+        maxResaleLamports: null,
+        allowCpi: false,
+        imageUri: null,
+        thumbnailUri: null,
+        allowlistRoot: null,
+        maxPassesPerWallet: 0,
+        referralBps: 0,
+        saleStartTs: new BN(0),
+        attendeeShareBps: 0,
+      } as any)
+      .accounts({
+        organizer: organizer.publicKey,
+        event: eventPda,
+        organizerStats: organizerStatsPda,
+        vaultState: vaultStatePda,
+        guestList: guestListPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .initializeEventTreasury()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: eventPda,
+        vaultState: vaultStatePda,
+        vaultTreasury: vaultTreasuryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const [adapterReservePda] = PublicKey.findProgramAddressSync(
+      [ADAPTER_RESERVE_SEED, vaultStatePda.toBuffer()],
+      vaultStub.programId
+    );
+    await program.methods
+      .initializeAdapterReserve()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: eventPda,
+        vaultState: vaultStatePda,
+        adapterReserve: adapterReservePda,
+        vaultAdapterProgram: vaultStub.programId,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return { eventPda, vaultStatePda, vaultTreasuryPda, guestListPda, adapterReservePda };
+  };
+
+  const mintPass = async (fixture: Awaited<ReturnType<typeof createEvent>>) => {
+    const attendee = Keypair.generate();
+    await fundWallet(attendee.publicKey);
+    const eventAccount: any = await program.account.event.fetch(fixture.eventPda);
+    const [eventPassPda] = PublicKey.findProgramAddressSync(
+      [
+        PASS_SEED,
+        fixture.eventPda.toBuffer(),
+        attendee.publicKey.toBuffer(),
+        eventAccount.nextPassIndex.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .mintPass(1, new BN(0), null)
+      .accounts({
+        attendee: attendee.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        guestList: fixture.guestListPda,
+        eventPass: eventPassPda,
+        attendeeRecord: PublicKey.findProgramAddressSync(
+          [ATTENDEE_RECORD_SEED, fixture.eventPda.toBuffer(), attendee.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        protocolConfig: PublicKey.findProgramAddressSync([PROTOCOL_CONFIG_SEED], program.programId)[0],
+        feeCollector: null,
+        referrer: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([attendee])
+      .rpc();
+  };
+
+  const adapterAccounts = (fixture: Awaited<ReturnType<typeof createEvent>>) => ({
+    organizer: organizer.publicKey,
+    event: fixture.eventPda,
+    vaultState: fixture.vaultStatePda,
+    vaultTreasury: fixture.vaultTreasuryPda,
+    adapterReserve: fixture.adapterReservePda,
+    vaultAdapterProgram: vaultStub.programId,
+  });
+
+  const harvest = (fixture: Awaited<ReturnType<typeof createEvent>>, amount: number) =>
+    program.methods.harvestYield(new BN(amount)).accounts(adapterAccounts(fixture)).rpc();
+
+  const recall = (fixture: Awaited<ReturnType<typeof createEvent>>, amount: number) =>
+    program.methods.recallFromAdapter(new BN(amount)).accounts(adapterAccounts(fixture)).rpc();
+
+  const settle = (fixture: Awaited<ReturnType<typeof createEvent>>) =>
+    program.methods
+      .withdrawTreasury()
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        organizerStats: organizerStatsPda,
+        vaultState: fixture.vaultStatePda,
+        destination: organizer.publicKey,
+        vaultTreasury: fixture.vaultTreasuryPda,
+      })
+      .rpc();
+
+  // Principal is either deployed to the adapter or still in the treasury on
+  // top of its rent reserve and harvested yield.
+  const assertLedger = async (fixture: Awaited<ReturnType<typeof createEvent>>) => {
+    const vaultState: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    const treasuryBalance = await connection.getBalance(fixture.vaultTreasuryPda);
+    const adapterPrincipal = vaultState.deployedPrincipal.toNumber();
+    const unclaimedYield =
+      vaultState.totalYieldHarvested.toNumber() - vaultState.yieldWithdrawn.toNumber();
+    const treasuryPrincipal = treasuryBalance - treasuryRent - unclaimedYield;
+
+    assert.equal(
+      vaultState.totalDeposited.toNumber(),
+      adapterPrincipal + treasuryPrincipal
+    );
+    return vaultState;
+  };
+
+  it("keeps principal and yield reconciled through harvest and settlement", async () => {
+    const fixture = await createEvent(6);
+    await assertLedger(fixture);
+
+    await mintPass(fixture);
+    await mintPass(fixture);
+    const afterMint = await assertLedger(fixture);
+    assert.equal(afterMint.totalDeposited.toNumber(), 2 * TICKET_PRICE);
+
+    // Yield accrues to the event's reserve as a plain transfer.
+    const harvestAmount = LAMPORTS_PER_SOL / 50;
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: organizer.publicKey,
+          toPubkey: fixture.adapterReservePda,
+          lamports: harvestAmount,
+        })
+      )
+    );
+
+    const reserveBefore = await connection.getBalance(fixture.adapterReservePda);
+    await harvest(fixture, harvestAmount);
+    const reserveAfter = await connection.getBalance(fixture.adapterReservePda);
+
+    const afterHarvest = await assertLedger(fixture);
+    assert.equal(afterHarvest.totalYieldHarvested.toNumber(), reserveBefore - reserveAfter);
+    assert.equal(afterHarvest.totalYieldHarvested.toNumber(), harvestAmount);
+
+    await sleep(7000);
+
+    const treasuryBefore = await connection.getBalance(fixture.vaultTreasuryPda);
+    await settle(fixture);
+
+    const settled: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(settled.totalWithdrawn.toNumber(), treasuryBefore);
+    assert.equal(
+      settled.totalWithdrawn.toNumber(),
+      settled.totalDeposited.toNumber() +
+        settled.totalYieldHarvested.toNumber() +
+        treasuryRent
+    );
+  });
+
+  it("never books deployed principal as yield and recalls it before settling", async () => {
+    const fixture = await createEvent(6);
+    await mintPass(fixture);
+    await mintPass(fixture);
+
+    await program.methods
+      .depositToAdapter(new BN(TICKET_PRICE))
+      .accounts({
+        organizer: organizer.publicKey,
+        event: fixture.eventPda,
+        vaultState: fixture.vaultStatePda,
+        vaultTreasury: fixture.vaultTreasuryPda,
+        adapterReserve: fixture.adapterReservePda,
+      })
+      .rpc();
+    const before = await assertLedger(fixture);
+    assert.equal(before.deployedPrincipal.toNumber(), TICKET_PRICE);
+
+    try {
+      await harvest(fixture, TICKET_PRICE);
+      assert.fail("Expected the principal-only reserve to reject the harvest");
+    } catch (err: any) {
+      const anchorErr = anchor.AnchorError.parse(err.logs ?? []);
+      assert.equal(anchorErr?.error.errorCode.code, "HarvestExceedsYield");
+    }
+    const afterHarvest = await assertLedger(fixture);
+    assert.equal(afterHarvest.totalYieldHarvested.toNumber(), 0);
+
+    await sleep(7000);
+    try {
+      await settle(fixture);
+      assert.fail("Expected settlement to wait for the deployed principal");
+    } catch (err: any) {
+      const anchorErr = anchor.AnchorError.parse(err.logs ?? []);
+      assert.equal(anchorErr?.error.errorCode.code, "PrincipalStillDeployed");
+    }
+
+    await recall(fixture, TICKET_PRICE);
+    const recalled = await assertLedger(fixture);
+    assert.equal(recalled.deployedPrincipal.toNumber(), 0);
+
+    await settle(fixture);
+    const settled: any = await program.account.vaultState.fetch(fixture.vaultStatePda);
+    assert.equal(
+      settled.totalWithdrawn.toNumber(),
+      settled.totalDeposited.toNumber() + treasuryRent
+    );
+  });
+});